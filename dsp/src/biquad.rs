@@ -0,0 +1,237 @@
+//! Audio EQ biquads from Robert Bristow-Johnson's cookbook, retunable while
+//! running.
+//!
+//! Coefficients are computed in f32 from center or corner frequency, Q, gain
+//! and sample rate so they are cheap enough to recompute on the board when a
+//! button is pressed. The filter runs in direct form I, which keeps the actual
+//! past inputs and outputs as state. Those stay meaningful when the
+//! coefficients change, unlike the internal states of the transposed forms, so
+//! retuning doesn't produce a click. On top of that [`Biquad::retune`] can
+//! ramp the coefficients linearly over a number of samples. The stability
+//! region of (a1, a2) is a triangle, which is convex, so every step between
+//! two stable filters is stable too.
+
+use crate::Complex32;
+use core::f32::consts::PI;
+
+/// Normalized biquad coefficients, a0 is divided out
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Coefficients {
+    pub b: [f32; 3],
+    pub a: [f32; 2],
+}
+
+impl Coefficients {
+    /// Passes everything through untouched
+    pub const IDENTITY: Coefficients = Coefficients {
+        b: [1.0, 0.0, 0.0],
+        a: [0.0, 0.0],
+    };
+
+    fn normalized(b: [f32; 3], a: [f32; 3]) -> Self {
+        Self {
+            b: [b[0] / a[0], b[1] / a[0], b[2] / a[0]],
+            a: [a[1] / a[0], a[2] / a[0]],
+        }
+    }
+
+    /// |H| at w radians per sample
+    pub fn gain(&self, w: f32) -> f32 {
+        let z1 = Complex32::new(libm::cosf(w), -libm::sinf(w));
+        let z2 = z1 * z1;
+        let num = z1 * self.b[1] + z2 * self.b[2] + self.b[0];
+        let den = z1 * self.a[0] + z2 * self.a[1] + 1.0;
+        libm::sqrtf(num.norm_sqr() / den.norm_sqr())
+    }
+
+    /// The largest |H| from DC to Nyquist, looking at `points` frequencies
+    /// spread evenly over it. How much headroom the input needs so the
+    /// output doesn't clip.
+    pub fn peak_gain(&self, points: usize) -> f32 {
+        (0..points)
+            .map(|k| self.gain(PI * k as f32 / (points - 1).max(1) as f32))
+            .fold(0.0, f32::max)
+    }
+
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        let mix = |from: f32, to: f32| from + (to - from) * t;
+        Self {
+            b: [
+                mix(self.b[0], to.b[0]),
+                mix(self.b[1], to.b[1]),
+                mix(self.b[2], to.b[2]),
+            ],
+            a: [mix(self.a[0], to.a[0]), mix(self.a[1], to.a[1])],
+        }
+    }
+}
+
+impl From<&crate::sos::Section> for Coefficients {
+    fn from(s: &crate::sos::Section) -> Self {
+        let b = [s.b[0] as f32, s.b[1] as f32, s.b[2] as f32];
+        let a = [s.a[0] as f32, s.a[1] as f32, s.a[2] as f32];
+        Self::normalized(b, a)
+    }
+}
+
+/// Cookbook filter types, shelf and peaking carry their gain in dB
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    LowPass,
+    HighPass,
+    /// constant 0dB peak gain
+    BandPass,
+    Notch,
+    AllPass,
+    Peaking(f32),
+    LowShelf(f32),
+    HighShelf(f32),
+}
+
+/// Everything needed to compute a cookbook filter
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Params {
+    pub kind: Kind,
+    /// center, corner or shelf midpoint frequency in Hz
+    pub f0: f32,
+    pub q: f32,
+    /// sample rate in Hz
+    pub fs: f32,
+}
+
+impl Params {
+    pub fn coefficients(&self) -> Coefficients {
+        let w0 = 2.0 * PI * self.f0 / self.fs;
+        let (sin, cos) = (libm::sinf(w0), libm::cosf(w0));
+        let alpha = sin / (2.0 * self.q);
+        let amp = |gain_db: f32| libm::powf(10.0, gain_db / 40.0);
+
+        let (b, a) = match self.kind {
+            Kind::LowPass => (
+                [(1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0],
+                [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+            ),
+            Kind::HighPass => (
+                [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0],
+                [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+            ),
+            Kind::BandPass => ([alpha, 0.0, -alpha], [1.0 + alpha, -2.0 * cos, 1.0 - alpha]),
+            Kind::Notch => (
+                [1.0, -2.0 * cos, 1.0],
+                [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+            ),
+            Kind::AllPass => (
+                [1.0 - alpha, -2.0 * cos, 1.0 + alpha],
+                [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+            ),
+            Kind::Peaking(gain_db) => {
+                let a = amp(gain_db);
+                (
+                    [1.0 + alpha * a, -2.0 * cos, 1.0 - alpha * a],
+                    [1.0 + alpha / a, -2.0 * cos, 1.0 - alpha / a],
+                )
+            }
+            Kind::LowShelf(gain_db) => {
+                let a = amp(gain_db);
+                let sq = 2.0 * libm::sqrtf(a) * alpha;
+                (
+                    [
+                        a * ((a + 1.0) - (a - 1.0) * cos + sq),
+                        2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                        a * ((a + 1.0) - (a - 1.0) * cos - sq),
+                    ],
+                    [
+                        (a + 1.0) + (a - 1.0) * cos + sq,
+                        -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                        (a + 1.0) + (a - 1.0) * cos - sq,
+                    ],
+                )
+            }
+            Kind::HighShelf(gain_db) => {
+                let a = amp(gain_db);
+                let sq = 2.0 * libm::sqrtf(a) * alpha;
+                (
+                    [
+                        a * ((a + 1.0) + (a - 1.0) * cos + sq),
+                        -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                        a * ((a + 1.0) + (a - 1.0) * cos - sq),
+                    ],
+                    [
+                        (a + 1.0) - (a - 1.0) * cos + sq,
+                        2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                        (a + 1.0) - (a - 1.0) * cos - sq,
+                    ],
+                )
+            }
+        };
+
+        Coefficients::normalized(b, a)
+    }
+}
+
+/// Direct form I biquad with coefficient ramping
+#[derive(Clone, Debug)]
+pub struct Biquad {
+    coeffs: Coefficients,
+    from: Coefficients,
+    to: Coefficients,
+    ramp: u32,
+    ramp_len: u32,
+    x: [f32; 2],
+    y: [f32; 2],
+}
+
+impl Biquad {
+    pub fn new(coeffs: Coefficients) -> Self {
+        Self {
+            coeffs,
+            from: coeffs,
+            to: coeffs,
+            ramp: 0,
+            ramp_len: 0,
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+
+    /// Coefficients in use right now, somewhere between the old and new ones
+    /// while ramping
+    pub fn coefficients(&self) -> Coefficients {
+        self.coeffs
+    }
+
+    /// Move to new coefficients over `samples` samples, 0 switches at once.
+    /// Retuning in the middle of a ramp starts the new ramp from wherever the
+    /// old one got to.
+    pub fn retune(&mut self, to: Coefficients, samples: u32) {
+        self.from = self.coeffs;
+        self.to = to;
+        self.ramp_len = samples;
+        self.ramp = 0;
+        if samples == 0 {
+            self.coeffs = to;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.x = [0.0; 2];
+        self.y = [0.0; 2];
+    }
+
+    pub fn process(&mut self, x: f32) -> f32 {
+        if self.ramp < self.ramp_len {
+            self.ramp += 1;
+            let t = self.ramp as f32 / self.ramp_len as f32;
+            self.coeffs = self.from.lerp(&self.to, t);
+        }
+
+        let c = &self.coeffs;
+        let y = c.b[0] * x + c.b[1] * self.x[0] + c.b[2] * self.x[1]
+            - c.a[0] * self.y[0]
+            - c.a[1] * self.y[1];
+
+        self.x = [x, self.x[0]];
+        self.y = [y, self.y[0]];
+        y
+    }
+}
@@ -0,0 +1,240 @@
+//! Real and complex cepstrum, liftering and minimum phase.
+//!
+//! The cepstrum is the inverse FFT of the log of the spectrum. Convolution
+//! multiplies spectra, the log turns that into a sum, so a signal convolved
+//! with something becomes the two cepstra added together, and they often
+//! land in different places. An echo of delay d multiplies the spectrum by
+//! 1 + a e^(-jwd), a ripple in frequency that turns into spikes at multiples
+//! of d. A pulse train of period P through a resonance is the resonance's
+//! smooth spectrum times a comb of harmonics, and the comb is a spike at P. The
+//! x axis of a cepstrum is in samples, called quefrency.
+//!
+//! - [`real`] takes the log magnitude only. It can't be turned back into the
+//!   signal but it's all that's needed to find echoes and pitch, [`peak`] and
+//!   [`pitch`] look for the spike.
+//! - [`complex`] keeps the unwrapped phase too, less the linear phase of
+//!   whatever delay the signal has and the sign, and [`inverse`] turns it
+//!   back.
+//! - [`lifter`] keeps only the low or high quefrencies, separating the smooth
+//!   envelope from the echoes or harmonics.
+//! - [`minimum_phase`] folds the real cepstrum onto positive quefrencies,
+//!   which gives the minimum phase signal with the same magnitude spectrum,
+//!   the one with its energy as early as it can be.
+//!
+//! Everything goes through N point complex FFTs from backend B, with the
+//! signal zero padded to N, so it can't be longer than N. The cepstrum of a
+//! finite signal goes on forever and wraps round, so N wants to be a few times
+//! longer than the signal.
+//!
+//! ```
+//! use dsp::cepstrum::{peak, real};
+//! use dsp::fft::Microfft;
+//! use heapless::consts::U256;
+//!
+//! // a click and its echo 40 samples later
+//! let mut x = [0.0f32; 100];
+//! x[0] = 1.0;
+//! x[40] = 0.5;
+//! let c = real::<Microfft, U256>(&x);
+//! assert_eq!(peak(&c, 10..128), 40);
+//! ```
+
+use crate::fft::Cfft;
+use crate::spectrum::unwrap;
+use crate::Complex32;
+use core::f32::consts::PI;
+use core::ops::Range;
+use heapless::{ArrayLength, Vec};
+
+/// Which quefrencies [`lifter`] keeps
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Lifter {
+    /// Below this many samples, the smooth spectral envelope
+    Low(usize),
+    /// From this many samples up, echoes and harmonics
+    High(usize),
+}
+
+/// A complex cepstrum and what was taken out of the signal first
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComplexCepstrum<N: ArrayLength<f32>> {
+    pub cepstrum: Vec<f32, N>,
+    /// Samples of delay taken out as linear phase, how late the signal
+    /// starts plus one for every zero outside the unit circle
+    pub delay: i32,
+    /// -1 if the signal sums to less than 0, which would put pi of phase at
+    /// DC that can't be unwrapped away
+    pub sign: f32,
+}
+
+/// Nothing is quieter than this, so silence doesn't take the log of 0
+const FLOOR: f32 = 1e-20;
+
+fn log(z: Complex32) -> f32 {
+    0.5 * libm::logf(z.norm_sqr().max(FLOOR))
+}
+
+fn spectrum<B: Cfft<N>, N: ArrayLength<Complex32>>(x: &[f32]) -> Vec<Complex32, N> {
+    assert!(x.len() <= N::to_usize(), "x is longer than N");
+    let mut z = x
+        .iter()
+        .map(|x| Complex32::new(*x, 0.0))
+        .collect::<Vec<Complex32, N>>();
+    B::cfft(&mut z);
+    z
+}
+
+fn real_part<N: ArrayLength<Complex32> + ArrayLength<f32>>(z: &[Complex32]) -> Vec<f32, N> {
+    z.iter().map(|z| z.re).collect()
+}
+
+/// e^z for the log spectrum, back to a spectrum
+fn exp(z: Complex32) -> Complex32 {
+    let (sin, cos) = libm::sincosf(z.im);
+    Complex32::new(cos, sin) * libm::expf(z.re)
+}
+
+/// Real cepstrum of x, the inverse FFT of log |X|. x can't be longer than N.
+pub fn real<B, N>(x: &[f32]) -> Vec<f32, N>
+where
+    B: Cfft<N>,
+    N: ArrayLength<Complex32> + ArrayLength<f32>,
+{
+    let mut z = spectrum::<B, N>(x);
+    z.iter_mut().for_each(|z| *z = Complex32::new(log(*z), 0.0));
+    B::icfft(&mut z);
+    real_part(&z)
+}
+
+/// Complex cepstrum of x, the inverse FFT of log |X| + j arg X with the phase
+/// unwrapped. The linear phase of a delay would swamp everything else so it's
+/// taken out first, and kept with the sign for [`inverse`]. x can't be longer
+/// than N.
+pub fn complex<B, N>(x: &[f32]) -> ComplexCepstrum<N>
+where
+    B: Cfft<N>,
+    N: ArrayLength<Complex32> + ArrayLength<f32>,
+{
+    let n = N::to_usize();
+    let mut z = spectrum::<B, N>(x);
+    let sign = if z[0].re < 0.0 { -1.0 } else { 1.0 };
+    z.iter_mut().for_each(|z| *z *= sign);
+    let mut phase = z
+        .iter()
+        .map(|z| libm::atan2f(z.im, z.re))
+        .collect::<Vec<f32, N>>();
+    unwrap(&mut phase);
+
+    // the phase at Nyquist is a whole number of pi for a real signal, -pi for
+    // every sample of delay
+    let half = n / 2;
+    let delay = -libm::roundf(phase[half] / PI) as i32;
+    for (k, (z, phase)) in z.iter_mut().zip(phase.iter()).enumerate() {
+        let linear = -PI * delay as f32 * k as f32 / half as f32;
+        *z = Complex32::new(log(*z), phase - linear);
+    }
+    B::icfft(&mut z);
+    ComplexCepstrum {
+        cepstrum: real_part(&z),
+        delay,
+        sign,
+    }
+}
+
+/// The signal back from its [`complex`] cepstrum
+pub fn inverse<B, N>(c: &ComplexCepstrum<N>) -> Vec<f32, N>
+where
+    B: Cfft<N>,
+    N: ArrayLength<Complex32> + ArrayLength<f32>,
+{
+    let half = (N::to_usize() / 2) as f32;
+    let delay = c.delay;
+    let mut z = c
+        .cepstrum
+        .iter()
+        .map(|c| Complex32::new(*c, 0.0))
+        .collect::<Vec<Complex32, N>>();
+    B::cfft(&mut z);
+    for (k, z) in z.iter_mut().enumerate() {
+        let linear = -PI * delay as f32 * k as f32 / half;
+        *z = exp(Complex32::new(z.re, z.im + linear)) * c.sign;
+    }
+    B::icfft(&mut z);
+    real_part(&z)
+}
+
+/// Zero the quefrencies the lifter doesn't keep. Quefrency n and N - n are
+/// the same distance from 0 so they're kept or dropped together.
+pub fn lifter(c: &mut [f32], lifter: Lifter) {
+    let len = c.len();
+    for (n, c) in c.iter_mut().enumerate() {
+        let quefrency = n.min(len - n);
+        let keep = match lifter {
+            Lifter::Low(cutoff) => quefrency < cutoff,
+            Lifter::High(cutoff) => quefrency >= cutoff,
+        };
+        if !keep {
+            *c = 0.0;
+        }
+    }
+}
+
+/// The minimum phase signal with the same magnitude spectrum as x, from its
+/// real cepstrum folded onto the positive quefrencies. x can't be longer than
+/// N.
+pub fn minimum_phase<B, N>(x: &[f32]) -> Vec<f32, N>
+where
+    B: Cfft<N>,
+    N: ArrayLength<Complex32> + ArrayLength<f32>,
+{
+    let n = N::to_usize();
+    let c = real::<B, N>(x);
+    let mut z = c
+        .iter()
+        .enumerate()
+        .map(|(k, c)| {
+            let fold = if k == 0 || k == n / 2 {
+                1.0
+            } else if k < n / 2 {
+                2.0
+            } else {
+                0.0
+            };
+            Complex32::new(c * fold, 0.0)
+        })
+        .collect::<Vec<Complex32, N>>();
+    B::cfft(&mut z);
+    z.iter_mut().for_each(|z| *z = exp(*z));
+    B::icfft(&mut z);
+    real_part(&z)
+}
+
+/// Quefrency of the biggest value of c in the range, an echo delay or pitch
+/// period in samples
+pub fn peak(c: &[f32], quefrencies: Range<usize>) -> usize {
+    let start = quefrencies.start;
+    c[quefrencies]
+        .iter()
+        .enumerate()
+        .fold((start, f32::MIN), |(best, max), (n, c)| {
+            if *c > max {
+                (start + n, *c)
+            } else {
+                (best, max)
+            }
+        })
+        .0
+}
+
+/// Pitch in Hz from the peak of a cepstrum at sample rate fs, looking between
+/// `lowest` and `highest` Hz. Quefrency 0 is the overall level and never
+/// counts, however high `highest` is. None if even the shortest period for
+/// `highest` doesn't fit in the first half of c.
+pub fn pitch(c: &[f32], fs: f32, lowest: f32, highest: f32) -> Option<f32> {
+    let shortest = (libm::floorf(fs / highest) as usize).max(1);
+    let longest = (libm::ceilf(fs / lowest) as usize).min(c.len() / 2);
+    if shortest > longest {
+        return None;
+    }
+    Some(fs / peak(c, shortest..longest + 1) as f32)
+}
@@ -0,0 +1,45 @@
+//! num-complex only has the transcendental functions with std, so these fill
+//! in the few we need via libm.
+
+use crate::Complex64;
+
+pub fn abs(z: Complex64) -> f64 {
+    libm::hypot(z.re, z.im)
+}
+
+pub fn from_polar(r: f64, theta: f64) -> Complex64 {
+    Complex64::new(r * libm::cos(theta), r * libm::sin(theta))
+}
+
+pub fn exp(z: Complex64) -> Complex64 {
+    from_polar(libm::exp(z.re), z.im)
+}
+
+/// principal square root
+pub fn sqrt(z: Complex64) -> Complex64 {
+    if z.im == 0.0 {
+        if z.re >= 0.0 {
+            Complex64::new(libm::sqrt(z.re), 0.0)
+        } else {
+            Complex64::new(0.0, libm::sqrt(-z.re))
+        }
+    } else {
+        let r = abs(z);
+        let re = libm::sqrt((r + z.re) / 2.0);
+        let im = libm::sqrt((r - z.re) / 2.0);
+        Complex64::new(re, if z.im < 0.0 { -im } else { im })
+    }
+}
+
+pub fn sinh(z: Complex64) -> Complex64 {
+    Complex64::new(
+        libm::sinh(z.re) * libm::cos(z.im),
+        libm::cosh(z.re) * libm::sin(z.im),
+    )
+}
+
+/// treat as real if the imaginary part is lost in the noise, same tolerance
+/// numpy uses in isreal checks after root finding
+pub fn is_real(z: Complex64) -> bool {
+    z.im.abs() <= 100.0 * f64::EPSILON * abs(z).max(1.0)
+}
@@ -0,0 +1,273 @@
+//! Auto- and cross-correlation, and the delay between two signals.
+//!
+//! The cross-correlation of x and y at lag m is r[m] = sum x[n + m] y[n], how
+//! much x looks like y moved m samples later. The autocorrelation is x against
+//! itself. Linear correlation of two blocks of L samples has the 2L - 1 lags
+//! from -(L - 1) to L - 1 and is laid out like matlab's xcorr, lag m at index
+//! m + L - 1. Circular correlation wraps n + m round the block, so there are
+//! only L lags, 0 to L - 1, and each has all L products in it.
+//!
+//! Fewer products go into the lags further from zero, so the raw sums fall
+//! off towards the ends. [`Scale`] picks what to divide by, see its variants.
+//!
+//! Every function comes in two versions that give the same answer. The direct
+//! sums cost L^2 multiplies and need no memory. The FFT ones multiply one
+//! spectrum by the conjugate of the other, which is N log N for an N point
+//! complex FFT from backend B, and a buffer of N. For linear correlation N has
+//! to be at least 2L - 1 so the ends don't wrap into each other, for circular
+//! it's exactly L. On target with the `cmsis` feature there's also
+//! [`correlate_cmsis`] which is the direct one done by arm_correlate_f32.
+//!
+//! [`delay`] estimates how far one signal is behind another from the peak of
+//! their correlation, fitting a parabola through the peak and its neighbours
+//! to get a fraction of a sample.
+//!
+//! ```
+//! use dsp::correlation::{correlate, Scale};
+//!
+//! let x = [1.0, 2.0, 3.0];
+//! let y = [0.0, 1.0, 0.5];
+//! let mut r = [0.0; 5];
+//! correlate(&x, &y, Scale::None, &mut r);
+//! assert_eq!(r, [0.5, 2.0, 3.5, 3.0, 0.0]);
+//! ```
+
+use crate::fft::Cfft;
+use crate::Complex32;
+use heapless::{ArrayLength, Vec};
+
+/// What to divide each lag by
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scale {
+    /// The raw sums
+    None,
+    /// By L. Biased towards zero away from lag 0, but the lower variance
+    /// estimate and the one that's always a valid autocorrelation.
+    Biased,
+    /// By the number of products in the lag, L - |m|. The right expected
+    /// value, but the lags near the ends are averages of very few products.
+    Unbiased,
+    /// By sqrt(rxx[0] ryy[0]), so an autocorrelation is 1 at lag 0 and a
+    /// cross-correlation is between -1 and 1
+    Normalized,
+}
+
+/// Divide the lags in r by what `scale` says. `products` is the number of
+/// products in lag index i, `energy` the sum of squares of x and of y.
+fn apply(
+    r: &mut [f32],
+    scale: Scale,
+    len: usize,
+    products: impl Fn(usize) -> usize,
+    energy: (f32, f32),
+) {
+    match scale {
+        Scale::None => {}
+        Scale::Biased => r.iter_mut().for_each(|r| *r /= len as f32),
+        Scale::Unbiased => {
+            for (i, r) in r.iter_mut().enumerate() {
+                *r /= products(i) as f32;
+            }
+        }
+        Scale::Normalized => {
+            let norm = libm::sqrtf(energy.0 * energy.1);
+            if norm > 0.0 {
+                r.iter_mut().for_each(|r| *r /= norm);
+            }
+        }
+    }
+}
+
+fn energy(x: &[f32]) -> f32 {
+    x.iter().map(|x| x * x).sum()
+}
+
+/// Linear correlation scaling, lag index i has L - |i - (L - 1)| products
+fn linear(r: &mut [f32], scale: Scale, x: &[f32], y: &[f32]) {
+    let len = x.len();
+    let products = |i: usize| len - i.abs_diff(len - 1);
+    apply(r, scale, len, products, (energy(x), energy(y)));
+}
+
+/// Circular correlation scaling, every lag has L products
+fn circular_scale(r: &mut [f32], scale: Scale, x: &[f32], y: &[f32]) {
+    let len = x.len();
+    apply(r, scale, len, |_| len, (energy(x), energy(y)));
+}
+
+fn check(x: &[f32], y: &[f32], out: &[f32], lags: usize) {
+    assert_eq!(x.len(), y.len(), "x and y must be the same length");
+    assert!(!x.is_empty(), "nothing to correlate");
+    assert!(out.len() >= lags, "out needs a place for every lag");
+}
+
+/// Linear cross-correlation of x and y, both L long, into the 2L - 1 lags of
+/// out by direct sums
+pub fn correlate(x: &[f32], y: &[f32], scale: Scale, out: &mut [f32]) {
+    let len = x.len();
+    check(x, y, out, 2 * len - 1);
+    let out = &mut out[..2 * len - 1];
+    for (i, r) in out.iter_mut().enumerate() {
+        // lag m = i - (L - 1), x[n + m] is in the block for n from
+        // max(0, -m) to min(L, L - m)
+        *r = if i < len {
+            let shift = len - 1 - i;
+            x[..len - shift]
+                .iter()
+                .zip(&y[shift..])
+                .map(|(x, y)| x * y)
+                .sum()
+        } else {
+            let shift = i - (len - 1);
+            x[shift..]
+                .iter()
+                .zip(&y[..len - shift])
+                .map(|(x, y)| x * y)
+                .sum()
+        };
+    }
+    linear(out, scale, x, y);
+}
+
+/// Linear autocorrelation of x into the 2L - 1 lags of out by direct sums
+pub fn autocorrelate(x: &[f32], scale: Scale, out: &mut [f32]) {
+    correlate(x, x, scale, out)
+}
+
+/// Circular cross-correlation of x and y, both L long, into the L lags of
+/// out by direct sums
+pub fn circular(x: &[f32], y: &[f32], scale: Scale, out: &mut [f32]) {
+    let len = x.len();
+    check(x, y, out, len);
+    let out = &mut out[..len];
+    for (m, r) in out.iter_mut().enumerate() {
+        *r = y
+            .iter()
+            .enumerate()
+            .map(|(n, y)| x[(n + m) % len] * y)
+            .sum();
+    }
+    circular_scale(out, scale, x, y);
+}
+
+/// The circular correlation of x and y zero padded to N, r[m] at index m
+/// mod N. X conj(Y) transformed back, the 1/N is in the inverse.
+fn spectra<B: Cfft<N>, N: ArrayLength<Complex32>>(x: &[f32], y: &[f32]) -> Vec<Complex32, N> {
+    let widen = |x: &[f32]| {
+        x.iter()
+            .map(|x| Complex32::new(*x, 0.0))
+            .collect::<Vec<Complex32, N>>()
+    };
+    let mut r = widen(x);
+    let mut y = widen(y);
+    B::cfft(&mut r);
+    B::cfft(&mut y);
+    r.iter_mut().zip(y.iter()).for_each(|(r, y)| *r *= y.conj());
+    B::icfft(&mut r);
+    r
+}
+
+/// [`correlate`] through N point FFTs from backend B, N at least 2L - 1
+pub fn correlate_fft<B: Cfft<N>, N: ArrayLength<Complex32>>(
+    x: &[f32],
+    y: &[f32],
+    scale: Scale,
+    out: &mut [f32],
+) {
+    let len = x.len();
+    let n = N::to_usize();
+    check(x, y, out, 2 * len - 1);
+    assert!(n > 2 * len - 2, "N must be at least 2L - 1");
+
+    let r = spectra::<B, N>(x, y);
+    let out = &mut out[..2 * len - 1];
+    for (i, out) in out.iter_mut().enumerate() {
+        // negative lags are at the end
+        *out = r[(i + n + 1 - len) % n].re;
+    }
+    linear(out, scale, x, y);
+}
+
+/// [`autocorrelate`] through N point FFTs from backend B, N at least 2L - 1
+pub fn autocorrelate_fft<B: Cfft<N>, N: ArrayLength<Complex32>>(
+    x: &[f32],
+    scale: Scale,
+    out: &mut [f32],
+) {
+    correlate_fft::<B, N>(x, x, scale, out)
+}
+
+/// [`circular`] through N point FFTs from backend B, N exactly L
+pub fn circular_fft<B: Cfft<N>, N: ArrayLength<Complex32>>(
+    x: &[f32],
+    y: &[f32],
+    scale: Scale,
+    out: &mut [f32],
+) {
+    let len = x.len();
+    check(x, y, out, len);
+    assert_eq!(N::to_usize(), len, "N must be L");
+
+    let r = spectra::<B, N>(x, y);
+    let out = &mut out[..len];
+    out.iter_mut()
+        .zip(r.iter())
+        .for_each(|(out, r)| *out = r.re);
+    circular_scale(out, scale, x, y);
+}
+
+/// [`correlate`] done by CMSIS-DSP arm_correlate_f32
+#[cfg(feature = "cmsis")]
+pub fn correlate_cmsis(x: &[f32], y: &[f32], scale: Scale, out: &mut [f32]) {
+    let len = x.len();
+    check(x, y, out, 2 * len - 1);
+    // for equal lengths it's the same layout as xcorr
+    unsafe {
+        crate::fft::arm::arm_correlate_f32(
+            x.as_ptr(),
+            len as u32,
+            y.as_ptr(),
+            len as u32,
+            out.as_mut_ptr(),
+        );
+    }
+    linear(&mut out[..2 * len - 1], scale, x, y);
+}
+
+/// Index of the largest value in r, between samples by fitting a parabola
+/// through it and its neighbours. A peak on either end isn't interpolated.
+pub fn peak(r: &[f32]) -> f32 {
+    let (i, _) =
+        r.iter().enumerate().fold(
+            (0, f32::MIN),
+            |(i, max), (j, r)| if *r > max { (j, *r) } else { (i, max) },
+        );
+    if i == 0 || i == r.len() - 1 {
+        return i as f32;
+    }
+
+    let (a, b, c) = (r[i - 1], r[i], r[i + 1]);
+    let curvature = a - 2.0 * b + c;
+    if curvature == 0.0 {
+        i as f32
+    } else {
+        i as f32 + 0.5 * (a - c) / curvature
+    }
+}
+
+/// How many samples y is behind x, y[n] = x[n - d], from the peak of their
+/// correlation through N point FFTs from backend B, N at least 2L - 1. x and y
+/// should have their means taken out first, or the triangle the overlap makes
+/// pulls the peak towards lag 0.
+pub fn delay<B, N>(x: &[f32], y: &[f32]) -> f32
+where
+    B: Cfft<N>,
+    N: ArrayLength<Complex32> + ArrayLength<f32>,
+{
+    let len = x.len();
+    let mut r = Vec::<f32, N>::new();
+    r.resize(2 * len - 1, 0.0).unwrap();
+    // y against x peaks at lag d
+    correlate_fft::<B, N>(y, x, Scale::None, &mut r);
+    peak(&r) - (len - 1) as f32
+}
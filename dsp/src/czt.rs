@@ -0,0 +1,166 @@
+//! Chirp-z transform, DFTs of any length and zoomed in spectra.
+//!
+//! The FFTs only come in powers of two. Bluestein's trick gets any other
+//! length out of them: with nk = (n^2 + k^2 - (k - n)^2) / 2 the DFT sum turns
+//! into a convolution with a chirp, and the convolution is done with a power
+//! of two FFT at least n + m - 1 long. A 1000 point DFT costs three 2048 point
+//! FFTs instead of a million multiplies.
+//!
+//! The same sum works for any m points z_k = A W^-k, not just the n roots of
+//! unity. Here they stay on the unit circle, so [`Czt`] evaluates the spectrum
+//! at m frequencies from w0 in steps of dw radians per sample, which is how a
+//! narrow band gets looked at in more detail than the bin spacing.
+//!
+//! The chirps and the transformed filter are worked out once by the
+//! constructor, in f64 so the n^2 phases don't lose precision, and then reused
+//! for every block. Everything is held in heapless::Vecs of the FFT length L
+//! and the FFT backend is picked the same way as in [`crate::fft`].
+//!
+//! ```
+//! use dsp::czt::Czt;
+//! use dsp::fft::Microfft;
+//! use dsp::Complex32;
+//! use heapless::consts::U32;
+//!
+//! // 12 point DFT with a 32 point FFT
+//! let czt = Czt::<Microfft, U32>::dft(12).unwrap();
+//! let mut out = heapless::Vec::new();
+//! czt.transform(&[Complex32::new(1.0, 0.0)], &mut out);
+//! assert_eq!(out.len(), 12);
+//! ```
+
+use crate::fft::Cfft;
+use crate::Complex32;
+use core::f64::consts::PI;
+use core::marker::PhantomData;
+use heapless::{ArrayLength, Vec};
+
+/// The FFT length L isn't at least n + m - 1
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TooShort {
+    pub needed: usize,
+}
+
+/// Precomputed chirp-z transform of n samples to m frequencies, using a
+/// length L FFT from backend B
+pub struct Czt<B, L: ArrayLength<Complex32>> {
+    w0: f64,
+    dw: f64,
+    n: usize,
+    /// A^-n W^(n^2/2), applied to the input
+    pre: Vec<Complex32, L>,
+    /// W^(k^2/2), applied to the output
+    post: Vec<Complex32, L>,
+    /// FFT of the W^-(i^2/2) chirp, wrapped around for negative i
+    filter: Vec<Complex32, L>,
+    backend: PhantomData<B>,
+}
+
+/// e^(j w i^2 / 2) with the phase reduced in f64 first
+fn chirp(w: f64, i: usize) -> Complex32 {
+    let (sin, cos) = libm::sincos((w * (i * i) as f64 / 2.0) % (2.0 * PI));
+    Complex32::new(cos as f32, sin as f32)
+}
+
+/// truncate and clear in heapless 0.5 index past the new end, pop instead
+fn shorten<L: ArrayLength<Complex32>>(x: &mut Vec<Complex32, L>, len: usize) {
+    while x.len() > len {
+        x.pop();
+    }
+}
+
+impl<B: Cfft<L>, L: ArrayLength<Complex32>> Czt<B, L> {
+    /// n samples to m frequencies, from w0 in steps of dw radians per sample
+    pub fn new(n: usize, m: usize, w0: f64, dw: f64) -> Result<Self, TooShort> {
+        let needed = (n + m).saturating_sub(1);
+        if needed > L::to_usize() {
+            return Err(TooShort { needed });
+        }
+
+        // W = e^-j dw and A = e^j w0
+        let pre = (0..n)
+            .map(|i| {
+                let (sin, cos) = libm::sincos((w0 * i as f64) % (2.0 * PI));
+                chirp(-dw, i) * Complex32::new(cos as f32, -sin as f32)
+            })
+            .collect();
+        let post = (0..m).map(|k| chirp(-dw, k)).collect();
+
+        let mut filter = Vec::new();
+        filter.resize(L::to_usize(), Complex32::default()).unwrap();
+        for i in 0..m {
+            filter[i] = chirp(dw, i);
+        }
+        for i in 1..n {
+            filter[L::to_usize() - i] = chirp(dw, i);
+        }
+        B::cfft(&mut filter);
+
+        Ok(Self {
+            w0,
+            dw,
+            n,
+            pre,
+            post,
+            filter,
+            backend: PhantomData,
+        })
+    }
+
+    /// The ordinary n point DFT, for n that isn't a power of two
+    pub fn dft(n: usize) -> Result<Self, TooShort> {
+        Self::new(n, n, 0.0, 2.0 * PI / n as f64)
+    }
+
+    /// n samples at sample rate fs to m frequencies from `start` Hz in steps
+    /// of `step` Hz
+    pub fn zoom(n: usize, m: usize, start: f32, step: f32, fs: f32) -> Result<Self, TooShort> {
+        let w = 2.0 * PI / fs as f64;
+        Self::new(n, m, w * start as f64, w * step as f64)
+    }
+
+    /// Number of input samples
+    pub fn input_len(&self) -> usize {
+        self.n
+    }
+
+    /// Number of output frequencies
+    pub fn len(&self) -> usize {
+        self.post.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.post.is_empty()
+    }
+
+    /// Frequency of output k in Hz at sample rate fs
+    pub fn frequency(&self, k: usize, fs: f32) -> f32 {
+        ((self.w0 + self.dw * k as f64) * fs as f64 / (2.0 * PI)) as f32
+    }
+
+    /// Transform x into out, which is left holding the m outputs. Anything
+    /// shorter than n is zero padded, anything longer is cut off.
+    pub fn transform(&self, x: &[Complex32], out: &mut Vec<Complex32, L>) {
+        self.run(x.iter().cloned(), out);
+    }
+
+    /// [`Czt::transform`] of a real signal
+    pub fn transform_real(&self, x: &[f32], out: &mut Vec<Complex32, L>) {
+        self.run(x.iter().map(|x| Complex32::new(*x, 0.0)), out);
+    }
+
+    fn run(&self, x: impl Iterator<Item = Complex32>, out: &mut Vec<Complex32, L>) {
+        shorten(out, 0);
+        out.extend(x.zip(self.pre.iter()).map(|(x, pre)| x * pre));
+        B::cfft(out);
+        out.iter_mut()
+            .zip(self.filter.iter())
+            .for_each(|(y, h)| *y *= h);
+        B::icfft(out);
+
+        shorten(out, self.post.len());
+        out.iter_mut()
+            .zip(self.post.iter())
+            .for_each(|(y, post)| *y *= post);
+    }
+}
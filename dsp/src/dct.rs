@@ -0,0 +1,201 @@
+//! Cosine transforms and the MDCT, on top of the real FFTs.
+//!
+//! All of them are orthonormal, scaled by sqrt(2/N) with the DC term of the
+//! DCT-II scaled by a further 1/sqrt(2). That makes the DCT-III the exact
+//! inverse of the DCT-II and the DCT-IV its own inverse, and keeps the energy
+//! of the signal in the coefficients.
+//!
+//! The DCT-II reorders the samples, evens forwards and odds backwards, after
+//! which it is a real FFT of the same length and a twiddle per bin (Makhoul).
+//! The DCT-III runs that backwards through the inverse real FFT. The DCT-IV is
+//! an N/2 point complex FFT with twiddles on both sides, which comes out of
+//! the real FFT by undoing its last step.
+//!
+//! The MDCT turns 2N samples into N coefficients. Successive frames overlap by
+//! N samples, and with a window that satisfies w[n]^2 + w[n + N]^2 = 1 like
+//! [`sine`] the time domain aliasing of each frame is cancelled by the next
+//! (TDAC) when the IMDCT frames are added back together. [`Dct::mdct`] and
+//! [`Dct::imdct`] both take N new values at a time and keep the other half of
+//! the frame in a buffer, so they can stream. The reconstruction comes out N
+//! samples behind the input.
+//!
+//! ```
+//! use dsp::dct::Dct;
+//! use dsp::fft::Microfft;
+//!
+//! let mut x = heapless::Vec::<f32, heapless::consts::U8>::new();
+//! x.extend_from_slice(&[1.0, 2.0, 3.0, 4.0]).unwrap();
+//! Microfft::dct2(&mut x);
+//! Microfft::dct3(&mut x);
+//! assert!((x[2] - 3.0).abs() < 1e-5 && x[7].abs() < 1e-5);
+//! ```
+
+use crate::fft::{as_complex, pad, split, Phasor, Rfft};
+use crate::Complex32;
+use core::f64::consts::PI;
+use heapless::{ArrayLength, Vec};
+
+/// Orthonormal DCTs of length N, in place. Anything shorter than N is zero
+/// padded first. Implemented for every backend with a real FFT of length N.
+pub trait Dct<N: ArrayLength<f32>> {
+    /// X[k] = s[k] sum x[n] cos(pi/N (n + 1/2) k)
+    fn dct2(x: &mut Vec<f32, N>);
+
+    /// Inverse of [`Dct::dct2`]
+    fn dct3(x: &mut Vec<f32, N>);
+
+    /// X[k] = sqrt(2/N) sum x[n] cos(pi/N (n + 1/2)(k + 1/2)), its own inverse
+    fn dct4(x: &mut Vec<f32, N>);
+
+    /// N new samples in x become N MDCT coefficients of the 2N sample frame
+    /// `previous` followed by x, multiplied by the 2N point `window`. x is
+    /// left in `previous` for the next frame.
+    fn mdct(x: &mut Vec<f32, N>, window: &[f32], previous: &mut Vec<f32, N>);
+
+    /// N MDCT coefficients in x become N output samples. The 2N sample frame
+    /// is multiplied by the `window`, its first half added to `overlap` from
+    /// the frame before into x and its second half left in `overlap`.
+    fn imdct(x: &mut Vec<f32, N>, window: &[f32], overlap: &mut Vec<f32, N>);
+}
+
+/// e^-j pi (start + k step) for k = 0, 1, 2..., stepped in f32 so the
+/// per bin loops don't call the soft float f64 sincos on the M4
+fn twiddles(start: f64, step: f64) -> Phasor {
+    Phasor::new(-PI * start, -PI * step)
+}
+
+/// Sine window of `len` points, w[n] = sin(pi (n + 1/2) / len), which meets
+/// the TDAC condition for an MDCT of len / 2 coefficients
+pub fn sine(len: usize) -> impl Iterator<Item = f32> + Clone {
+    (0..len).map(move |n| libm::sin(PI * (n as f64 + 0.5) / len as f64) as f32)
+}
+
+impl<B: Rfft<N>, N: ArrayLength<f32>> Dct<N> for B {
+    fn dct2(x: &mut Vec<f32, N>) {
+        pad(x);
+        let n = N::to_usize();
+        let h = n / 2;
+
+        let mut v = Vec::<f32, N>::new();
+        pad(&mut v);
+        for i in 0..h {
+            v[i] = x[2 * i];
+            v[n - 1 - i] = x[2 * i + 1];
+        }
+        let packed = B::rfft(&mut v);
+
+        // y = e^-j pi k / 2N V[k] has X[k] in re and -X[N - k] in im
+        let scale = libm::sqrtf(2.0 / n as f32);
+        x[0] = packed[0].re * libm::sqrtf(1.0 / n as f32);
+        x[h] = packed[0].im * scale * core::f32::consts::FRAC_1_SQRT_2;
+        let step = 1.0 / (2 * n) as f64;
+        for (k, w) in (1..h).zip(twiddles(step, step)) {
+            let y = packed[k] * w;
+            x[k] = y.re * scale;
+            x[n - k] = -y.im * scale;
+        }
+    }
+
+    fn dct3(x: &mut Vec<f32, N>) {
+        pad(x);
+        let n = N::to_usize();
+        let h = n / 2;
+
+        let mut v = Vec::<f32, N>::new();
+        pad(&mut v);
+        {
+            let packed = as_complex(&mut v);
+            let scale = libm::sqrtf(n as f32 / 2.0);
+            packed[0] = Complex32::new(
+                x[0] * libm::sqrtf(n as f32),
+                x[h] * scale * core::f32::consts::SQRT_2,
+            );
+            let step = 1.0 / (2 * n) as f64;
+            for (k, w) in (1..h).zip(twiddles(step, step)) {
+                let y = Complex32::new(x[k], -x[n - k]) * scale;
+                packed[k] = y * w.conj();
+            }
+        }
+        B::irfft(&mut v);
+
+        for i in 0..h {
+            x[2 * i] = v[i];
+            x[2 * i + 1] = v[n - 1 - i];
+        }
+    }
+
+    fn dct4(x: &mut Vec<f32, N>) {
+        pad(x);
+        let n = N::to_usize();
+        let h = n / 2;
+
+        // t[i] = (x[2i] + j x[N - 1 - 2i]) e^-j pi (i + 1/4) / N, interleaved
+        // so the real FFT followed by split is its N/2 point complex FFT
+        let mut v = Vec::<f32, N>::new();
+        pad(&mut v);
+        {
+            let t = as_complex(&mut v);
+            let w = twiddles(0.25 / n as f64, 1.0 / n as f64);
+            for (i, w) in (0..h).zip(w) {
+                let z = Complex32::new(x[2 * i], x[n - 1 - 2 * i]);
+                t[i] = z * w;
+            }
+        }
+        split(B::rfft(&mut v));
+
+        let t = as_complex(&mut v);
+        let scale = libm::sqrtf(2.0 / n as f32);
+        for (k, w) in (0..h).zip(twiddles(0.0, 1.0 / n as f64)) {
+            let y = t[k] * w;
+            x[2 * k] = y.re * scale;
+            x[n - 1 - 2 * k] = -y.im * scale;
+        }
+    }
+
+    fn mdct(x: &mut Vec<f32, N>, window: &[f32], previous: &mut Vec<f32, N>) {
+        pad(x);
+        pad(previous);
+        let n = N::to_usize();
+        let h = n / 2;
+        assert_eq!(window.len(), 2 * n, "window must be twice the length");
+
+        // With the windowed frame as quarters a b c d the MDCT is the DCT-IV
+        // of -c_r - d and a - b_r, where _r is reversed
+        let mut u = Vec::<f32, N>::new();
+        pad(&mut u);
+        let frame = |i: usize| {
+            if i < n {
+                previous[i] * window[i]
+            } else {
+                x[i - n] * window[i]
+            }
+        };
+        for i in 0..h {
+            u[i] = -frame(3 * h - 1 - i) - frame(3 * h + i);
+            u[h + i] = frame(i) - frame(n - 1 - i);
+        }
+        B::dct4(&mut u);
+
+        previous.copy_from_slice(x);
+        x.copy_from_slice(&u);
+    }
+
+    fn imdct(x: &mut Vec<f32, N>, window: &[f32], overlap: &mut Vec<f32, N>) {
+        pad(x);
+        pad(overlap);
+        let n = N::to_usize();
+        let h = n / 2;
+        assert_eq!(window.len(), 2 * n, "window must be twice the length");
+
+        // The DCT-IV gives back y1 y2 = -c_r - d, a - b_r which unfolds to
+        // y2, -y2_r, -y1_r, -y1 and the aliases cancel with the next frame
+        B::dct4(x);
+        let y = x.clone();
+        for i in 0..h {
+            x[i] = y[h + i] * window[i] + overlap[i];
+            x[h + i] = -y[n - 1 - i] * window[h + i] + overlap[h + i];
+            overlap[i] = -y[h - 1 - i] * window[n + i];
+            overlap[h + i] = -y[i] * window[n + h + i];
+        }
+    }
+}
@@ -0,0 +1,398 @@
+//! FFT with the length picked at compile time.
+//!
+//! microfft and CMSIS-DSP both have a separate transform for every power of
+//! two, cfft_16 to cfft_4096 and arm_cfft_sR_f32_len16 to len4096. [`Cfft`] is
+//! implemented for each typenum length a backend has, so the transform is
+//! chosen from the length of the heapless::Vec it's called on. Changing the
+//! length is a change to the Vec's type, and a length the backend doesn't have
+//! fails to compile instead of panicking.
+//!
+//! [`Microfft`] runs anywhere. [`Cmsis`] needs the `cmsis` feature and a
+//! firmware crate that links the CMSIS-DSP library, which lab4 does through
+//! cmsis-dsp-sys.
+//!
+//! ```
+//! use dsp::fft::{Cfft, Microfft};
+//! use dsp::Complex32;
+//!
+//! let mut x = heapless::Vec::<Complex32, heapless::consts::U16>::new();
+//! x.push(Complex32::new(1.0, 0.0)).unwrap();
+//! Microfft::cfft(&mut x);
+//! assert_eq!(x.len(), 16);
+//! ```
+//!
+//! [`Rfft`] is the same for real signals, which is most of them. It takes N
+//! samples and gives back N/2 complex bins in the same buffer, packed like
+//! arm_rfft_fast_f32 packs them. That's half the memory of widening to
+//! Complex32 with a zero imaginary part, and about half the work.
+//!
+//! There is no 12 point transform
+//!
+//! ```compile_fail
+//! use dsp::fft::{Cfft, Microfft};
+//! use dsp::Complex32;
+//!
+//! let mut x = heapless::Vec::<Complex32, heapless::consts::U12>::new();
+//! Microfft::cfft(&mut x);
+//! ```
+
+use crate::Complex32;
+use heapless::consts::*;
+use heapless::{ArrayLength, Vec};
+
+/// In place complex FFT of length N
+pub trait Cfft<N: ArrayLength<Complex32>> {
+    /// Transform x in place, unscaled and in natural order. Anything shorter
+    /// than N is zero padded first.
+    fn cfft(x: &mut Vec<Complex32, N>);
+
+    /// Inverse transform in place, scaled by 1/N so `icfft(cfft(x)) == x`.
+    /// Anything shorter than N is zero padded first.
+    fn icfft(x: &mut Vec<Complex32, N>);
+}
+
+/// The pure rust microfft radix-2 transforms
+pub struct Microfft;
+
+/// CMSIS-DSP arm_cfft_f32 with the precomputed arm_cfft_sR_f32 instances, and
+/// arm_rfft_fast_f32, which borrows N floats of stack for its output
+#[cfg(feature = "cmsis")]
+pub struct Cmsis;
+
+/// Real input FFT of length N, in place and packed like arm_rfft_fast_f32.
+///
+/// The N samples become N/2 Complex32 in the same memory. Bins 1 to N/2 - 1
+/// are where you'd expect them. DC and Nyquist are both purely real so they
+/// share bin 0, DC in re and Nyquist in im. The negative frequencies are the
+/// conjugates of the positive ones and aren't kept.
+/// [`crate::spectrum::bins`] unpacks it to the N/2 + 1 bins from DC to
+/// Nyquist, and [`crate::spectrum::Spectrum`] has magnitudes and powers of
+/// them.
+pub trait Rfft<N: ArrayLength<f32>> {
+    /// Forward transform, unscaled. Anything shorter than N is zero padded
+    /// first. Returns the packed spectrum, which is x reinterpreted.
+    fn rfft(x: &mut Vec<f32, N>) -> &mut [Complex32];
+
+    /// Inverse of a packed spectrum left in x by [`Rfft::rfft`], scaled by
+    /// 1/N so the round trip gives back the samples
+    fn irfft(x: &mut Vec<f32, N>);
+}
+
+pub(crate) fn pad<T: Clone + Default, N: ArrayLength<T>>(x: &mut Vec<T, N>) {
+    x.resize(N::to_usize(), T::default()).unwrap();
+}
+
+/// Pairs of samples as one Complex32, which is repr(C) re, im
+pub(crate) fn as_complex(x: &mut [f32]) -> &mut [Complex32] {
+    unsafe { core::slice::from_raw_parts_mut(x.as_mut_ptr() as *mut Complex32, x.len() / 2) }
+}
+
+/// Multiply two packed real spectra bin by bin into a, which is circular
+/// convolution once transformed back. Bin 0 is two real bins so it isn't a
+/// complex multiply.
+pub fn multiply(a: &mut [Complex32], b: &[Complex32]) {
+    a[0] = Complex32::new(a[0].re * b[0].re, a[0].im * b[0].im);
+    for (a, b) in a[1..].iter_mut().zip(b[1..].iter()) {
+        *a *= b;
+    }
+}
+
+/// microfft only goes forwards. The inverse DFT is the forward one of the
+/// conjugate, conjugated again and divided by N.
+fn inverse(x: &mut [Complex32], forward: impl FnOnce(&mut [Complex32])) {
+    x.iter_mut().for_each(|x| *x = x.conj());
+    forward(x);
+    let scale = 1.0 / x.len() as f32;
+    x.iter_mut().for_each(|x| *x = x.conj() * scale);
+}
+
+/// e^(j(start + step k)) for k = 0, 1, 2... in f32. One sincos up front and
+/// then a complex multiply-add each, w += w (e^(j step) - 1) with the
+/// e^(j step) - 1 worked out from the half angle so it doesn't lose its
+/// precision to the 1 (Numerical Recipes 5.4). The f64 libm calls are soft
+/// float on the M4, so they stay out of the per bin loops.
+#[derive(Clone, Debug)]
+pub(crate) struct Phasor {
+    w: Complex32,
+    d: Complex32,
+}
+
+impl Phasor {
+    pub(crate) fn new(start: f64, step: f64) -> Self {
+        let (sin, cos) = libm::sincos(start);
+        let half = libm::sin(0.5 * step);
+        Self {
+            w: Complex32::new(cos as f32, sin as f32),
+            d: Complex32::new((-2.0 * half * half) as f32, libm::sin(step) as f32),
+        }
+    }
+}
+
+impl Iterator for Phasor {
+    type Item = Complex32;
+
+    fn next(&mut self) -> Option<Complex32> {
+        let w = self.w;
+        self.w += w * self.d;
+        Some(w)
+    }
+}
+
+/// Undo the last step of a real FFT. The N/2 packed bins of an N point real
+/// spectrum turn back into the N/2 point complex FFT of the even samples as re
+/// and the odd samples as im, which an inverse complex FFT then unpacks.
+pub(crate) fn split(x: &mut [Complex32]) {
+    let m = x.len();
+    let (dc, nyquist) = (x[0].re, x[0].im);
+    x[0] = Complex32::new(dc + nyquist, dc - nyquist) * 0.5;
+
+    // X[k] = E[k] + W^k O[k] and X[M - k]* = E[k] - W^k O[k], with
+    // W^k = e^(j pi k/M) and W^(M - k) = -W^k*
+    let unmix = |a: Complex32, b: Complex32, w: Complex32| {
+        let even = (a + b.conj()) * 0.5;
+        let odd = (a - b.conj()) * 0.5 * w;
+        even + Complex32::i() * odd
+    };
+    let step = core::f64::consts::PI / m as f64;
+    for (k, w) in (1..=m / 2).zip(Phasor::new(step, step)) {
+        let (a, b) = (x[k], x[m - k]);
+        x[k] = unmix(a, b, w);
+        x[m - k] = unmix(b, a, -w.conj());
+    }
+}
+
+macro_rules! microfft {
+    ($($n:ty => $f:ident,)*) => {$(
+        impl Cfft<$n> for Microfft {
+            fn cfft(x: &mut Vec<Complex32, $n>) {
+                pad(x);
+                let _ = microfft::complex::$f(&mut x[..]);
+            }
+
+            fn icfft(x: &mut Vec<Complex32, $n>) {
+                pad(x);
+                inverse(x, |x| {
+                    let _ = microfft::complex::$f(x);
+                });
+            }
+        }
+    )*};
+}
+
+macro_rules! microfft_real {
+    ($($n:ty => $rfft:ident, $half:ident,)*) => {$(
+        impl Rfft<$n> for Microfft {
+            fn rfft(x: &mut Vec<f32, $n>) -> &mut [Complex32] {
+                pad(x);
+                // microfft drops the Nyquist bin, put it back in bin 0
+                let nyquist = x.chunks(2).map(|p| p[0] - p[1]).sum();
+                let packed = microfft::real::$rfft(&mut x[..]);
+                packed[0].im = nyquist;
+                packed
+            }
+
+            fn irfft(x: &mut Vec<f32, $n>) {
+                pad(x);
+                let z = as_complex(x);
+                split(z);
+
+                inverse(z, |z| {
+                    let _ = microfft::complex::$half(z);
+                });
+            }
+        }
+    )*};
+}
+
+microfft! {
+    U2 => cfft_2,
+    U4 => cfft_4,
+    U8 => cfft_8,
+    U16 => cfft_16,
+    U32 => cfft_32,
+    U64 => cfft_64,
+    U128 => cfft_128,
+    U256 => cfft_256,
+    U512 => cfft_512,
+    U1024 => cfft_1024,
+    U2048 => cfft_2048,
+    U4096 => cfft_4096,
+}
+
+microfft_real! {
+    U4 => rfft_4, cfft_2,
+    U8 => rfft_8, cfft_4,
+    U16 => rfft_16, cfft_8,
+    U32 => rfft_32, cfft_16,
+    U64 => rfft_64, cfft_32,
+    U128 => rfft_128, cfft_64,
+    U256 => rfft_256, cfft_128,
+    U512 => rfft_512, cfft_256,
+    U1024 => rfft_1024, cfft_512,
+    U2048 => rfft_2048, cfft_1024,
+    U4096 => rfft_4096, cfft_2048,
+}
+
+/// The parts of arm_math.h used here. Declared directly rather than through
+/// cmsis-dsp-sys so the host builds don't need it, the symbols come from the
+/// library the firmware links.
+#[cfg(feature = "cmsis")]
+#[allow(non_camel_case_types, non_upper_case_globals)]
+pub(crate) mod arm {
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct arm_cfft_instance_f32 {
+        fft_len: u16,
+        p_twiddle: *const f32,
+        p_bit_rev_table: *const u16,
+        bit_rev_length: u16,
+    }
+
+    /// What arm_rfft_fast_init_f32 fills in, the N/2 point complex instance
+    /// and the N point real twiddles
+    #[repr(C)]
+    pub struct arm_rfft_fast_instance_f32 {
+        pub sint: arm_cfft_instance_f32,
+        pub fft_len_rfft: u16,
+        pub p_twiddle_rfft: *const f32,
+    }
+
+    extern "C" {
+        pub fn arm_cfft_f32(
+            s: *const arm_cfft_instance_f32,
+            p1: *mut f32,
+            ifft_flag: u8,
+            bit_reverse_flag: u8,
+        );
+
+        pub fn arm_rfft_fast_f32(
+            s: *const arm_rfft_fast_instance_f32,
+            p: *mut f32,
+            p_out: *mut f32,
+            ifft_flag: u8,
+        );
+
+        pub fn arm_cmplx_mag_f32(p_src: *const f32, p_dst: *mut f32, num_samples: u32);
+
+        pub fn arm_cmplx_mag_squared_f32(p_src: *const f32, p_dst: *mut f32, num_samples: u32);
+
+        pub fn arm_correlate_f32(
+            p_src_a: *const f32,
+            src_a_len: u32,
+            p_src_b: *const f32,
+            src_b_len: u32,
+            p_dst: *mut f32,
+        );
+
+        pub static arm_cfft_sR_f32_len16: arm_cfft_instance_f32;
+        pub static arm_cfft_sR_f32_len32: arm_cfft_instance_f32;
+        pub static arm_cfft_sR_f32_len64: arm_cfft_instance_f32;
+        pub static arm_cfft_sR_f32_len128: arm_cfft_instance_f32;
+        pub static arm_cfft_sR_f32_len256: arm_cfft_instance_f32;
+        pub static arm_cfft_sR_f32_len512: arm_cfft_instance_f32;
+        pub static arm_cfft_sR_f32_len1024: arm_cfft_instance_f32;
+        pub static arm_cfft_sR_f32_len2048: arm_cfft_instance_f32;
+        pub static arm_cfft_sR_f32_len4096: arm_cfft_instance_f32;
+
+        pub static twiddleCoef_rfft_32: [f32; 32];
+        pub static twiddleCoef_rfft_64: [f32; 64];
+        pub static twiddleCoef_rfft_128: [f32; 128];
+        pub static twiddleCoef_rfft_256: [f32; 256];
+        pub static twiddleCoef_rfft_512: [f32; 512];
+        pub static twiddleCoef_rfft_1024: [f32; 1024];
+        pub static twiddleCoef_rfft_2048: [f32; 2048];
+        pub static twiddleCoef_rfft_4096: [f32; 4096];
+    }
+}
+
+#[cfg(feature = "cmsis")]
+macro_rules! cmsis {
+    ($($n:ty => $instance:ident,)*) => {$(
+        impl Cfft<$n> for Cmsis {
+            fn cfft(x: &mut Vec<Complex32, $n>) {
+                pad(x);
+                // Complex32 is repr(C) re, im which is the interleaved layout
+                // CMSIS expects
+                unsafe {
+                    arm::arm_cfft_f32(&arm::$instance, x.as_mut_ptr() as *mut f32, 0, 1);
+                }
+            }
+
+            /// arm_cfft_f32 does the 1/N scaling itself for the inverse
+            fn icfft(x: &mut Vec<Complex32, $n>) {
+                pad(x);
+                unsafe {
+                    arm::arm_cfft_f32(&arm::$instance, x.as_mut_ptr() as *mut f32, 1, 1);
+                }
+            }
+        }
+    )*};
+}
+
+#[cfg(feature = "cmsis")]
+cmsis! {
+    U16 => arm_cfft_sR_f32_len16,
+    U32 => arm_cfft_sR_f32_len32,
+    U64 => arm_cfft_sR_f32_len64,
+    U128 => arm_cfft_sR_f32_len128,
+    U256 => arm_cfft_sR_f32_len256,
+    U512 => arm_cfft_sR_f32_len512,
+    U1024 => arm_cfft_sR_f32_len1024,
+    U2048 => arm_cfft_sR_f32_len2048,
+    U4096 => arm_cfft_sR_f32_len4096,
+}
+
+/// arm_rfft_fast_f32 works out of place and trashes its input, so it runs into
+/// a scratch buffer of N floats on the stack that's copied back, 16 KB at
+/// N = 4096. The instance is put together from the library's precomputed
+/// tables, the same thing arm_rfft_fast_init_f32 would do but with the length
+/// known at compile time, so nothing is initialized per transform.
+#[cfg(feature = "cmsis")]
+fn rfft_fast<N: ArrayLength<f32>>(
+    x: &mut Vec<f32, N>,
+    half: &arm::arm_cfft_instance_f32,
+    twiddles: &[f32],
+    ifft_flag: u8,
+) {
+    let instance = arm::arm_rfft_fast_instance_f32 {
+        sint: *half,
+        fft_len_rfft: N::to_usize() as u16,
+        p_twiddle_rfft: twiddles.as_ptr(),
+    };
+    let mut out = Vec::<f32, N>::new();
+    pad(&mut out);
+    unsafe {
+        arm::arm_rfft_fast_f32(&instance, x.as_mut_ptr(), out.as_mut_ptr(), ifft_flag);
+    }
+    x.copy_from_slice(&out);
+}
+
+#[cfg(feature = "cmsis")]
+macro_rules! cmsis_real {
+    ($($n:ty => $half:ident, $twiddles:ident,)*) => {$(
+        impl Rfft<$n> for Cmsis {
+            fn rfft(x: &mut Vec<f32, $n>) -> &mut [Complex32] {
+                pad(x);
+                unsafe { rfft_fast(x, &arm::$half, &arm::$twiddles, 0) };
+                as_complex(x)
+            }
+
+            fn irfft(x: &mut Vec<f32, $n>) {
+                pad(x);
+                unsafe { rfft_fast(x, &arm::$half, &arm::$twiddles, 1) };
+            }
+        }
+    )*};
+}
+
+#[cfg(feature = "cmsis")]
+cmsis_real! {
+    U32 => arm_cfft_sR_f32_len16, twiddleCoef_rfft_32,
+    U64 => arm_cfft_sR_f32_len32, twiddleCoef_rfft_64,
+    U128 => arm_cfft_sR_f32_len64, twiddleCoef_rfft_128,
+    U256 => arm_cfft_sR_f32_len128, twiddleCoef_rfft_256,
+    U512 => arm_cfft_sR_f32_len256, twiddleCoef_rfft_512,
+    U1024 => arm_cfft_sR_f32_len512, twiddleCoef_rfft_1024,
+    U2048 => arm_cfft_sR_f32_len1024, twiddleCoef_rfft_2048,
+    U4096 => arm_cfft_sR_f32_len2048, twiddleCoef_rfft_4096,
+}
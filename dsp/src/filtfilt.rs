@@ -0,0 +1,118 @@
+//! Zero phase filtering of recorded data, like scipy's filtfilt.
+//!
+//! Running a filter forwards and then backwards over a signal cancels its
+//! phase response, so nothing gets delayed and the magnitude response is
+//! applied twice. That only works offline on a whole buffer, for captured logs
+//! on the host rather than the sampling loops on the board.
+//!
+//! The start up transient is avoided by extending the signal at both ends with
+//! a point-reflected copy of itself (odd extension) and by starting the filter
+//! from the steady state it would have reached for a constant input equal to
+//! the first sample, which [`lfilter_zi`] calculates.
+//!
+//! The filter is given as b and a in z^-1, an FIR is just `a = [1.0]`. Keep to
+//! low orders here, for anything high order see [`crate::sos`].
+
+use crate::zpk::Coefficients;
+
+/// Normalize by a[0] and pad b and a to the same length
+fn normalized(b: &[f64], a: &[f64]) -> (Coefficients, Coefficients) {
+    let len = b.len().max(a.len());
+    let mut nb = Coefficients::new();
+    let mut na = Coefficients::new();
+    for i in 0..len {
+        nb.push(b.get(i).unwrap_or(&0.0) / a[0]).unwrap();
+        na.push(a.get(i).unwrap_or(&0.0) / a[0]).unwrap();
+    }
+    (nb, na)
+}
+
+/// Filter `x` in place with a transposed direct form II, the same structure
+/// as scipy's lfilter. `z` is the state, one shorter than the longer of b and
+/// a, and is left holding the final state.
+pub fn lfilter(b: &[f64], a: &[f64], z: &mut [f64], x: &mut [f64]) {
+    let (b, a) = normalized(b, a);
+    let n = b.len() - 1;
+    assert_eq!(
+        z.len(),
+        n,
+        "state must be one shorter than the coefficients"
+    );
+
+    for x in x.iter_mut() {
+        let input = *x;
+        let y = b[0] * input + z.first().unwrap_or(&0.0);
+        for k in 0..n {
+            let next = z.get(k + 1).cloned().unwrap_or(0.0);
+            z[k] = next + b[k + 1] * input - a[k + 1] * y;
+        }
+        *x = y;
+    }
+}
+
+/// Initial state for [`lfilter`] that corresponds to the steady state of a
+/// unit step input. Scale it by the first sample to start without a
+/// transient.
+pub fn lfilter_zi(b: &[f64], a: &[f64]) -> Coefficients {
+    let (b, a) = normalized(b, a);
+    let n = b.len() - 1;
+    let mut zi = Coefficients::new();
+    if n == 0 {
+        return zi;
+    }
+
+    // Solves zi = A zi + B for the companion matrix A, the way scipy does it
+    // without building the matrix
+    let first =
+        (1..=n).map(|k| b[k] - a[k] * b[0]).sum::<f64>() / (1.0 + a[1..].iter().sum::<f64>());
+    zi.push(first).unwrap();
+    let mut asum = 1.0;
+    let mut csum = 0.0;
+    for k in 1..n {
+        asum += a[k];
+        csum += b[k] - a[k] * b[0];
+        zi.push(asum * first - csum).unwrap();
+    }
+    zi
+}
+
+/// The input isn't longer than the padding [`filtfilt`] adds to each end
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TooShort {
+    pub needed: usize,
+}
+
+/// Forward-backward zero phase filtering with odd extension padding of
+/// 3 * max(len(a), len(b)) samples, scipy's defaults
+#[cfg(feature = "std")]
+pub fn filtfilt(b: &[f64], a: &[f64], x: &[f64]) -> Result<std::vec::Vec<f64>, TooShort> {
+    let pad = 3 * b.len().max(a.len());
+    if x.len() <= pad {
+        return Err(TooShort { needed: pad + 1 });
+    }
+
+    let (first, last) = (x[0], x[x.len() - 1]);
+    let mut ext = std::vec::Vec::with_capacity(x.len() + 2 * pad);
+    ext.extend(x[1..=pad].iter().rev().map(|x| 2.0 * first - x));
+    ext.extend_from_slice(x);
+    ext.extend(
+        x[x.len() - pad - 1..x.len() - 1]
+            .iter()
+            .rev()
+            .map(|x| 2.0 * last - x),
+    );
+
+    let zi = lfilter_zi(b, a);
+
+    let mut z = zi.iter().map(|z| z * ext[0]).collect::<std::vec::Vec<_>>();
+    lfilter(b, a, &mut z, &mut ext);
+
+    ext.reverse();
+    let mut z = zi.iter().map(|z| z * ext[0]).collect::<std::vec::Vec<_>>();
+    lfilter(b, a, &mut z, &mut ext);
+    ext.reverse();
+
+    ext.truncate(ext.len() - pad);
+    ext.drain(..pad);
+    Ok(ext)
+}
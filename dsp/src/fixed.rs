@@ -0,0 +1,262 @@
+//! Fixed point biquad cascades in Q15 and Q31, the way CMSIS-DSP's
+//! arm_biquad_cascade_df1_q15 and arm_biquad_cascade_df1_q31 run them.
+//!
+//! Coefficients have to fit in -1..1, and IIR coefficients usually don't (a1
+//! of a low pass is close to -2). Like CMSIS every coefficient is divided by
+//! 2^postShift so the largest one fits, the products are summed in a 64 bit
+//! accumulator, and the result is shifted back up by postShift on the way
+//! out. The low bits that don't fit the output are truncated like the CMSIS
+//! arithmetic shift does. That truncation error, fed back around the poles, is
+//! what produces limit cycles, and it can be shaped away from the passband
+//! with [`ErrorFeedback`].
+//!
+//! Unlike CMSIS the output is saturated instead of wrapping, and every clipped
+//! sample is counted in [`Cascade::saturations`].
+
+use crate::sos::{MaxSections, Section};
+
+/// Sample and coefficient format, i16 for Q15 and i32 for Q31
+pub trait Fixed: Copy + Default + PartialEq + core::fmt::Debug {
+    /// fractional bits, 15 or 31
+    const FRACTION: u32;
+    const MIN: i64;
+    const MAX: i64;
+
+    fn from_i64(v: i64) -> Self;
+    fn to_i64(self) -> i64;
+
+    /// Round a float into the format, saturating at the ends
+    fn from_f64(x: f64) -> Self {
+        let scaled = libm::round(x * (1i64 << Self::FRACTION) as f64);
+        Self::from_i64((scaled as i64).clamp(Self::MIN, Self::MAX))
+    }
+
+    fn to_f64(self) -> f64 {
+        self.to_i64() as f64 / (1i64 << Self::FRACTION) as f64
+    }
+}
+
+impl Fixed for i16 {
+    const FRACTION: u32 = 15;
+    const MIN: i64 = i16::MIN as i64;
+    const MAX: i64 = i16::MAX as i64;
+
+    fn from_i64(v: i64) -> Self {
+        v as i16
+    }
+
+    fn to_i64(self) -> i64 {
+        self as i64
+    }
+}
+
+impl Fixed for i32 {
+    const FRACTION: u32 = 31;
+    const MIN: i64 = i32::MIN as i64;
+    const MAX: i64 = i32::MAX as i64;
+
+    fn from_i64(v: i64) -> Self {
+        v as i32
+    }
+
+    fn to_i64(self) -> i64 {
+        self as i64
+    }
+}
+
+/// Feed the truncation error back into the next samples so its spectrum is
+/// shaped by (1 - z^-1) or (1 - z^-1)^2. That pushes the noise up towards
+/// Nyquist, away from the passband of a low pass with poles near z = 1, and
+/// also breaks up most limit cycles. Only worth it for low pass filters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorFeedback {
+    None,
+    FirstOrder,
+    SecondOrder,
+}
+
+/// b0, b1, b2, a1, a2 with a1 and a2 negated, the CMSIS layout
+type Coefficients<T> = [T; 5];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum QuantizeError {
+    /// a coefficient is infinite or NaN
+    NotFinite,
+    /// a coefficient is too large for any postShift, 2^FRACTION or more
+    TooLarge,
+}
+
+#[derive(Clone, Debug)]
+pub struct Cascade<T: Fixed> {
+    coefficients: heapless::Vec<Coefficients<T>, MaxSections>,
+    /// x[n-1], x[n-2], y[n-1], y[n-2] per section
+    state: heapless::Vec<[T; 4], MaxSections>,
+    /// truncation error e[n-1], e[n-2] per section, in accumulator units
+    error: heapless::Vec<[i64; 2], MaxSections>,
+    post_shift: u32,
+    feedback: ErrorFeedback,
+    /// Number of outputs of any section that had to be clipped
+    pub saturations: u32,
+}
+
+impl<T: Fixed> Cascade<T> {
+    /// Quantize sections, choosing the smallest postShift that fits every
+    /// coefficient. The postShift can't be more than the fraction bits, so
+    /// coefficients have to stay below 2^15 for Q15 and 2^31 for Q31.
+    pub fn new(sections: &[Section], feedback: ErrorFeedback) -> Result<Self, QuantizeError> {
+        let coefficients = || {
+            sections
+                .iter()
+                .flat_map(|s| s.b.iter().chain(s.a[1..].iter()))
+        };
+        // max skips NaN, so check before folding
+        if !coefficients().all(|c| c.is_finite()) {
+            return Err(QuantizeError::NotFinite);
+        }
+        let largest = coefficients().fold(0.0f64, |m, c| m.max(c.abs()));
+        let mut post_shift = 0;
+        while largest / (1u64 << post_shift) as f64 >= 1.0 {
+            if post_shift == T::FRACTION {
+                return Err(QuantizeError::TooLarge);
+            }
+            post_shift += 1;
+        }
+
+        let scale = (1u64 << post_shift) as f64;
+        let mut cascade = Self {
+            coefficients: heapless::Vec::new(),
+            state: heapless::Vec::new(),
+            error: heapless::Vec::new(),
+            post_shift,
+            feedback,
+            saturations: 0,
+        };
+        for s in sections {
+            let q = |c: f64| T::from_f64(c / scale);
+            cascade
+                .coefficients
+                .push([q(s.b[0]), q(s.b[1]), q(s.b[2]), q(-s.a[1]), q(-s.a[2])])
+                .unwrap();
+            cascade.state.push([T::default(); 4]).unwrap();
+            cascade.error.push([0; 2]).unwrap();
+        }
+        Ok(cascade)
+    }
+
+    /// The CMSIS postShift, coefficients are scaled down by 2^post_shift
+    pub fn post_shift(&self) -> u32 {
+        self.post_shift
+    }
+
+    /// Coefficients actually in use, back as floats with the postShift undone
+    /// and a0 put back
+    pub fn quantized(&self) -> heapless::Vec<Section, MaxSections> {
+        let scale = (1u64 << self.post_shift) as f64;
+        self.coefficients
+            .iter()
+            .map(|c| Section {
+                b: [
+                    c[0].to_f64() * scale,
+                    c[1].to_f64() * scale,
+                    c[2].to_f64() * scale,
+                ],
+                a: [1.0, -c[3].to_f64() * scale, -c[4].to_f64() * scale],
+            })
+            .collect()
+    }
+
+    pub fn reset(&mut self) {
+        self.state.iter_mut().for_each(|s| *s = [T::default(); 4]);
+        self.error.iter_mut().for_each(|e| *e = [0; 2]);
+        self.saturations = 0;
+    }
+
+    pub fn process(&mut self, x: T) -> T {
+        let shift = T::FRACTION - self.post_shift;
+        let mask = (1i64 << shift) - 1;
+        let feedback = self.feedback;
+        let saturations = &mut self.saturations;
+
+        self.coefficients
+            .iter()
+            .zip(self.state.iter_mut())
+            .zip(self.error.iter_mut())
+            .fold(x, |x, ((c, s), e)| {
+                let terms = [x, s[0], s[1], s[2], s[3]];
+                let mut acc = c.iter().zip(terms.iter()).fold(0i64, |acc, (c, t)| {
+                    acc.wrapping_add(c.to_i64() * t.to_i64())
+                });
+
+                acc = acc.wrapping_add(match feedback {
+                    ErrorFeedback::None => 0,
+                    ErrorFeedback::FirstOrder => e[0],
+                    ErrorFeedback::SecondOrder => 2 * e[0] - e[1],
+                });
+
+                // arithmetic shift floors, the error is what got dropped
+                let y = acc >> shift;
+                *e = [acc & mask, e[0]];
+
+                let clipped = y.clamp(T::MIN, T::MAX);
+                if clipped != y {
+                    *saturations += 1;
+                }
+                let y = T::from_i64(clipped);
+
+                *s = [x, s[0], y, s[2]];
+                y
+            })
+    }
+}
+
+/// Signal to noise ratio in dB of `actual` against `reference`
+pub fn snr_db(reference: &[f64], actual: &[f64]) -> f64 {
+    let (signal, noise) = reference
+        .iter()
+        .zip(actual.iter())
+        .fold((0.0, 0.0), |(s, n), (r, a)| {
+            (s + r * r, n + (r - a) * (r - a))
+        });
+    10.0 * libm::log10(signal / noise)
+}
+
+/// What's left once the input goes to zero
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LimitCycle {
+    /// largest output magnitude after the response should have died out, in
+    /// LSBs of the output format
+    pub amplitude: i64,
+    /// period in samples, 1 for a stuck nonzero output, None if it didn't
+    /// repeat within the samples looked at
+    pub period: Option<usize>,
+}
+
+/// Zero input test. Kick the filter with a single sample of `kick` to set its
+/// state, run it for `settle` samples which is plenty for the float filter to
+/// decay to nothing, and then check whether the output has died out. Returns
+/// the limit cycle if it didn't.
+pub fn limit_cycle<T: Fixed>(
+    cascade: &mut Cascade<T>,
+    kick: T,
+    settle: usize,
+) -> Option<LimitCycle> {
+    const WINDOW: usize = 256;
+
+    cascade.reset();
+    cascade.process(kick);
+    for _ in 0..settle {
+        cascade.process(T::default());
+    }
+
+    let mut tail = [0i64; WINDOW];
+    for t in tail.iter_mut() {
+        *t = cascade.process(T::default()).to_i64();
+    }
+
+    let amplitude = tail.iter().map(|y| y.abs()).max().unwrap();
+    if amplitude == 0 {
+        return None;
+    }
+    let period = (1..WINDOW / 2).find(|p| (0..WINDOW - p).all(|i| tail[i] == tail[i + p]));
+    Some(LimitCycle { amplitude, period })
+}
@@ -0,0 +1,249 @@
+//! Fourier series of periodic signals.
+//!
+//! A real signal with a period of P samples is a sum of harmonics of 2 pi/P,
+//! x[n] = sum c[k] e^(j 2 pi k n/P) for k from 0 to P - 1, with c[k] = X[k] / P
+//! the DFT of one period scaled down. Being real, c[P - k] is the conjugate of
+//! c[k], so the negative harmonics add the same again and only k = 0 to P/2
+//! need keeping, x[n] = c[0] + 2 sum |c[k]| cos(2 pi k n/P + arg c[k]) with
+//! the Nyquist term, when P is even, counted once like DC.
+//!
+//! [`Series`] holds the first K of those. It gets them from any number of
+//! whole periods of known length with [`Series::analyze`], or from the FFT of
+//! exactly one period with [`Series::from_fft`]. From there it reports the
+//! amplitude and phase of each harmonic, the total harmonic distortion, and the
+//! RMS error of rebuilding the signal from the first k harmonics, which comes
+//! straight from Parseval without synthesizing anything.
+//!
+//! [`Series::synthesize`] rebuilds the signal from any subset of the
+//! harmonics. Each one is a phasor turned by a fixed step every sample, so a
+//! sample costs one complex multiply per harmonic and no trig. The phasors are
+//! set back to 1 at the start of every period so rounding can't build up. The
+//! period doesn't have to be the one analyzed, synthesizing more samples per
+//! period draws the band limited signal in between the samples, which is where
+//! the Gibbs overshoot of a square wave shows up.
+//!
+//! ```
+//! use dsp::fourier::Series;
+//! use heapless::consts::U8;
+//!
+//! // a square wave, 16 samples per period
+//! let square = (0..64).map(|n| if n % 16 < 8 { 1.0 } else { -1.0 }).collect::<std::vec::Vec<f32>>();
+//! let series = Series::<U8>::analyze(&square, 16);
+//! // only odd harmonics
+//! assert!(series.amplitude(2) < 1e-6);
+//! assert!(series.amplitude(3) > 0.1);
+//!
+//! // the fundamental alone, in 16 samples
+//! let y = series.synthesize(16, |k| k == 1).take(16).collect::<std::vec::Vec<f32>>();
+//! let fundamental = series.amplitude(1) * series.phase(1).cos();
+//! assert!((y[0] - fundamental).abs() < 1e-5);
+//! ```
+
+use crate::Complex32;
+use core::f64::consts::PI;
+use heapless::{ArrayLength, Vec};
+
+/// The first K harmonics of a real signal with a period of P samples
+#[derive(Clone, Debug)]
+pub struct Series<K: ArrayLength<Complex32>> {
+    period: usize,
+    /// c[k] for k from 0
+    harmonics: Vec<Complex32, K>,
+    /// mean of x^2 over the samples analyzed, everything the harmonics could
+    /// add up to
+    mean_square: f32,
+}
+
+impl<K: ArrayLength<Complex32>> Series<K> {
+    /// Harmonics of x, which repeats every `period` samples. All the whole
+    /// periods in x are averaged, anything left over at the end isn't used.
+    pub fn analyze(x: &[f32], period: usize) -> Self {
+        assert!(period > 0, "period must be at least 1");
+        let periods = x.len() / period;
+        assert!(periods > 0, "x is shorter than a period");
+        let x = &x[..periods * period];
+        let len = x.len() as f64;
+
+        let held = K::to_usize().min(period / 2 + 1);
+        let harmonics = (0..held)
+            .map(|k| {
+                // straight DFT sum in f64, n wraps round the period by itself
+                let w = 2.0 * PI * k as f64 / period as f64;
+                let (re, im) = x.iter().enumerate().fold((0.0, 0.0), |(re, im), (n, x)| {
+                    let theta = w * (n % period) as f64;
+                    (
+                        re + *x as f64 * libm::cos(theta),
+                        im - *x as f64 * libm::sin(theta),
+                    )
+                });
+                Complex32::new((re / len) as f32, (im / len) as f32)
+            })
+            .collect();
+
+        let mean_square = x.iter().map(|x| *x as f64 * *x as f64).sum::<f64>() / len;
+        Self {
+            period,
+            harmonics,
+            mean_square: mean_square as f32,
+        }
+    }
+
+    /// Harmonics from the complex FFT of exactly one period, in FFT order
+    /// like [`Cfft`](crate::fft::Cfft) leaves it. The period is the FFT length.
+    pub fn from_fft(bins: &[Complex32]) -> Self {
+        let period = bins.len();
+        assert!(period > 0, "no bins");
+        let scale = 1.0 / period as f32;
+        let harmonics = bins
+            .iter()
+            .take(K::to_usize().min(period / 2 + 1))
+            .map(|c| c * scale)
+            .collect();
+
+        // Parseval, the mean square is the bins' power over P^2
+        let power = bins.iter().map(|c| c.norm_sqr() as f64).sum::<f64>();
+        Self {
+            period,
+            harmonics,
+            mean_square: (power / (period as f64 * period as f64)) as f32,
+        }
+    }
+
+    /// Samples per period
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Number of harmonics held, counting DC, at most K and P/2 + 1
+    pub fn len(&self) -> usize {
+        self.harmonics.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.harmonics.is_empty()
+    }
+
+    /// The complex coefficients c[k] from DC up. The negative harmonics are
+    /// their conjugates.
+    pub fn harmonics(&self) -> &[Complex32] {
+        &self.harmonics
+    }
+
+    /// How many times harmonic k is counted, once for DC and Nyquist and
+    /// twice for the rest where the negative harmonic adds the same again
+    fn fold(&self, k: usize) -> f32 {
+        if k == 0 || 2 * k == self.period {
+            1.0
+        } else {
+            2.0
+        }
+    }
+
+    /// Peak amplitude of the cosine harmonic k adds to the signal
+    pub fn amplitude(&self, k: usize) -> f32 {
+        let c = self.harmonics[k];
+        self.fold(k) * libm::sqrtf(c.norm_sqr())
+    }
+
+    /// Phase in radians of the cosine harmonic k adds to the signal
+    pub fn phase(&self, k: usize) -> f32 {
+        let c = self.harmonics[k];
+        libm::atan2f(c.im, c.re)
+    }
+
+    /// Mean square harmonic k adds to the signal
+    pub fn power(&self, k: usize) -> f32 {
+        self.fold(k) * self.harmonics[k].norm_sqr()
+    }
+
+    /// Mean square of the signal analyzed
+    pub fn mean_square(&self) -> f32 {
+        self.mean_square
+    }
+
+    /// Total harmonic distortion, the RMS of harmonics 2 and up over the RMS
+    /// of the fundamental, as a ratio. Only the harmonics held count. None if
+    /// there's no fundamental to compare against, because K or the period is
+    /// under 2 or the fundamental is lost in the rounding of the rest.
+    pub fn thd(&self) -> Option<f32> {
+        if self.len() < 2 || self.power(1) <= 1e-12 * self.mean_square {
+            return None;
+        }
+        let harmonics = (2..self.len()).map(|k| self.power(k)).sum::<f32>();
+        Some(libm::sqrtf(harmonics / self.power(1)))
+    }
+
+    /// RMS error of the signal rebuilt from harmonics 0 to k, by Parseval the
+    /// power of everything left out
+    pub fn error(&self, k: usize) -> f32 {
+        let kept = (0..=k.min(self.len() - 1))
+            .map(|k| self.power(k) as f64)
+            .sum::<f64>();
+        libm::sqrt((self.mean_square as f64 - kept).max(0.0)) as f32
+    }
+
+    /// Rebuild the signal from the harmonics `keep` says yes to, `len` samples
+    /// per period. With `len` the period analyzed and every harmonic kept this
+    /// is the signal again.
+    pub fn synthesize(&self, len: usize, keep: impl Fn(usize) -> bool) -> Synthesis<K> {
+        assert!(len > 0, "len must be at least 1");
+        let mut terms = Vec::new();
+        let mut steps = Vec::new();
+        for (k, c) in self.harmonics.iter().enumerate().filter(|(k, _)| keep(*k)) {
+            let theta = 2.0 * PI * k as f64 / len as f64;
+            terms.push(c * self.fold(k)).ok();
+            steps
+                .push(Complex32::new(
+                    libm::cos(theta) as f32,
+                    libm::sin(theta) as f32,
+                ))
+                .ok();
+        }
+        let phasors = steps.iter().map(|_| Complex32::new(1.0, 0.0)).collect();
+        Synthesis {
+            terms,
+            steps,
+            phasors,
+            len,
+            n: 0,
+        }
+    }
+}
+
+/// Endless samples of a [`Series`], one period after another
+#[derive(Clone, Debug)]
+pub struct Synthesis<K: ArrayLength<Complex32>> {
+    /// c[k] counted once or twice
+    terms: Vec<Complex32, K>,
+    /// e^(j 2 pi k/len)
+    steps: Vec<Complex32, K>,
+    phasors: Vec<Complex32, K>,
+    len: usize,
+    n: usize,
+}
+
+impl<K: ArrayLength<Complex32>> Iterator for Synthesis<K> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let mut y = 0.0;
+        for ((term, step), phasor) in self
+            .terms
+            .iter()
+            .zip(self.steps.iter())
+            .zip(self.phasors.iter_mut())
+        {
+            y += (term * *phasor).re;
+            *phasor *= step;
+        }
+
+        self.n += 1;
+        if self.n == self.len {
+            self.n = 0;
+            self.phasors
+                .iter_mut()
+                .for_each(|phasor| *phasor = Complex32::new(1.0, 0.0));
+        }
+        Some(y)
+    }
+}
@@ -0,0 +1,155 @@
+//! Frequency response of a difference equation, like matlab's freqz and
+//! grpdelay.
+//!
+//! H(e^jw) = (b0 + b1 e^-jw + ... ) / (a0 + a1 e^-jw + ...) is evaluated
+//! directly from the B and A coefficients at any frequency, no FFT and so no
+//! zero padding or fixed grid. Frequencies are in radians per sample, 0 to pi
+//! covers DC to Nyquist.
+//!
+//! Group delay is worked out exactly from the coefficients rather than by
+//! differentiating the phase, using
+//! -d/dw arg B(e^jw) = Re(sum n b_n e^-jwn / B(e^jw)).
+//! It's undefined where the response has a zero on the unit circle and comes
+//! back as NaN there.
+//!
+//! On the host [`Response`] evaluates a whole grid at once and plots it in Hz.
+
+use crate::complex;
+#[cfg(feature = "std")]
+use crate::spectrum::{phase, unwrap};
+use crate::Complex64;
+
+/// Evaluate the polynomial in e^-jw, also returning the sum weighted by the
+/// power of each term which the group delay needs
+fn eval<T: Copy + Into<f64>>(c: &[T], w: f64) -> (Complex64, Complex64) {
+    c.iter().enumerate().fold(
+        (Complex64::new(0.0, 0.0), Complex64::new(0.0, 0.0)),
+        |(sum, weighted), (n, c)| {
+            let term = complex::from_polar((*c).into(), -w * n as f64);
+            (sum + term, weighted + term * n as f64)
+        },
+    )
+}
+
+/// Complex response at w radians per sample
+pub fn freqz<T: Copy + Into<f64>>(b: &[T], a: &[T], w: f64) -> Complex64 {
+    eval(b, w).0 / eval(a, w).0
+}
+
+/// Group delay in samples at w radians per sample
+pub fn group_delay<T: Copy + Into<f64>>(b: &[T], a: &[T], w: f64) -> f64 {
+    let delay = |c: &[T]| {
+        let (sum, weighted) = eval(c, w);
+        if complex::abs(sum) <= f64::EPSILON * 1e3 {
+            f64::NAN
+        } else {
+            (weighted / sum).re
+        }
+    };
+    delay(b) - delay(a)
+}
+
+/// Magnitude in dB, floored at -300 so nulls stay plottable
+pub fn db(h: Complex64) -> f64 {
+    20.0 * libm::log10(complex::abs(h).max(1e-15))
+}
+
+/// Response over a grid of frequencies, with the different views of it
+/// available as std Vecs ready for plotting
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct Response {
+    /// sample rate the grid is in
+    pub fs: f64,
+    /// frequencies in Hz
+    pub hz: std::vec::Vec<f64>,
+    pub h: std::vec::Vec<Complex64>,
+    pub group_delay: std::vec::Vec<f64>,
+}
+
+#[cfg(feature = "std")]
+impl Response {
+    /// Evaluate at the given frequencies in Hz
+    pub fn at<T: Copy + Into<f64>>(b: &[T], a: &[T], fs: f64, hz: &[f64]) -> Self {
+        let w = |f: &f64| 2.0 * core::f64::consts::PI * f / fs;
+        Self {
+            fs,
+            hz: hz.to_vec(),
+            h: hz.iter().map(|f| freqz(b, a, w(f))).collect(),
+            group_delay: hz.iter().map(|f| group_delay(b, a, w(f))).collect(),
+        }
+    }
+
+    /// Evaluate at n evenly spaced frequencies from DC up to but not including
+    /// Nyquist, like freqz(b, a, n) does
+    pub fn new<T: Copy + Into<f64>>(b: &[T], a: &[T], fs: f64, n: usize) -> Self {
+        let hz = (0..n)
+            .map(|i| fs / 2.0 * i as f64 / n as f64)
+            .collect::<std::vec::Vec<_>>();
+        Self::at(b, a, fs, &hz)
+    }
+
+    pub fn magnitude(&self) -> std::vec::Vec<f64> {
+        self.h.iter().map(|h| complex::abs(*h)).collect()
+    }
+
+    pub fn magnitude_db(&self) -> std::vec::Vec<f64> {
+        self.h.iter().map(|h| db(*h)).collect()
+    }
+
+    pub fn phase(&self) -> std::vec::Vec<f64> {
+        let mut out = std::vec![0.0; self.h.len()];
+        phase(&self.h, &mut out);
+        out
+    }
+
+    pub fn unwrapped_phase(&self) -> std::vec::Vec<f64> {
+        let mut phase = self.phase();
+        unwrap(&mut phase);
+        phase
+    }
+
+    /// -phase / w in samples. At DC where that's 0/0 the group delay is the
+    /// limit.
+    pub fn phase_delay(&self) -> std::vec::Vec<f64> {
+        self.unwrapped_phase()
+            .iter()
+            .zip(self.hz.iter())
+            .zip(self.group_delay.iter())
+            .map(|((phase, hz), gd)| {
+                if *hz == 0.0 {
+                    *gd
+                } else {
+                    -phase / (2.0 * core::f64::consts::PI * hz / self.fs)
+                }
+            })
+            .collect()
+    }
+
+    /// Plot magnitude in dB, unwrapped phase, group delay and phase delay
+    /// against frequency in Hz
+    pub fn plot(&self, name: &str) {
+        self.plot_one(name, "magnitude (dB)", &self.magnitude_db());
+        self.plot_one(name, "phase (rad)", &self.unwrapped_phase());
+        self.plot_one(name, "group delay (samples)", &self.group_delay);
+        self.plot_one(name, "phase delay (samples)", &self.phase_delay());
+    }
+
+    /// Plot one view, like `response.plot_one("lpf", "magnitude", &response.magnitude())`
+    pub fn plot_one(&self, name: &str, label: &str, y: &[f64]) {
+        use textplots::{Chart, Plot, Shape};
+
+        std::println!("{} {} vs frequency (Hz):", name, label);
+        let points = self
+            .hz
+            .iter()
+            .zip(y.iter())
+            .filter(|(_, y)| y.is_finite())
+            .map(|(x, y)| (*x as f32, *y as f32))
+            .collect::<std::vec::Vec<_>>();
+        let (first, last) = (self.hz[0] as f32, self.hz[self.hz.len() - 1] as f32);
+        Chart::new(120, 60, first, last)
+            .lineplot(&Shape::Lines(&points[..]))
+            .display();
+    }
+}
@@ -0,0 +1,177 @@
+//! Goertzel detectors for when only a few frequencies matter.
+//!
+//! A Goertzel filter works out a single DFT bin with one multiply and two adds
+//! per sample. For a handful of tones that's cheaper than an FFT of the whole
+//! block, and it doesn't need the block in memory so it can run sample by
+//! sample from the sampling interrupt. It's a two pole resonator at the bin
+//! frequency, s[n] = x[n] + 2cos(w) s[n-1] - s[n-2], and the bin is read off
+//! the last two states.
+//!
+//! The frequency doesn't have to land on a bin, k can be fractional, the
+//! generalized Goertzel. The phase correction that needs is applied when the
+//! result is read so it costs nothing per sample. [`Bank`] runs several at
+//! once over the same samples.
+
+use crate::Complex32;
+use core::f64::consts::PI;
+use heapless::{ArrayLength, Vec};
+
+/// Single bin detector
+#[derive(Clone, Debug)]
+pub struct Goertzel {
+    /// bin frequency in radians per sample
+    w: f64,
+    /// 2cos(w)
+    coefficient: f32,
+    len: usize,
+    count: usize,
+    s1: f32,
+    s2: f32,
+}
+
+impl Goertzel {
+    /// Bin k of a `len` point DFT, k doesn't have to be a whole number
+    pub fn new(k: f32, len: usize) -> Self {
+        let w = 2.0 * PI * k as f64 / len as f64;
+        Self {
+            w,
+            coefficient: (2.0 * libm::cos(w)) as f32,
+            len,
+            count: 0,
+            s1: 0.0,
+            s2: 0.0,
+        }
+    }
+
+    /// The bin for f0 Hz at sample rate fs, over blocks of `len` samples
+    pub fn frequency(f0: f32, fs: f32, len: usize) -> Self {
+        Self::new(f0 * len as f32 / fs, len)
+    }
+
+    /// Block length
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn reset(&mut self) {
+        self.count = 0;
+        self.s1 = 0.0;
+        self.s2 = 0.0;
+    }
+
+    fn update(&mut self, x: f32) {
+        let s = x + self.coefficient * self.s1 - self.s2;
+        self.s2 = self.s1;
+        self.s1 = s;
+        self.count += 1;
+    }
+
+    /// Feed one sample. Every `len` samples this returns the bin and starts
+    /// over on the next block.
+    pub fn process(&mut self, x: f32) -> Option<Complex32> {
+        self.update(x);
+        if self.count < self.len {
+            return None;
+        }
+        let result = self.result();
+        self.reset();
+        Some(result)
+    }
+
+    /// The bin over a whole block from scratch, the same as the DFT sum
+    /// X = sum x[n] e^-jwn over however many samples x has
+    pub fn block(&mut self, x: &[f32]) -> Complex32 {
+        self.reset();
+        x.iter().for_each(|x| self.update(*x));
+        self.result()
+    }
+
+    /// The bin over the samples so far, phase referenced to the first one
+    pub fn result(&self) -> Complex32 {
+        if self.count == 0 {
+            return Complex32::default();
+        }
+        // the resonator output is X e^jw(count - 1)
+        let (sin, cos) = libm::sincos(self.w);
+        let y = Complex32::new(self.s1 - cos as f32 * self.s2, sin as f32 * self.s2);
+        let turn = (self.w * (self.count - 1) as f64) % (2.0 * PI);
+        let (sin, cos) = libm::sincos(turn);
+        y * Complex32::new(cos as f32, -sin as f32)
+    }
+
+    /// |X|^2 straight from the states, without the phase correction
+    pub fn power(&self) -> f32 {
+        self.s1 * self.s1 + self.s2 * self.s2 - self.coefficient * self.s1 * self.s2
+    }
+}
+
+/// |X| of a bin
+pub fn magnitude(x: Complex32) -> f32 {
+    libm::sqrtf(x.norm_sqr())
+}
+
+/// Phase of a bin in radians
+pub fn phase(x: Complex32) -> f32 {
+    libm::atan2f(x.im, x.re)
+}
+
+/// Several detectors fed the same samples
+#[derive(Clone, Debug)]
+pub struct Bank<M: ArrayLength<Goertzel> + ArrayLength<Complex32>> {
+    filters: Vec<Goertzel, M>,
+}
+
+impl<M: ArrayLength<Goertzel> + ArrayLength<Complex32>> Bank<M> {
+    /// Bins `k` of a `len` point DFT
+    pub fn new(k: &[f32], len: usize) -> Self {
+        Self {
+            filters: k.iter().map(|k| Goertzel::new(*k, len)).collect(),
+        }
+    }
+
+    /// The bins for each of `f0` in Hz at sample rate fs
+    pub fn frequencies(f0: &[f32], fs: f32, len: usize) -> Self {
+        Self {
+            filters: f0
+                .iter()
+                .map(|f0| Goertzel::frequency(*f0, fs, len))
+                .collect(),
+        }
+    }
+
+    pub fn filters(&self) -> &[Goertzel] {
+        &self.filters
+    }
+
+    pub fn reset(&mut self) {
+        self.filters.iter_mut().for_each(|f| f.reset());
+    }
+
+    /// Feed one sample to every detector, returns all the bins once a block
+    /// is complete
+    pub fn process(&mut self, x: f32) -> Option<Vec<Complex32, M>> {
+        let results = self
+            .filters
+            .iter_mut()
+            .filter_map(|f| f.process(x))
+            .collect::<Vec<Complex32, M>>();
+        if results.is_empty() {
+            None
+        } else {
+            Some(results)
+        }
+    }
+
+    /// Every bin over a whole block from scratch
+    pub fn block(&mut self, x: &[f32]) -> Vec<Complex32, M> {
+        self.reset();
+        for x in x {
+            self.filters.iter_mut().for_each(|f| f.update(*x));
+        }
+        self.filters.iter().map(|f| f.result()).collect()
+    }
+}
@@ -0,0 +1,155 @@
+//! Hilbert transform, the analytic signal and what can be read off it.
+//!
+//! The analytic signal of a real x is z = x + j H{x}, where the Hilbert
+//! transform H shifts every frequency by -90 degrees. Its spectrum is the
+//! positive frequencies of x doubled and nothing at the negative ones, so a
+//! tone A cos(wn + p) becomes the phasor A e^j(wn + p). The amplitude and
+//! angle of z at each sample are then the envelope and instantaneous phase of
+//! x, and the rate the angle turns is its instantaneous frequency. That's what
+//! vibration analysis wants, a bearing fault shows up as the envelope of a
+//! resonance pulsing at the fault rate.
+//!
+//! There are two ways to get z:
+//! - [`analytic`], for a block that's already in memory, does it exactly in
+//!   the frequency domain, zeroing the negative bins of an FFT. The FFT treats
+//!   the block as one period of a periodic signal, so the ends wrap into each
+//!   other and the first and last few samples are off unless x is periodic in
+//!   the block.
+//! - [`Fir`] does it sample by sample, from the sampling interrupt if need be.
+//!   It's the ideal Hilbert impulse response 2/(pi m) at odd m, windowed to N
+//!   taps. That delays the output by (N - 1)/2 samples, so the real part is x
+//!   delayed by the same amount to match. Being an FIR it can't reach down to
+//!   DC or up to Nyquist, the longer N the closer it gets.
+//!
+//! [`envelope`], [`phase`] and [`frequency`] read z one sample at a time so
+//! they work the same for both. The phase wraps at -pi to pi,
+//! [`unwrap`](crate::spectrum::unwrap) takes care of that for a block.
+//!
+//! ```
+//! use dsp::fft::Microfft;
+//! use dsp::hilbert::{analytic, envelope, frequency};
+//! use heapless::consts::U64;
+//!
+//! // 8 cycles of cos in 64 samples, the envelope is 1 and it turns 1/8 of
+//! // a cycle each sample
+//! let x = (0..64)
+//!     .map(|n| (core::f32::consts::PI * n as f32 / 4.0).cos())
+//!     .collect::<std::vec::Vec<f32>>();
+//! let z = analytic::<Microfft, U64>(&x);
+//! assert!(z.iter().all(|z| (envelope(*z) - 1.0).abs() < 1e-5));
+//! assert!((frequency(z[10], z[11], 1.0) - 0.125).abs() < 1e-5);
+//! ```
+
+use crate::fft::Cfft;
+use crate::window::Window;
+use crate::Complex32;
+use core::f64::consts::PI;
+use heapless::{ArrayLength, Vec};
+
+/// The analytic signal of the first N samples of x, zero padded if it's
+/// shorter, through the complex FFT of backend B
+pub fn analytic<B: Cfft<N>, N: ArrayLength<Complex32>>(x: &[f32]) -> Vec<Complex32, N> {
+    let mut z = x
+        .iter()
+        .take(N::to_usize())
+        .map(|x| Complex32::new(*x, 0.0))
+        .collect::<Vec<Complex32, N>>();
+    B::cfft(&mut z);
+
+    // positive frequencies doubled, DC and Nyquist belong to both halves so
+    // they stay, the negative ones go
+    let n = N::to_usize();
+    for (k, z) in z.iter_mut().enumerate().skip(1) {
+        if k < n.div_ceil(2) {
+            *z *= 2.0;
+        } else if 2 * k != n {
+            *z = Complex32::default();
+        }
+    }
+    B::icfft(&mut z);
+    z
+}
+
+/// Streaming FIR Hilbert transformer of N taps, N odd
+#[derive(Clone, Debug)]
+pub struct Fir<N: ArrayLength<f32>> {
+    /// taps at m = 1, 3, 5... past the middle, the ones before it are the
+    /// same negated and the even ones are 0
+    taps: Vec<f32, N>,
+    /// the last N samples, newest at `newest`
+    history: Vec<f32, N>,
+    newest: usize,
+}
+
+impl<N: ArrayLength<f32>> Fir<N> {
+    /// The ideal response cut to N taps by the symmetric `window`. Hamming or
+    /// Blackman keep the ripple down, rectangular has the narrowest
+    /// transition bands but ripples by about 10% near DC and Nyquist.
+    pub fn new(window: Window) -> Self {
+        let n = N::to_usize();
+        assert!(n & 1 == 1, "N must be odd");
+        let delay = n / 2;
+        let window = window.symmetric(n).collect::<Vec<f32, N>>();
+        let taps = (1..=delay)
+            .step_by(2)
+            .map(|m| (2.0 / (PI * m as f64)) as f32 * window[delay + m])
+            .collect();
+
+        let mut history = Vec::new();
+        history.resize(n, 0.0).unwrap();
+        Self {
+            taps,
+            history,
+            newest: 0,
+        }
+    }
+
+    /// Samples the output is behind the input, (N - 1)/2
+    pub fn delay(&self) -> usize {
+        N::to_usize() / 2
+    }
+
+    /// Start again from silence
+    pub fn reset(&mut self) {
+        self.history.iter_mut().for_each(|x| *x = 0.0);
+        self.newest = 0;
+    }
+
+    /// Feed one sample, get the analytic signal [`Fir::delay`] samples ago.
+    /// The real part is that sample and the imaginary part its Hilbert
+    /// transform.
+    pub fn process(&mut self, x: f32) -> Complex32 {
+        let n = N::to_usize();
+        self.newest = (self.newest + 1) % n;
+        self.history[self.newest] = x;
+
+        // sample n - j is j behind the newest
+        let past = |j: usize| self.history[(self.newest + n - j) % n];
+        let delay = self.delay();
+        let im = self
+            .taps
+            .iter()
+            .zip((1..=delay).step_by(2))
+            .map(|(h, m)| h * (past(delay + m) - past(delay - m)))
+            .sum();
+        Complex32::new(past(delay), im)
+    }
+}
+
+/// The envelope, the amplitude of z
+pub fn envelope(z: Complex32) -> f32 {
+    libm::sqrtf(z.norm_sqr())
+}
+
+/// Instantaneous phase in radians, wrapped to -pi to pi
+pub fn phase(z: Complex32) -> f32 {
+    libm::atan2f(z.im, z.re)
+}
+
+/// Instantaneous frequency in Hz between two consecutive samples of z at
+/// sample rate fs, from the angle the phasor turned through. That can't wrap
+/// so long as it's below fs/2.
+pub fn frequency(previous: Complex32, z: Complex32, fs: f32) -> f32 {
+    let turn = z * previous.conj();
+    libm::atan2f(turn.im, turn.re) * fs / (2.0 * core::f32::consts::PI)
+}
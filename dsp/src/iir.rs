@@ -0,0 +1,518 @@
+//! Classical IIR filter design, Butterworth, Chebyshev type I and II and
+//! elliptic (Cauer), following the same steps as matlab and scipy.signal:
+//!
+//! 1. an analog lowpass prototype with cutoff 1 rad/s, as zeros poles and gain
+//! 2. prewarp the digital band edges so they land in the right place after
+//!    the bilinear transform
+//! 3. frequency transform the prototype to the wanted lowpass, highpass,
+//!    bandpass or bandstop
+//! 4. bilinear transform to the z plane
+//!
+//! Frequencies are normalized like scipy's `Wn`, 1.0 is Nyquist, so a 100Hz
+//! cutoff at 8kHz sampling is `100.0 / 4000.0`. Everything is done in f64 and
+//! without allocation so designs can run on the host, in a build script, or
+//! on the board at runtime. Use [`sos::from_zpk`](crate::sos::from_zpk) to get
+//! second order sections to actually filter with.
+//!
+//! ```
+//! use dsp::iir::{butter, Band};
+//!
+//! let zpk = butter(4, Band::LowPass(0.2)).unwrap();
+//! let sections = dsp::sos::from_zpk(&zpk);
+//! assert_eq!(sections.len(), 2);
+//! ```
+
+use crate::complex;
+use crate::zpk::{Roots, Zpk};
+use crate::Complex64;
+use core::f64::consts::PI;
+
+/// Highest prototype order, band pass and band stop double it
+pub const MAX_ORDER: usize = 16;
+
+/// Band edges normalized to Nyquist, each in (0, 1)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Band {
+    LowPass(f64),
+    HighPass(f64),
+    BandPass(f64, f64),
+    BandStop(f64, f64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DesignError {
+    /// order has to be in 1..=MAX_ORDER
+    Order,
+    /// band edges have to be in (0, 1) and increasing
+    Frequency,
+    /// ripple and attenuation are positive dB values, with rs > rp
+    Ripple,
+}
+
+/// Butterworth, maximally flat passband, -3dB at the band edge
+pub fn butter(order: usize, band: Band) -> Result<Zpk, DesignError> {
+    iirfilter(buttap(order)?, band)
+}
+
+/// Chebyshev type I, `rp` dB of passband ripple, -rp dB at the band edge
+pub fn cheby1(order: usize, rp: f64, band: Band) -> Result<Zpk, DesignError> {
+    iirfilter(cheb1ap(order, rp)?, band)
+}
+
+/// Chebyshev type II, at least `rs` dB of stopband attenuation, the band edge
+/// is where the stopband starts
+pub fn cheby2(order: usize, rs: f64, band: Band) -> Result<Zpk, DesignError> {
+    iirfilter(cheb2ap(order, rs)?, band)
+}
+
+/// Elliptic, `rp` dB passband ripple and at least `rs` dB stopband
+/// attenuation, -rp dB at the band edge
+pub fn ellip(order: usize, rp: f64, rs: f64, band: Band) -> Result<Zpk, DesignError> {
+    iirfilter(ellipap(order, rp, rs)?, band)
+}
+
+/// Turn an analog lowpass prototype into a digital filter for the given band
+pub fn iirfilter(prototype: Zpk, band: Band) -> Result<Zpk, DesignError> {
+    // bilinear with fs = 2 so the normalized frequencies map straight through
+    let fs = 2.0;
+
+    let analog = match band {
+        Band::LowPass(wn) => lp2lp(&prototype, prewarp(check(wn)?, fs)),
+        Band::HighPass(wn) => lp2hp(&prototype, prewarp(check(wn)?, fs)),
+        Band::BandPass(w1, w2) | Band::BandStop(w1, w2) => {
+            if check(w1)? >= check(w2)? {
+                return Err(DesignError::Frequency);
+            }
+            let (w1, w2) = (prewarp(w1, fs), prewarp(w2, fs));
+            let bw = w2 - w1;
+            let wo = libm::sqrt(w1 * w2);
+            if let Band::BandPass(..) = band {
+                lp2bp(&prototype, wo, bw)
+            } else {
+                lp2bs(&prototype, wo, bw)
+            }
+        }
+    };
+
+    Ok(bilinear(&analog, fs))
+}
+
+fn check(wn: f64) -> Result<f64, DesignError> {
+    if wn > 0.0 && wn < 1.0 {
+        Ok(wn)
+    } else {
+        Err(DesignError::Frequency)
+    }
+}
+
+fn check_order(order: usize) -> Result<(), DesignError> {
+    if (1..=MAX_ORDER).contains(&order) {
+        Ok(())
+    } else {
+        Err(DesignError::Order)
+    }
+}
+
+/// Analog frequency in rad/s that the bilinear transform at `fs` maps to the
+/// normalized digital frequency `wn`
+pub fn prewarp(wn: f64, fs: f64) -> f64 {
+    2.0 * fs * libm::tan(PI * wn / fs)
+}
+
+/// Butterworth analog prototype, poles evenly spaced on the left half of the
+/// unit circle
+pub fn buttap(order: usize) -> Result<Zpk, DesignError> {
+    check_order(order)?;
+    let n = order as f64;
+    let p = (0..order)
+        .map(|m| {
+            let m = 2.0 * m as f64 - n + 1.0;
+            -complex::exp(Complex64::new(0.0, PI * m / (2.0 * n)))
+        })
+        .collect::<Roots>();
+
+    Ok(Zpk {
+        z: Roots::new(),
+        p,
+        k: 1.0,
+    })
+}
+
+/// Chebyshev type I analog prototype, poles on an ellipse
+pub fn cheb1ap(order: usize, rp: f64) -> Result<Zpk, DesignError> {
+    check_order(order)?;
+    if rp <= 0.0 {
+        return Err(DesignError::Ripple);
+    }
+    let n = order as f64;
+    let eps = libm::sqrt(libm::exp10(0.1 * rp) - 1.0);
+    let mu = libm::asinh(1.0 / eps) / n;
+
+    let p = (0..order)
+        .map(|m| {
+            let theta = PI * (2.0 * m as f64 - n + 1.0) / (2.0 * n);
+            -complex::sinh(Complex64::new(mu, theta))
+        })
+        .collect::<Roots>();
+
+    let mut k = product_neg(&p).re;
+    if order % 2 == 0 {
+        k /= libm::sqrt(1.0 + eps * eps);
+    }
+
+    Ok(Zpk {
+        z: Roots::new(),
+        p,
+        k,
+    })
+}
+
+/// Chebyshev type II (inverse Chebyshev) analog prototype, zeros on the
+/// imaginary axis give the equiripple stopband
+pub fn cheb2ap(order: usize, rs: f64) -> Result<Zpk, DesignError> {
+    check_order(order)?;
+    if rs <= 0.0 {
+        return Err(DesignError::Ripple);
+    }
+    let n = order as f64;
+    let de = 1.0 / libm::sqrt(libm::exp10(0.1 * rs) - 1.0);
+    let mu = libm::asinh(1.0 / de) / n;
+
+    // odd orders skip m = 0, that zero would be at infinity
+    let z = (0..order)
+        .map(|m| 2.0 * m as f64 - n + 1.0)
+        .filter(|m| *m != 0.0)
+        .map(|m| Complex64::new(0.0, 1.0 / libm::sin(m * PI / (2.0 * n))))
+        .collect::<Roots>();
+
+    let p = (0..order)
+        .map(|m| {
+            let m = 2.0 * m as f64 - n + 1.0;
+            let p = -complex::exp(Complex64::new(0.0, PI * m / (2.0 * n)));
+            Complex64::new(libm::sinh(mu) * p.re, libm::cosh(mu) * p.im).inv()
+        })
+        .collect::<Roots>();
+
+    let k = (product_neg(&p) / product_neg(&z)).re;
+
+    Ok(Zpk { z, p, k })
+}
+
+/// Elliptic analog prototype, equiripple in both bands. Same construction as
+/// scipy's ellipap, see Orfanidis "Lecture notes on elliptic filter design".
+pub fn ellipap(order: usize, rp: f64, rs: f64) -> Result<Zpk, DesignError> {
+    check_order(order)?;
+    if rp <= 0.0 || rs <= rp {
+        return Err(DesignError::Ripple);
+    }
+
+    let eps_sq = libm::exp10(0.1 * rp) - 1.0;
+    let eps = libm::sqrt(eps_sq);
+
+    if order == 1 {
+        let p = -libm::sqrt(1.0 / eps_sq);
+        return Ok(Zpk::new(&[], &[Complex64::new(p, 0.0)], -p));
+    }
+
+    let n = order as f64;
+    let ck1_sq = eps_sq / (libm::exp10(0.1 * rs) - 1.0);
+    let k1 = ellipk(ck1_sq);
+
+    // selectivity from the degree equation
+    let m = ellipdeg(order, ck1_sq);
+    let capk = ellipk(m);
+
+    let mut z = Roots::new();
+    let mut p = Roots::new();
+
+    let r = arc_jac_sc1(1.0 / eps, ck1_sq);
+    let v0 = capk * r / (n * k1);
+    let (sv, cv, dv) = ellipj(v0, 1.0 - m);
+
+    for j in ((1 - order % 2)..order).step_by(2) {
+        let (s, c, d) = ellipj(j as f64 * capk / n, m);
+
+        if s.abs() > f64::EPSILON {
+            let zero = Complex64::new(0.0, 1.0 / (libm::sqrt(m) * s));
+            z.push(zero).unwrap();
+            z.push(zero.conj()).unwrap();
+        }
+
+        let den = 1.0 - (d * sv) * (d * sv);
+        let pole = Complex64::new(-c * d * sv * cv / den, -s * dv / den);
+        p.push(pole).unwrap();
+        // the j = 0 pole of odd orders is real and has no conjugate
+        if pole.im.abs() > f64::EPSILON * complex::abs(pole) {
+            p.push(pole.conj()).unwrap();
+        }
+    }
+
+    let mut k = (product_neg(&p) / product_neg(&z)).re;
+    if order % 2 == 0 {
+        k /= libm::sqrt(1.0 + eps_sq);
+    }
+
+    Ok(Zpk { z, p, k })
+}
+
+/// Move the prototype cutoff from 1 to `wo` rad/s
+pub fn lp2lp(zpk: &Zpk, wo: f64) -> Zpk {
+    let degree = zpk.p.len() as i32 - zpk.z.len() as i32;
+    Zpk {
+        z: zpk.z.iter().map(|z| z * wo).collect(),
+        p: zpk.p.iter().map(|p| p * wo).collect(),
+        k: zpk.k * libm::pow(wo, degree as f64),
+    }
+}
+
+/// Lowpass prototype to highpass with cutoff `wo`, s -> wo/s
+pub fn lp2hp(zpk: &Zpk, wo: f64) -> Zpk {
+    let degree = zpk.p.len() - zpk.z.len();
+    let mut z = zpk
+        .z
+        .iter()
+        .map(|z| Complex64::new(wo, 0.0) / z)
+        .collect::<Roots>();
+    let p = zpk
+        .p
+        .iter()
+        .map(|p| Complex64::new(wo, 0.0) / p)
+        .collect::<Roots>();
+
+    // zeros at infinity move to the origin
+    for _ in 0..degree {
+        z.push(Complex64::new(0.0, 0.0)).unwrap();
+    }
+
+    Zpk {
+        z,
+        p,
+        k: zpk.k * (product_neg(&zpk.z) / product_neg(&zpk.p)).re,
+    }
+}
+
+/// Lowpass prototype to bandpass centered at `wo` with width `bw`,
+/// s -> (s^2 + wo^2) / (s*bw)
+pub fn lp2bp(zpk: &Zpk, wo: f64, bw: f64) -> Zpk {
+    let degree = zpk.p.len() - zpk.z.len();
+
+    let mut z = split(&zpk.z, |z| z * (bw / 2.0), wo);
+    let p = split(&zpk.p, |p| p * (bw / 2.0), wo);
+
+    // zeros at infinity, half stay there and half move to the origin
+    for _ in 0..degree {
+        z.push(Complex64::new(0.0, 0.0)).unwrap();
+    }
+
+    Zpk {
+        z,
+        p,
+        k: zpk.k * libm::pow(bw, degree as f64),
+    }
+}
+
+/// Lowpass prototype to bandstop centered at `wo` with width `bw`,
+/// s -> (s*bw) / (s^2 + wo^2)
+pub fn lp2bs(zpk: &Zpk, wo: f64, bw: f64) -> Zpk {
+    let degree = zpk.p.len() - zpk.z.len();
+
+    let mut z = split(&zpk.z, |z| Complex64::new(bw / 2.0, 0.0) / z, wo);
+    let p = split(&zpk.p, |p| Complex64::new(bw / 2.0, 0.0) / p, wo);
+
+    // zeros at infinity move to the notch at +-j*wo
+    for _ in 0..degree {
+        z.push(Complex64::new(0.0, wo)).unwrap();
+    }
+    for _ in 0..degree {
+        z.push(Complex64::new(0.0, -wo)).unwrap();
+    }
+
+    Zpk {
+        z,
+        p,
+        k: zpk.k * (product_neg(&zpk.z) / product_neg(&zpk.p)).re,
+    }
+}
+
+/// Each root r becomes the pair r' +- sqrt(r'^2 - wo^2) where r' = f(r),
+/// the + roots first then the - roots like scipy orders them
+fn split(roots: &Roots, f: impl Fn(&Complex64) -> Complex64, wo: f64) -> Roots {
+    let scaled = roots.iter().map(f).collect::<Roots>();
+    let root = |r: &Complex64| complex::sqrt(r * r - wo * wo);
+
+    scaled
+        .iter()
+        .map(|r| r + root(r))
+        .chain(scaled.iter().map(|r| r - root(r)))
+        .collect()
+}
+
+/// Analog to digital via s = 2*fs*(z-1)/(z+1). Zeros at infinity end up at
+/// Nyquist, z = -1.
+pub fn bilinear(zpk: &Zpk, fs: f64) -> Zpk {
+    let fs2 = Complex64::new(2.0 * fs, 0.0);
+    let degree = zpk.p.len() - zpk.z.len();
+
+    let mut z = zpk
+        .z
+        .iter()
+        .map(|z| (fs2 + z) / (fs2 - z))
+        .collect::<Roots>();
+    let p = zpk
+        .p
+        .iter()
+        .map(|p| (fs2 + p) / (fs2 - p))
+        .collect::<Roots>();
+
+    for _ in 0..degree {
+        z.push(Complex64::new(-1.0, 0.0)).unwrap();
+    }
+
+    let num = zpk
+        .z
+        .iter()
+        .fold(Complex64::new(1.0, 0.0), |acc, z| acc * (fs2 - z));
+    let den = zpk
+        .p
+        .iter()
+        .fold(Complex64::new(1.0, 0.0), |acc, p| acc * (fs2 - p));
+
+    Zpk {
+        z,
+        p,
+        k: zpk.k * (num / den).re,
+    }
+}
+
+/// prod(-r)
+fn product_neg(roots: &[Complex64]) -> Complex64 {
+    roots
+        .iter()
+        .fold(Complex64::new(1.0, 0.0), |acc, r| acc * -r)
+}
+
+/// Arithmetic-geometric mean
+fn agm(mut a: f64, mut b: f64) -> f64 {
+    while (a - b).abs() > f64::EPSILON * a {
+        let next = (a + b) / 2.0;
+        b = libm::sqrt(a * b);
+        a = next;
+    }
+    a
+}
+
+/// Complete elliptic integral of the first kind K(m), parameter m = k^2
+fn ellipk(m: f64) -> f64 {
+    PI / (2.0 * agm(1.0, libm::sqrt(1.0 - m)))
+}
+
+/// K(1 - m1) without cancellation for small m1
+fn ellipkm1(m1: f64) -> f64 {
+    PI / (2.0 * agm(1.0, libm::sqrt(m1)))
+}
+
+/// Jacobi elliptic functions sn, cn and dn of u with parameter m, via the
+/// descending Landen / AGM scale like cephes ellpj.
+fn ellipj(u: f64, m: f64) -> (f64, f64, f64) {
+    if m < 1.0e-9 {
+        let t = libm::sin(u);
+        let b = libm::cos(u);
+        let ai = 0.25 * m * (u - t * b);
+        return (t - ai * b, b + ai * t, 1.0 - 0.5 * m * t * t);
+    }
+
+    if m >= 0.999_999_999_9 {
+        let ai = 0.25 * (1.0 - m);
+        let b = libm::cosh(u);
+        let t = libm::tanh(u);
+        let phi = 1.0 / b;
+        let twon = b * libm::sinh(u);
+        let sn = t + ai * (twon - u) / (b * b);
+        let ai = ai * t * phi;
+        return (sn, phi - ai * (twon - u), phi + ai * (twon + u));
+    }
+
+    let mut a = [0.0; 9];
+    let mut c = [0.0; 9];
+    a[0] = 1.0;
+    c[0] = libm::sqrt(m);
+    let mut b = libm::sqrt(1.0 - m);
+    let mut twon = 1.0;
+    let mut i = 0;
+
+    while (c[i] / a[i]).abs() > f64::EPSILON / 2.0 && i < 8 {
+        let ai = a[i];
+        i += 1;
+        c[i] = (ai - b) / 2.0;
+        let t = libm::sqrt(ai * b);
+        a[i] = (ai + b) / 2.0;
+        b = t;
+        twon *= 2.0;
+    }
+
+    // backward recurrence
+    let mut phi = twon * a[i] * u;
+    let mut prev = phi;
+    while i > 0 {
+        let t = c[i] * libm::sin(phi) / a[i];
+        prev = phi;
+        phi = (libm::asin(t) + phi) / 2.0;
+        i -= 1;
+    }
+
+    let sn = libm::sin(phi);
+    let cn = libm::cos(phi);
+    let dnfac = libm::cos(phi - prev);
+    let dn = if dnfac.abs() < 0.1 {
+        libm::sqrt(1.0 - m * sn * sn)
+    } else {
+        cn / dnfac
+    };
+
+    (sn, cn, dn)
+}
+
+/// Solve the degree equation for the elliptic modulus m given the order and
+/// the discrimination m1, using the nome series.
+fn ellipdeg(order: usize, m1: f64) -> f64 {
+    const MMAX: i32 = 7;
+
+    let q1 = libm::exp(-PI * ellipkm1(m1) / ellipk(m1));
+    let q = libm::pow(q1, 1.0 / order as f64);
+
+    let num = (0..=MMAX)
+        .map(|j| libm::pow(q, (j * (j + 1)) as f64))
+        .sum::<f64>();
+    let den = 1.0
+        + 2.0
+            * (1..=MMAX)
+                .map(|j| libm::pow(q, (j * j) as f64))
+                .sum::<f64>();
+
+    16.0 * q * libm::pow(num / den, 4.0)
+}
+
+/// Real inverse of the Jacobi sc function, im(arcsn(j*w, m)), via the Landen
+/// sequence of moduli.
+fn arc_jac_sc1(w: f64, m: f64) -> f64 {
+    let complement = |kx: f64| libm::sqrt((1.0 - kx) * (1.0 + kx));
+
+    let mut ks = [0.0; 16];
+    ks[0] = libm::sqrt(m);
+    let mut len = 1;
+    while ks[len - 1] != 0.0 && len < ks.len() {
+        let kp = complement(ks[len - 1]);
+        ks[len] = (1.0 - kp) / (1.0 + kp);
+        len += 1;
+    }
+
+    let capk = ks[1..len].iter().map(|k| 1.0 + k).product::<f64>() * PI / 2.0;
+
+    // sn argument stays on the imaginary axis, track its imaginary part
+    let wn = ks[..len].windows(2).fold(w, |wn, k| {
+        let (kn, knext) = (k[0], k[1]);
+        2.0 * wn / ((1.0 + knext) * (1.0 + libm::sqrt(1.0 + (kn * wn) * (kn * wn))))
+    });
+
+    capk * 2.0 / PI * libm::asinh(wn)
+}
@@ -0,0 +1,47 @@
+//! Signal processing building blocks shared between the labs.
+//!
+//! Everything here is no_std and allocation free so it can run on the
+//! discovery board as well as natively. Fixed capacities come from heapless
+//! like in the examples. Helpers that only make sense on the host (returning
+//! std Vecs, writing files) are behind the default `std` feature, firmware
+//! should depend on this crate with `default-features = false`.
+
+#![no_std]
+
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod biquad;
+pub mod cepstrum;
+pub mod correlation;
+pub mod czt;
+pub mod dct;
+pub mod fft;
+pub mod filtfilt;
+pub mod fixed;
+pub mod fourier;
+pub mod freqz;
+pub mod goertzel;
+pub mod hilbert;
+pub mod iir;
+pub mod psd;
+pub mod pz;
+pub mod sdft;
+pub mod sos;
+pub mod spectrogram;
+pub mod spectrum;
+pub mod stft;
+pub mod window;
+pub mod zoom;
+pub mod zpk;
+
+pub(crate) mod complex;
+pub(crate) mod poly;
+
+pub use num_complex::Complex;
+
+/// Single precision complex, the sample format of the FFTs
+pub type Complex32 = Complex<f32>;
+
+/// Double precision complex, used for design calculations
+pub type Complex64 = Complex<f64>;
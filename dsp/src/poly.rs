@@ -0,0 +1,301 @@
+//! Polynomial helpers. Coefficients are in descending powers like matlab and
+//! numpy, so `[1.0, -0.5]` is `z - 0.5`, or `1 - 0.5z^-1` when used as a
+//! filter polynomial in z^-1.
+
+use crate::complex;
+use crate::zpk::{Coefficients, Roots};
+use crate::Complex64;
+
+/// Expand the monic polynomial with the given roots, keeping the real part.
+/// Roots have to come in conjugate pairs for the imaginary parts to cancel.
+pub fn from_roots(roots: &[Complex64]) -> Coefficients {
+    let mut c: heapless::Vec<Complex64, crate::zpk::MaxCoefficients> = heapless::Vec::new();
+    c.push(Complex64::new(1.0, 0.0)).unwrap();
+
+    for root in roots {
+        c.push(Complex64::new(0.0, 0.0)).unwrap();
+        for i in (1..c.len()).rev() {
+            let prev = c[i - 1];
+            c[i] -= prev * *root;
+        }
+    }
+
+    c.iter().map(|c| c.re).collect()
+}
+
+/// Roots of the polynomial as the eigenvalues of its companion matrix, the
+/// same way numpy's roots does. The QR iteration is backward stable, so even
+/// clusters from repeated roots multiply back out to the right polynomial.
+/// Leading zero coefficients are dropped and trailing ones become exact roots
+/// at 0. Complex roots come back as exact conjugate pairs. None if there are
+/// more coefficients than a [`Coefficients`] holds or the QR iteration doesn't
+/// converge, rather than a root that's wrong.
+#[allow(clippy::needless_range_loop)]
+pub fn roots(c: &[f64]) -> Option<Roots> {
+    if c.len() > Coefficients::new().capacity() {
+        return None;
+    }
+    let mut roots = Roots::new();
+
+    let start = c.iter().position(|c| *c != 0.0).unwrap_or(c.len());
+    let end = c.iter().rposition(|c| *c != 0.0).map_or(start, |i| i + 1);
+    for _ in end..c.len() {
+        roots.push(Complex64::new(0.0, 0.0)).unwrap();
+    }
+    let c = &c[start..end];
+    if c.len() < 2 {
+        return Some(roots);
+    }
+
+    // companion matrix, already upper Hessenberg. 1 based to follow the
+    // EISPACK code below, and big enough for a full Coefficients.
+    let n = c.len() - 1;
+    let mut a = [[0.0f64; 33]; 33];
+    for j in 1..=n {
+        a[1][j] = -c[j] / c[0];
+    }
+    for i in 2..=n {
+        a[i][i - 1] = 1.0;
+    }
+    balance(&mut a, n);
+
+    for mut r in hqr(&mut a, n)? {
+        // A double root usually splits into a pair with an imaginary part
+        // around 1e-8, call those real
+        if r.im.abs() <= 1e-7 * complex::abs(r).max(1.0) {
+            r.im = 0.0;
+        }
+        roots.push(r).unwrap();
+    }
+    Some(roots)
+}
+
+/// Scale rows and columns by powers of 2 so their norms are comparable, which
+/// keeps the eigenvalues of badly scaled companion matrices accurate
+#[allow(clippy::needless_range_loop)]
+fn balance(a: &mut [[f64; 33]; 33], n: usize) {
+    const RADIX: f64 = 2.0;
+    loop {
+        let mut done = true;
+        for i in 1..=n {
+            let (mut c, mut r) = (0.0, 0.0);
+            for j in (1..=n).filter(|j| *j != i) {
+                c += a[j][i].abs();
+                r += a[i][j].abs();
+            }
+            if c == 0.0 || r == 0.0 {
+                continue;
+            }
+            let s = c + r;
+            let mut f = 1.0;
+            while c < r / RADIX {
+                f *= RADIX;
+                c *= RADIX * RADIX;
+            }
+            while c > r * RADIX {
+                f /= RADIX;
+                c /= RADIX * RADIX;
+            }
+            if (c + r) / f < 0.95 * s {
+                done = false;
+                for j in 1..=n {
+                    a[i][j] /= f;
+                    a[j][i] *= f;
+                }
+            }
+        }
+        if done {
+            break;
+        }
+    }
+}
+
+/// Eigenvalues of an upper Hessenberg matrix by the shifted QR algorithm,
+/// EISPACK's hqr as given in Numerical Recipes. Destroys `a`. None if an
+/// eigenvalue takes more than 60 iterations.
+#[allow(clippy::needless_range_loop)]
+fn hqr(a: &mut [[f64; 33]; 33], n: usize) -> Option<Roots> {
+    let mut eigenvalues = Roots::new();
+    eigenvalues.resize(n, Complex64::new(0.0, 0.0)).unwrap();
+    let mut set = |i: isize, re: f64, im: f64| eigenvalues[i as usize - 1] = Complex64::new(re, im);
+
+    let mut anorm = 0.0;
+    for i in 1..=n {
+        for j in (i - 1).max(1)..=n {
+            anorm += a[i][j].abs();
+        }
+    }
+
+    let at = |i: isize| i as usize;
+    let mut nn = n as isize;
+    let mut t = 0.0;
+    while nn >= 1 {
+        let mut its = 0;
+        loop {
+            // look for a single small subdiagonal element to split at
+            let mut l = nn;
+            while l >= 2 {
+                let mut s = a[at(l - 1)][at(l - 1)].abs() + a[at(l)][at(l)].abs();
+                if s == 0.0 {
+                    s = anorm;
+                }
+                if a[at(l)][at(l - 1)].abs() + s == s {
+                    a[at(l)][at(l - 1)] = 0.0;
+                    break;
+                }
+                l -= 1;
+            }
+
+            let mut x = a[at(nn)][at(nn)];
+            if l == nn {
+                // one root found
+                set(nn, x + t, 0.0);
+                nn -= 1;
+                break;
+            }
+
+            let mut y = a[at(nn - 1)][at(nn - 1)];
+            let mut w = a[at(nn)][at(nn - 1)] * a[at(nn - 1)][at(nn)];
+            if l == nn - 1 {
+                // two roots found
+                let p = 0.5 * (y - x);
+                let q = p * p + w;
+                let mut z = libm::sqrt(q.abs());
+                x += t;
+                if q >= 0.0 {
+                    z = p + if p >= 0.0 { z } else { -z };
+                    set(nn - 1, x + z, 0.0);
+                    set(nn, if z != 0.0 { x - w / z } else { x + z }, 0.0);
+                } else {
+                    set(nn - 1, x + p, -z);
+                    set(nn, x + p, z);
+                }
+                nn -= 2;
+                break;
+            }
+
+            if its == 60 {
+                // never seen with filter polynomials, but a made up root
+                // would go on to get a stability verdict
+                return None;
+            }
+            if its % 10 == 0 && its > 0 {
+                // exceptional shift
+                t += x;
+                for i in 1..=at(nn) {
+                    a[i][i] -= x;
+                }
+                let s = a[at(nn)][at(nn - 1)].abs() + a[at(nn - 1)][at(nn - 2)].abs();
+                x = 0.75 * s;
+                y = x;
+                w = -0.4375 * s * s;
+            }
+            its += 1;
+
+            // form the shift and look for two consecutive small subdiagonal
+            // elements
+            let mut m = nn - 2;
+            let (mut p, mut q, mut r, mut z);
+            loop {
+                z = a[at(m)][at(m)];
+                r = x - z;
+                let s = y - z;
+                p = (r * s - w) / a[at(m + 1)][at(m)] + a[at(m)][at(m + 1)];
+                q = a[at(m + 1)][at(m + 1)] - z - r - s;
+                r = a[at(m + 2)][at(m + 1)];
+                let s = p.abs() + q.abs() + r.abs();
+                p /= s;
+                q /= s;
+                r /= s;
+                if m == l {
+                    break;
+                }
+                let u = a[at(m)][at(m - 1)].abs() * (q.abs() + r.abs());
+                let v = p.abs()
+                    * (a[at(m - 1)][at(m - 1)].abs() + z.abs() + a[at(m + 1)][at(m + 1)].abs());
+                if u + v == v {
+                    break;
+                }
+                m -= 1;
+            }
+            for i in m + 2..=nn {
+                a[at(i)][at(i - 2)] = 0.0;
+                if i != m + 2 {
+                    a[at(i)][at(i - 3)] = 0.0;
+                }
+            }
+
+            // double QR step on rows l to nn and columns m to nn
+            for k in m..nn {
+                if k != m {
+                    p = a[at(k)][at(k - 1)];
+                    q = a[at(k + 1)][at(k - 1)];
+                    r = if k != nn - 1 {
+                        a[at(k + 2)][at(k - 1)]
+                    } else {
+                        0.0
+                    };
+                    x = p.abs() + q.abs() + r.abs();
+                    if x != 0.0 {
+                        p /= x;
+                        q /= x;
+                        r /= x;
+                    }
+                }
+                let s = libm::sqrt(p * p + q * q + r * r);
+                let s = if p >= 0.0 { s } else { -s };
+                if s == 0.0 {
+                    continue;
+                }
+                if k == m {
+                    if l != m {
+                        a[at(k)][at(k - 1)] = -a[at(k)][at(k - 1)];
+                    }
+                } else {
+                    a[at(k)][at(k - 1)] = -s * x;
+                }
+                p += s;
+                x = p / s;
+                y = q / s;
+                z = r / s;
+                q /= p;
+                r /= p;
+                for j in k..=nn {
+                    let mut p = a[at(k)][at(j)] + q * a[at(k + 1)][at(j)];
+                    if k != nn - 1 {
+                        p += r * a[at(k + 2)][at(j)];
+                        a[at(k + 2)][at(j)] -= p * z;
+                    }
+                    a[at(k + 1)][at(j)] -= p * y;
+                    a[at(k)][at(j)] -= p * x;
+                }
+                for i in l..=nn.min(k + 3) {
+                    let mut p = x * a[at(i)][at(k)] + y * a[at(i)][at(k + 1)];
+                    if k != nn - 1 {
+                        p += z * a[at(i)][at(k + 2)];
+                        a[at(i)][at(k + 2)] -= p * r;
+                    }
+                    a[at(i)][at(k + 1)] -= p * q;
+                    a[at(i)][at(k)] -= p;
+                }
+            }
+
+            if l >= nn - 1 {
+                break;
+            }
+        }
+    }
+    Some(eigenvalues)
+}
+
+/// Product of two polynomials, the convolution of their coefficients
+pub fn multiply(a: &[f64], b: &[f64]) -> Coefficients {
+    let mut c = Coefficients::new();
+    c.resize(a.len() + b.len() - 1, 0.0).unwrap();
+    for (i, a) in a.iter().enumerate() {
+        for (j, b) in b.iter().enumerate() {
+            c[i + j] += a * b;
+        }
+    }
+    c
+}
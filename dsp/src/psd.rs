@@ -0,0 +1,179 @@
+//! Power spectral density estimates, in units^2/Hz.
+//!
+//! The periodogram of one block is |X[k]|^2 / (fs sum w[n]^2), the window
+//! normalized out so white noise of variance s^2 reads the same level whatever
+//! the window. It's an unbiased but very noisy estimate, each bin is off by
+//! about 100% no matter how long the block.
+//!
+//! Bartlett's method cuts the signal into K segments, takes the periodogram of
+//! each and averages them, which brings the spread down by sqrt(K) at the cost
+//! of K times coarser bins. Welch's method windows the segments and overlaps
+//! them, 50% with a Hann window gets nearly twice the segments out of the same
+//! samples for almost the same independence. [`Welch`] does all three, the
+//! periodogram being a single segment and Bartlett a rectangular window with no
+//! overlap.
+//!
+//! The output is one-sided, the N/2 + 1 bins from DC to fs/2 with everything
+//! but DC and Nyquist doubled, so summing it times fs/N gives the mean square
+//! of the signal and white noise of variance s^2 reads 2 s^2/fs. Segments
+//! are N samples long, the length of the heapless::Vec the real FFT of backend
+//! B works on, and are the only thing held, so the estimate runs on target
+//! with no more memory than one FFT.
+//!
+//! ```
+//! use dsp::fft::Microfft;
+//! use dsp::psd::{Detrend, Welch};
+//! use dsp::window::Window;
+//!
+//! // 64 point segments overlapping by half, sampled at 100 Hz
+//! let welch =
+//!     Welch::<Microfft, heapless::consts::U64>::new(Window::Hann, 32, Detrend::Mean, 100.0);
+//! let x = [1.0f32; 256];
+//! let mut psd = [0.0; 33];
+//! assert_eq!(welch.estimate(&x, &mut psd), 7);
+//! // a constant is all trend
+//! assert!(psd.iter().all(|p| p.abs() < 1e-6));
+//! ```
+
+use crate::fft::Rfft;
+use crate::spectrum::bins;
+use crate::window::Window;
+use core::marker::PhantomData;
+use heapless::{ArrayLength, Vec};
+
+/// What to take out of each segment before the FFT
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Detrend {
+    None,
+    /// The mean, so an offset doesn't leak from DC into the low bins
+    Mean,
+    /// The least squares straight line, for drift
+    Linear,
+}
+
+/// PSD estimator with segments of N samples, using the real FFT of backend B
+pub struct Welch<B, N: ArrayLength<f32>> {
+    window: Vec<f32, N>,
+    overlap: usize,
+    detrend: Detrend,
+    fs: f32,
+    /// 1 / (fs sum w^2)
+    scale: f32,
+    backend: PhantomData<B>,
+}
+
+impl<B: Rfft<N>, N: ArrayLength<f32>> Welch<B, N> {
+    /// Segments multiplied by the periodic `window`, each starting N -
+    /// `overlap` samples after the last, at sample rate fs
+    pub fn new(window: Window, overlap: usize, detrend: Detrend, fs: f32) -> Self {
+        assert!(overlap < N::to_usize(), "overlap must be less than N");
+        let window = window.periodic(N::to_usize()).collect::<Vec<f32, N>>();
+        let squares = window.iter().map(|w| *w as f64 * *w as f64).sum::<f64>();
+        Self {
+            window,
+            overlap,
+            detrend,
+            fs,
+            scale: (1.0 / (fs as f64 * squares)) as f32,
+            backend: PhantomData,
+        }
+    }
+
+    /// Bartlett's method, rectangular segments that don't overlap
+    pub fn bartlett(fs: f32) -> Self {
+        Self::new(Window::Rectangular, 0, Detrend::None, fs)
+    }
+
+    /// The periodogram of N samples. Longer signals are cut into segments
+    /// like [`Welch::bartlett`] but with the window.
+    pub fn periodogram(window: Window, fs: f32) -> Self {
+        Self::new(window, 0, Detrend::None, fs)
+    }
+
+    /// Samples from the start of one segment to the start of the next
+    pub fn step(&self) -> usize {
+        N::to_usize() - self.overlap
+    }
+
+    /// How many whole segments fit in `len` samples, anything left over at
+    /// the end isn't used
+    pub fn segments(&self, len: usize) -> usize {
+        if len < N::to_usize() {
+            0
+        } else {
+            (len - N::to_usize()) / self.step() + 1
+        }
+    }
+
+    /// Width of a bin in Hz
+    pub fn resolution(&self) -> f32 {
+        self.fs / N::to_usize() as f32
+    }
+
+    /// Frequency in Hz of each of the N/2 + 1 bins
+    pub fn frequencies(&self) -> impl Iterator<Item = f32> + Clone {
+        let step = self.resolution();
+        (0..N::to_usize() / 2 + 1).map(move |k| k as f32 * step)
+    }
+
+    /// Average the periodograms of the segments of x into the N/2 + 1 bins of
+    /// out, in units of x squared per Hz. Returns the number of segments,
+    /// x has to hold at least one.
+    pub fn estimate(&self, x: &[f32], out: &mut [f32]) -> usize {
+        let n = N::to_usize();
+        let segments = self.segments(x.len());
+        assert!(segments > 0, "x is shorter than a segment");
+        assert!(out.len() > n / 2, "out needs N/2 + 1 bins");
+
+        out[..=n / 2].iter_mut().for_each(|p| *p = 0.0);
+        let mut segment = Vec::<f32, N>::new();
+        for s in 0..segments {
+            while segment.pop().is_some() {}
+            let start = s * self.step();
+            segment.extend_from_slice(&x[start..start + n]).unwrap();
+            detrend(&mut segment, self.detrend);
+            segment
+                .iter_mut()
+                .zip(self.window.iter())
+                .for_each(|(x, w)| *x *= w);
+
+            for (p, x) in out.iter_mut().zip(bins(B::rfft(&mut segment))) {
+                *p += x.norm_sqr();
+            }
+        }
+
+        // one-sided, the negative frequencies fold onto all but DC and Nyquist
+        let scale = self.scale / segments as f32;
+        for (k, p) in out[..=n / 2].iter_mut().enumerate() {
+            let fold = if k == 0 || k == n / 2 { 1.0 } else { 2.0 };
+            *p *= fold * scale;
+        }
+        segments
+    }
+}
+
+/// Take the mean or least squares line out of x in place
+pub fn detrend(x: &mut [f32], detrend: Detrend) {
+    let len = x.len() as f64;
+    let mean = x.iter().map(|x| *x as f64).sum::<f64>() / len;
+    match detrend {
+        Detrend::None => {}
+        Detrend::Mean => x.iter_mut().for_each(|x| *x -= mean as f32),
+        Detrend::Linear => {
+            // the line through the mean at the middle sample
+            let middle = (len - 1.0) / 2.0;
+            let (covariance, variance) = x.iter().enumerate().fold((0.0, 0.0), |(c, v), (n, x)| {
+                let t = n as f64 - middle;
+                (c + t * (*x as f64 - mean), v + t * t)
+            });
+            let slope = if variance > 0.0 {
+                covariance / variance
+            } else {
+                0.0
+            };
+            for (n, x) in x.iter_mut().enumerate() {
+                *x -= (mean + slope * (n as f64 - middle)) as f32;
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Pole-zero analysis of a digital filter.
+//!
+//! Get a [`Zpk`] from difference equation coefficients with [`Zpk::from_tf`]
+//! or straight from one of the designers, then [`analyze`] it. If the roots
+//! can't be found `from_tf` says so with None, so there's never a verdict on
+//! made up poles. A causal filter is stable when every pole is strictly
+//! inside the unit circle, and minimum phase when, on top of that, no zero is
+//! outside it.
+//!
+//! On the host [`plot`] draws the z-plane in the terminal like the other
+//! examples do with their signals, and [`svg`] returns an image to save.
+
+use crate::complex;
+use crate::zpk::Zpk;
+
+/// How close to the unit circle a root has to be to count as on it. Repeated
+/// roots only come out of the root finder to about this precision.
+pub const UNIT_CIRCLE_TOLERANCE: f64 = 1e-6;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stability {
+    /// all poles inside the unit circle, the impulse response dies out
+    Stable,
+    /// simple poles on the unit circle, the impulse response neither dies out
+    /// nor grows, like an oscillator or an accumulator
+    Marginal,
+    /// a pole outside or a repeated pole on the unit circle, the output
+    /// grows without bound
+    Unstable,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Analysis {
+    pub stability: Stability,
+    /// no zeros outside the unit circle. Zeros on the circle, like the ones at
+    /// z = -1 every Butterworth low pass has, are allowed the same as matlab's
+    /// isminphase does.
+    pub minimum_phase: bool,
+    /// radius of the outermost pole, how fast the impulse response decays
+    pub max_pole_radius: f64,
+    /// radius of the outermost zero
+    pub max_zero_radius: f64,
+}
+
+pub fn analyze(zpk: &Zpk) -> Analysis {
+    let radius =
+        |roots: &[crate::Complex64]| roots.iter().map(|r| complex::abs(*r)).fold(0.0, f64::max);
+    let max_pole_radius = radius(&zpk.p);
+    let max_zero_radius = radius(&zpk.z);
+
+    let on_circle = |r: &crate::Complex64| (complex::abs(*r) - 1.0).abs() <= UNIT_CIRCLE_TOLERANCE;
+    let repeated = zpk.p.iter().enumerate().any(|(i, p)| {
+        on_circle(p)
+            && zpk.p[i + 1..]
+                .iter()
+                .any(|q| complex::abs(p - q) <= libm::sqrt(UNIT_CIRCLE_TOLERANCE))
+    });
+
+    let stability = if max_pole_radius > 1.0 + UNIT_CIRCLE_TOLERANCE || repeated {
+        Stability::Unstable
+    } else if zpk.p.iter().any(on_circle) {
+        Stability::Marginal
+    } else {
+        Stability::Stable
+    };
+
+    Analysis {
+        stability,
+        minimum_phase: stability == Stability::Stable
+            && max_zero_radius <= 1.0 + UNIT_CIRCLE_TOLERANCE,
+        max_pole_radius,
+        max_zero_radius,
+    }
+}
+
+/// Plot extent, the unit circle plus anything sticking out of it
+#[cfg(feature = "std")]
+fn extent(zpk: &Zpk) -> f64 {
+    zpk.p
+        .iter()
+        .chain(zpk.z.iter())
+        .map(|r| complex::abs(*r))
+        .fold(1.0, f64::max)
+        * 1.1
+}
+
+/// Draw the z-plane in the terminal. Poles are x, zeros are o, the dotted
+/// lines are the axes.
+#[cfg(feature = "std")]
+pub fn plot(name: &str, zpk: &Zpk) {
+    use std::vec::Vec;
+    use textplots::{Chart, Plot, Shape};
+
+    let r = extent(zpk) as f32;
+    // marker size in plot units, a couple of braille dots across
+    let m = r / 25.0;
+    let steps = |n: usize| (0..=n).map(move |i| i as f32 / n as f32);
+    let arc = move |x: f32, y: f32, radius: f32, n: usize| {
+        steps(n).map(move |t| {
+            let theta = 2.0 * core::f32::consts::PI * t;
+            (
+                x + radius * libm::cosf(theta),
+                y + radius * libm::sinf(theta),
+            )
+        })
+    };
+
+    // Lines would join everything up, so trace each mark as a run of points
+    let mut points: Vec<(f32, f32)> = Vec::new();
+    // dotted axes
+    points.extend(steps(40).map(|t| (-r + 2.0 * r * t, 0.0)));
+    points.extend(steps(40).map(|t| (0.0, -r + 2.0 * r * t)));
+    points.extend(arc(0.0, 0.0, 1.0, 300));
+    for p in zpk.p.iter() {
+        let (x, y) = (p.re as f32, p.im as f32);
+        points.extend(steps(8).map(|t| (x - m + 2.0 * m * t, y - m + 2.0 * m * t)));
+        points.extend(steps(8).map(|t| (x - m + 2.0 * m * t, y + m - 2.0 * m * t)));
+    }
+    for z in zpk.z.iter() {
+        points.extend(arc(z.re as f32, z.im as f32, m, 16));
+    }
+
+    std::println!("{:?}: {:?}", name, analyze(zpk));
+    // braille dots are about square so equal width and height keep the
+    // circle round
+    Chart::new(120, 120, -r, r)
+        .lineplot(&Shape::Points(&points[..]))
+        .display();
+}
+
+/// Render the z-plane as a standalone SVG document, poles as x and zeros as o
+/// on top of the unit circle.
+#[cfg(feature = "std")]
+pub fn svg(name: &str, zpk: &Zpk) -> std::string::String {
+    use core::fmt::Write;
+
+    const SIZE: f64 = 400.0;
+    let r = extent(zpk);
+    let scale = SIZE / 2.0 / r;
+    // svg y points down
+    let to_px = |re: f64, im: f64| (SIZE / 2.0 + re * scale, SIZE / 2.0 - im * scale);
+    let m = 6.0;
+
+    let mut out = std::string::String::new();
+    let analysis = analyze(zpk);
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{s}" height="{t}" viewBox="0 0 {s} {t}">"#,
+        s = SIZE,
+        t = SIZE + 30.0
+    )
+    .unwrap();
+    writeln!(out, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+    writeln!(
+        out,
+        r##"<g stroke="#999" stroke-dasharray="4 4"><line x1="0" y1="{c}" x2="{s}" y2="{c}"/><line x1="{c}" y1="0" x2="{c}" y2="{s}"/></g>"##,
+        c = SIZE / 2.0,
+        s = SIZE
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"<circle cx="{c}" cy="{c}" r="{r}" fill="none" stroke="black"/>"#,
+        c = SIZE / 2.0,
+        r = scale
+    )
+    .unwrap();
+
+    for z in zpk.z.iter() {
+        let (x, y) = to_px(z.re, z.im);
+        writeln!(
+            out,
+            r#"<circle cx="{:.2}" cy="{:.2}" r="{}" fill="none" stroke="blue" stroke-width="2"/>"#,
+            x, y, m
+        )
+        .unwrap();
+    }
+    for p in zpk.p.iter() {
+        let (x, y) = to_px(p.re, p.im);
+        writeln!(
+            out,
+            r#"<path d="M{:.2} {:.2}l{m} {m}m0 -{m}l-{m} {m}" stroke="red" stroke-width="2"/>"#,
+            x - m / 2.0,
+            y - m / 2.0,
+            m = m
+        )
+        .unwrap();
+    }
+
+    writeln!(
+        out,
+        r#"<text x="10" y="{}" font-family="monospace" font-size="14">{}: {:?}, {}minimum phase</text>"#,
+        SIZE + 20.0,
+        name,
+        analysis.stability,
+        if analysis.minimum_phase { "" } else { "not " }
+    )
+    .unwrap();
+    out.push_str("</svg>\n");
+    out
+}
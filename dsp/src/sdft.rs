@@ -0,0 +1,291 @@
+//! Sliding DFT, a few bins of an N point DFT updated on every sample.
+//!
+//! An STFT waits for a block, or at least a hop, and then does a whole FFT.
+//! When only a handful of bins matter and they're wanted after every sample,
+//! the sliding DFT is much cheaper. Moving the window on by one sample adds
+//! x[n] at the end and drops x[n - N] off the front, and everything else just
+//! turns by one bin's worth of phase:
+//!
+//! X_k[n] = e^(j 2 pi k/N) (X_k[n - 1] + x[n] - x[n - N])
+//!
+//! That's a couple of complex multiply-adds per bin per sample whatever N is.
+//! The catch is the resonator sits right on the unit circle, so rounding in
+//! the twiddle and in each update never dies away. The error walks off
+//! slowly and the bin drifts. [`Sdft::recursive`] is this version, with a
+//! damping factor r just under 1 to pull the poles inside the circle, which
+//! keeps it stable but weights the older samples by up to r^N.
+//!
+//! The modulated SDFT, [`Sdft::new`], keeps the input turning instead of the
+//! bin. Each sample goes in multiplied by e^(-j 2 pi k n/N), looked up in a table
+//! by kn mod N, and is taken back out N samples later with the same twiddle
+//! so it cancels exactly. Nothing is multiplied over and over, so there's
+//! nothing to drift. The phase is put back when a bin is read.
+//!
+//! Windows are applied in the frequency domain. A cosine sum window is a
+//! handful of bins either side of DC in the frequency domain, so Hann is
+//! -X[k - 1]/4 + X[k]/2 - X[k + 1]/4 and the neighbours of every bin asked for
+//! are tracked too. Only the cosine sums with up to three terms can be done
+//! that way, so the sliding [`Window`] is its own enum of rectangular, Hann,
+//! Hamming and Blackman.
+//!
+//! Everything is in the struct and [`Sdft::process`] never allocates, so it
+//! can run from the sampling interrupt.
+//!
+//! ```
+//! use dsp::sdft::{Sdft, Window};
+//! use heapless::consts::{U2, U64};
+//!
+//! // bins 4 and 10 of a 64 point DFT, 4 cycles of cos lands on bin 4
+//! let mut sdft = Sdft::<U64, U2>::new(&[4, 10], Window::Rectangular);
+//! for n in 0..200 {
+//!     sdft.process((core::f32::consts::PI * n as f32 / 8.0).cos());
+//! }
+//! assert!((sdft.bin(0).norm_sqr().sqrt() - 32.0).abs() < 1e-3);
+//! assert!(sdft.bin(1).norm_sqr().sqrt() < 1e-3);
+//! ```
+
+use crate::Complex32;
+use core::f64::consts::PI;
+use heapless::{ArrayLength, Vec};
+
+/// Most bins either side a window reaches, Blackman's two
+const REACH: usize = 2;
+const STATES: usize = 2 * REACH + 1;
+
+/// The windows that can slide, the cosine sums of up to three terms that
+/// reach at most two bins either side
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Window {
+    Rectangular,
+    Hann,
+    Hamming,
+    Blackman,
+}
+
+impl Window {
+    /// a0, a1 and a2 of a0 - a1 cos(2 pi m/N) + a2 cos(4 pi m/N), and how
+    /// many neighbours either side that needs
+    fn terms(self) -> ([f32; 3], usize) {
+        match self {
+            Window::Rectangular => ([1.0, 0.0, 0.0], 0),
+            Window::Hann => ([0.5, 0.5, 0.0], 1),
+            Window::Hamming => ([0.54, 0.46, 0.0], 1),
+            Window::Blackman => ([0.42, 0.5, 0.08], 2),
+        }
+    }
+}
+
+/// The same window in the time domain
+impl From<Window> for crate::window::Window {
+    fn from(window: Window) -> Self {
+        match window {
+            Window::Rectangular => crate::window::Window::Rectangular,
+            Window::Hann => crate::window::Window::Hann,
+            Window::Hamming => crate::window::Window::Hamming,
+            Window::Blackman => crate::window::Window::Blackman,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Method {
+    /// r^N, what's left of the oldest sample when it drops off
+    Recursive {
+        fade: f32,
+    },
+    Modulated,
+}
+
+/// One of the bins asked for and its neighbours, k - 2 to k + 2
+#[derive(Clone, Debug)]
+pub struct Resonator {
+    k: usize,
+    /// (k + j - 2) mod N for the neighbour in state j
+    bins: [usize; STATES],
+    /// where each neighbour's twiddle has got to for the modulated update,
+    /// (k + j - 2) n mod N
+    turns: [usize; STATES],
+    /// r e^(j 2 pi (k + j - 2)/N) for the recursive update
+    steps: [Complex32; STATES],
+    states: [Complex32; STATES],
+}
+
+/// Sliding DFT of the bins in M over the last N samples
+#[derive(Clone, Debug)]
+pub struct Sdft<N, M>
+where
+    N: ArrayLength<f32> + ArrayLength<Complex32>,
+    M: ArrayLength<Resonator> + ArrayLength<Complex32>,
+{
+    method: Method,
+    /// a0, a1 and a2 of the window, see [`Window::terms`]
+    window: [f32; 3],
+    /// how many neighbours either side the window needs
+    reach: usize,
+    /// e^(-j 2 pi m/N)
+    twiddles: Vec<Complex32, N>,
+    /// the last N samples, the oldest at `oldest`
+    history: Vec<f32, N>,
+    oldest: usize,
+    /// samples since the reset, up to N
+    count: usize,
+    resonators: Vec<Resonator, M>,
+}
+
+impl<N, M> Sdft<N, M>
+where
+    N: ArrayLength<f32> + ArrayLength<Complex32>,
+    M: ArrayLength<Resonator> + ArrayLength<Complex32>,
+{
+    /// Modulated SDFT of bins `k` with the periodic `window`, stable for as
+    /// long as it runs
+    pub fn new(k: &[usize], window: Window) -> Self {
+        Self::build(k.iter().cloned(), window, Method::Modulated, 1.0)
+    }
+
+    /// Plain recursive SDFT of bins `k` with the periodic `window`, damped by
+    /// r. At 1 it's exact to start with but drifts, under 1 it's stable but
+    /// isn't quite the DFT any more.
+    pub fn recursive(k: &[usize], window: Window, damping: f32) -> Self {
+        let fade = libm::pow(damping as f64, N::to_usize() as f64) as f32;
+        Self::build(
+            k.iter().cloned(),
+            window,
+            Method::Recursive { fade },
+            damping,
+        )
+    }
+
+    /// Modulated SDFT of the bins nearest each of `f0` in Hz at sample rate fs
+    pub fn frequencies(f0: &[f32], fs: f32, window: Window) -> Self {
+        let n = N::to_usize();
+        let k = f0
+            .iter()
+            .map(|f0| (libm::roundf(f0 * n as f32 / fs) as usize) % n);
+        Self::build(k, window, Method::Modulated, 1.0)
+    }
+
+    fn build(k: impl Iterator<Item = usize>, window: Window, method: Method, damping: f32) -> Self {
+        let n = N::to_usize();
+        let (window, reach) = window.terms();
+        let twiddles = (0..n)
+            .map(|m| {
+                let (sin, cos) = libm::sincos(2.0 * PI * m as f64 / n as f64);
+                Complex32::new(cos as f32, -sin as f32)
+            })
+            .collect::<Vec<Complex32, N>>();
+        let resonators = k
+            .map(|k| {
+                assert!(k < n, "bins go up to N - 1");
+                let mut bins = [0; STATES];
+                let mut steps = [Complex32::default(); STATES];
+                for (j, (bin, step)) in bins.iter_mut().zip(steps.iter_mut()).enumerate() {
+                    *bin = (k + n + j - REACH) % n;
+                    *step = twiddles[*bin].conj() * damping;
+                }
+                Resonator {
+                    k,
+                    bins,
+                    turns: [0; STATES],
+                    steps,
+                    states: [Complex32::default(); STATES],
+                }
+            })
+            .collect();
+
+        let mut history = Vec::new();
+        history.resize(n, 0.0).unwrap();
+        Self {
+            method,
+            window,
+            reach,
+            twiddles,
+            history,
+            oldest: 0,
+            count: 0,
+            resonators,
+        }
+    }
+
+    /// The bins being tracked
+    pub fn k(&self) -> impl Iterator<Item = usize> + '_ {
+        self.resonators.iter().map(|r| r.k)
+    }
+
+    /// Whether N samples have gone in since the reset. Before that the
+    /// missing ones count as zeros.
+    pub fn is_full(&self) -> bool {
+        self.count == N::to_usize()
+    }
+
+    /// Start again from silence
+    pub fn reset(&mut self) {
+        self.history.iter_mut().for_each(|x| *x = 0.0);
+        self.oldest = 0;
+        self.count = 0;
+        for r in self.resonators.iter_mut() {
+            r.turns = [0; STATES];
+            r.states = [Complex32::default(); STATES];
+        }
+    }
+
+    /// Feed one sample, sliding the window on by one
+    pub fn process(&mut self, x: f32) {
+        let n = N::to_usize();
+        let old = core::mem::replace(&mut self.history[self.oldest], x);
+        self.oldest += 1;
+        if self.oldest == n {
+            self.oldest = 0;
+        }
+        self.count = (self.count + 1).min(n);
+
+        let used = REACH - self.reach..=REACH + self.reach;
+        match self.method {
+            Method::Recursive { fade } => {
+                let delta = x - fade * old;
+                for r in self.resonators.iter_mut() {
+                    for j in used.clone() {
+                        r.states[j] = (r.states[j] + delta) * r.steps[j];
+                    }
+                }
+            }
+            Method::Modulated => {
+                let delta = x - old;
+                for r in self.resonators.iter_mut() {
+                    for j in used.clone() {
+                        r.states[j] += self.twiddles[r.turns[j]] * delta;
+                        r.turns[j] += r.bins[j];
+                        if r.turns[j] >= n {
+                            r.turns[j] -= n;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Bin i of the ones asked for, windowed, over the last N samples with
+    /// the oldest first, the same as an FFT of them would give
+    pub fn bin(&self, i: usize) -> Complex32 {
+        let r = &self.resonators[i];
+        let unwindowed = |j: usize| match self.method {
+            Method::Recursive { .. } => r.states[j],
+            // the phase it's short of, e^(j 2 pi k (n + 1)/N)
+            Method::Modulated => r.states[j] * self.twiddles[r.turns[j]].conj(),
+        };
+        let [a0, a1, a2] = self.window;
+        let mut z = unwindowed(REACH) * a0;
+        if self.reach > 0 {
+            z -= (unwindowed(REACH - 1) + unwindowed(REACH + 1)) * (a1 / 2.0);
+        }
+        if self.reach > 1 {
+            z += (unwindowed(REACH - 2) + unwindowed(REACH + 2)) * (a2 / 2.0);
+        }
+        z
+    }
+
+    /// Every bin asked for, in order
+    pub fn bins(&self) -> Vec<Complex32, M> {
+        (0..self.resonators.len()).map(|i| self.bin(i)).collect()
+    }
+}
@@ -0,0 +1,357 @@
+//! Second order sections, the form IIR filters should actually be run in.
+//!
+//! A high order filter expanded into a single pair of b and a polynomials needs
+//! more precision than f32 has, so designs are split into biquads and run as a
+//! cascade instead.
+
+use crate::complex;
+use crate::freqz;
+use crate::poly;
+use crate::zpk::{Coefficients, Roots, Zpk};
+use crate::Complex64;
+use heapless::consts::U16;
+
+/// Half of [`MaxRoots`](crate::zpk::MaxRoots)
+pub type MaxSections = U16;
+
+/// One biquad, b0 + b1z^-1 + b2z^-2 over a0 + a1z^-1 + a2z^-2 with a0 == 1
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Section {
+    pub b: [f64; 3],
+    pub a: [f64; 3],
+}
+
+impl Default for Section {
+    fn default() -> Self {
+        Self {
+            b: [1.0, 0.0, 0.0],
+            a: [1.0, 0.0, 0.0],
+        }
+    }
+}
+
+pub type Sections = heapless::Vec<Section, MaxSections>;
+
+impl Section {
+    /// Build a section from up to two zeros and two poles
+    fn from_roots(z: &[Complex64], p: &[Complex64]) -> Self {
+        let zpk = Zpk::new(z, p, 1.0);
+        let (b, a) = zpk.to_tf();
+
+        // right align into the 3 coefficient slots
+        let mut section = Section {
+            b: [0.0; 3],
+            a: [0.0; 3],
+        };
+        section.b[3 - b.len()..].copy_from_slice(&b);
+        section.a[3 - a.len()..].copy_from_slice(&a);
+        section
+    }
+}
+
+/// Split roots into the complex ones with positive imaginary part (the
+/// conjugate is implied) followed by the real ones, each sorted by real part
+/// like scipy's _cplxreal so ties pair up the same way.
+fn cplxreal(roots: &[Complex64]) -> Roots {
+    let mut complex_roots = Roots::new();
+    let mut real_roots = Roots::new();
+    for r in roots {
+        if complex::is_real(*r) {
+            real_roots.push(Complex64::new(r.re, 0.0)).unwrap();
+        } else if r.im > 0.0 {
+            complex_roots.push(*r).unwrap();
+        }
+    }
+    let by_re_im = |a: &Complex64, b: &Complex64| {
+        a.re.partial_cmp(&b.re)
+            .unwrap()
+            .then(a.im.partial_cmp(&b.im).unwrap())
+    };
+    complex_roots.sort_unstable_by(by_re_im);
+    real_roots.sort_unstable_by(by_re_im);
+    complex_roots.extend_from_slice(&real_roots).unwrap();
+    complex_roots
+}
+
+fn remove(roots: &mut Roots, idx: usize) -> Complex64 {
+    let r = roots[idx];
+    roots[idx..].rotate_left(1);
+    roots.pop();
+    r
+}
+
+enum Which {
+    Any,
+    Real,
+    Complex,
+}
+
+/// Remove and return the root in `from` closest to `to`, restricted to real
+/// or complex roots if asked
+fn take_nearest(from: &mut Roots, to: Complex64, which: Which) -> Complex64 {
+    let idx = from
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| match which {
+            Which::Any => true,
+            Which::Real => r.im == 0.0,
+            Which::Complex => r.im != 0.0,
+        })
+        .fold(None, |best: Option<(usize, f64)>, (idx, r)| {
+            let dist = complex::abs(*r - to);
+            match best {
+                Some((_, best_dist)) if best_dist <= dist => best,
+                _ => Some((idx, dist)),
+            }
+        })
+        .unwrap()
+        .0;
+    remove(from, idx)
+}
+
+/// Pair poles and zeros into second order sections the same way scipy's
+/// zpk2sos does with `pairing='nearest'`: the pole closest to the unit circle
+/// is paired with its nearest zero, and those sections go last in the cascade
+/// so they see the least amplified signal. All gain goes in the first section.
+pub fn from_zpk(zpk: &Zpk) -> Sections {
+    let mut sections = Sections::new();
+
+    if zpk.z.is_empty() && zpk.p.is_empty() {
+        let mut section = Section::default();
+        section.b[0] = zpk.k;
+        sections.push(section).unwrap();
+        return sections;
+    }
+
+    let mut z = zpk.z.clone();
+    let mut p = zpk.p.clone();
+    while p.len() < z.len() {
+        p.push(Complex64::new(0.0, 0.0)).unwrap();
+    }
+    while z.len() < p.len() {
+        z.push(Complex64::new(0.0, 0.0)).unwrap();
+    }
+    let n_sections = p.len().div_ceil(2);
+    if p.len() % 2 == 1 {
+        p.push(Complex64::new(0.0, 0.0)).unwrap();
+        z.push(Complex64::new(0.0, 0.0)).unwrap();
+    }
+
+    let mut z = cplxreal(&z);
+    let mut p = cplxreal(&p);
+
+    for _ in 0..n_sections {
+        // worst remaining pole, closest to the unit circle
+        let p1_idx = p
+            .iter()
+            .enumerate()
+            .fold((0, f64::INFINITY), |best, (idx, r)| {
+                let dist = (1.0 - complex::abs(*r)).abs();
+                if dist < best.1 {
+                    (idx, dist)
+                } else {
+                    best
+                }
+            })
+            .0;
+        let p1 = remove(&mut p, p1_idx);
+        let reals_left = p.iter().filter(|r| r.im == 0.0).count();
+
+        let section = if p1.im == 0.0 && reals_left == 0 {
+            // last remaining real pole gets a real zero
+            let z1 = take_nearest(&mut z, p1, Which::Real);
+            Section::from_roots(
+                &[z1, Complex64::new(0.0, 0.0)],
+                &[p1, Complex64::new(0.0, 0.0)],
+            )
+        } else if p.len() + 1 == z.len()
+            && p1.im != 0.0
+            && reals_left == 1
+            && z.iter().filter(|r| r.im == 0.0).count() == 1
+        {
+            // one real pole and one real zero left, this complex pole has to
+            // take a complex zero so they end up together
+            let z1 = take_nearest(&mut z, p1, Which::Complex);
+            Section::from_roots(&[z1, z1.conj()], &[p1, p1.conj()])
+        } else {
+            let p2 = if p1.im == 0.0 {
+                let p2_idx = p
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, r)| r.im == 0.0)
+                    .fold((0, f64::INFINITY), |best, (idx, r)| {
+                        let dist = (complex::abs(*r) - 1.0).abs();
+                        if dist < best.1 {
+                            (idx, dist)
+                        } else {
+                            best
+                        }
+                    })
+                    .0;
+                remove(&mut p, p2_idx)
+            } else {
+                p1.conj()
+            };
+
+            if z.is_empty() {
+                Section::from_roots(&[], &[p1, p2])
+            } else {
+                let z1 = take_nearest(&mut z, p1, Which::Any);
+                if z1.im != 0.0 {
+                    Section::from_roots(&[z1, z1.conj()], &[p1, p2])
+                } else if !z.is_empty() {
+                    let z2 = take_nearest(&mut z, p1, Which::Real);
+                    Section::from_roots(&[z1, z2], &[p1, p2])
+                } else {
+                    Section::from_roots(&[z1], &[p1, p2])
+                }
+            }
+        };
+        sections.push(section).unwrap();
+    }
+
+    sections.reverse();
+    sections[0].b.iter_mut().for_each(|b| *b *= zpk.k);
+    sections
+}
+
+/// Factor a transfer function and pair it up into sections, see [`from_zpk`].
+/// None if it couldn't be factored.
+pub fn from_tf(b: &[f64], a: &[f64]) -> Option<Sections> {
+    Zpk::from_tf(b, a).map(|zpk| from_zpk(&zpk))
+}
+
+/// Zeros, poles and overall gain of a cascade. A zero and a pole both at the
+/// origin cancel, so the padding [`from_zpk`] adds to odd orders goes away
+/// again. None if a section couldn't be factored.
+pub fn to_zpk(sections: &[Section]) -> Option<Zpk> {
+    let mut zpk = Zpk::new(&[], &[], 1.0);
+    for s in sections {
+        let section = Zpk::from_tf(&s.b, &s.a)?;
+        zpk.z.extend_from_slice(&section.z).unwrap();
+        zpk.p.extend_from_slice(&section.p).unwrap();
+        zpk.k *= section.k;
+    }
+
+    let origin = Complex64::new(0.0, 0.0);
+    while let (Some(z), Some(p)) = (
+        zpk.z.iter().position(|r| *r == origin),
+        zpk.p.iter().position(|r| *r == origin),
+    ) {
+        remove(&mut zpk.z, z);
+        remove(&mut zpk.p, p);
+    }
+    Some(zpk)
+}
+
+/// Multiply the sections back out into a single b and a, normalized so
+/// a[0] == 1. Only safe to run as is in double precision and low orders.
+pub fn to_tf(sections: &[Section]) -> (Coefficients, Coefficients) {
+    let mut b = Coefficients::from_slice(&[1.0]).unwrap();
+    let mut a = Coefficients::from_slice(&[1.0]).unwrap();
+    for s in sections {
+        b = poly::multiply(&b, trimmed(&s.b));
+        a = poly::multiply(&a, trimmed(&s.a));
+    }
+    let a0 = a[0];
+    b.iter_mut().for_each(|b| *b /= a0);
+    a.iter_mut().for_each(|a| *a /= a0);
+
+    // pad to the same length, trailing zeros are just extra delay
+    let len = b.len().max(a.len());
+    b.resize(len, 0.0).unwrap();
+    a.resize(len, 0.0).unwrap();
+    (b, a)
+}
+
+/// Drop trailing zero coefficients, in z^-1 they're only padding
+fn trimmed(c: &[f64; 3]) -> &[f64] {
+    let len = c.iter().rposition(|c| *c != 0.0).map_or(1, |i| i + 1);
+    &c[..len]
+}
+
+/// Norm used to scale sections for fixed point
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Norm {
+    /// Energy gain. Keeps white noise or typical signals from overflowing,
+    /// but a sine right at a resonance still can.
+    L2,
+    /// Peak gain over frequency. No sine can overflow, at the cost of more
+    /// headroom and so more quantization noise.
+    LInf,
+}
+
+/// Points on the unit circle the norms are estimated from
+const NORM_POINTS: usize = 1024;
+
+/// Gain of the first `sections` at the given norm
+fn norm(sections: &[Section], norm: Norm) -> f64 {
+    let gains = (0..NORM_POINTS).map(|i| {
+        // midpoints between DC and Nyquist, the other half mirrors it
+        let w = core::f64::consts::PI * (i as f64 + 0.5) / NORM_POINTS as f64;
+        sections
+            .iter()
+            .map(|s| complex::abs(freqz::freqz(&s.b, &s.a, w)))
+            .product::<f64>()
+    });
+    match norm {
+        Norm::L2 => libm::sqrt(gains.map(|g| g * g).sum::<f64>() / NORM_POINTS as f64),
+        Norm::LInf => gains.fold(0.0, f64::max),
+    }
+}
+
+/// Scale the numerators so the response from the input to the output of every
+/// section has unit gain in the given norm, as needed before quantizing to
+/// Q15 or Q31. Returns the gain to apply after the cascade to get the original
+/// response back.
+pub fn scale(sections: &mut [Section], by: Norm) -> f64 {
+    let mut gain = 1.0;
+    for i in 0..sections.len() {
+        let s = norm(&sections[..=i], by);
+        if s > 0.0 {
+            sections[i].b.iter_mut().for_each(|b| *b /= s);
+            gain *= s;
+        }
+    }
+    gain
+}
+
+/// Runtime cascade of biquads in transposed direct form II, one f32 state pair
+/// per section.
+#[derive(Clone, Debug)]
+pub struct Cascade {
+    sections: heapless::Vec<([f32; 3], [f32; 2]), MaxSections>,
+    state: heapless::Vec<[f32; 2], MaxSections>,
+}
+
+impl Cascade {
+    pub fn new(sections: &[Section]) -> Self {
+        let mut cascade = Self {
+            sections: heapless::Vec::new(),
+            state: heapless::Vec::new(),
+        };
+        for s in sections {
+            let b = [s.b[0] as f32, s.b[1] as f32, s.b[2] as f32];
+            let a = [s.a[1] as f32, s.a[2] as f32];
+            cascade.sections.push((b, a)).unwrap();
+            cascade.state.push([0.0; 2]).unwrap();
+        }
+        cascade
+    }
+
+    pub fn reset(&mut self) {
+        self.state.iter_mut().for_each(|s| *s = [0.0; 2]);
+    }
+
+    pub fn process(&mut self, x: f32) -> f32 {
+        self.sections
+            .iter()
+            .zip(self.state.iter_mut())
+            .fold(x, |x, ((b, a), s)| {
+                let y = b[0] * x + s[0];
+                s[0] = b[1] * x - a[0] * y + s[1];
+                s[1] = b[2] * x - a[1] * y;
+                y
+            })
+    }
+}
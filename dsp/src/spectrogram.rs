@@ -0,0 +1,198 @@
+//! Spectrograms and the colours to draw them in.
+//!
+//! A spectrogram is the magnitude of an STFT drawn with time across and
+//! frequency up, each frame a column and the loudness a colour. [`Spectrogram`]
+//! holds the magnitudes frame by bin along with the time in seconds of each
+//! frame and the frequency in Hz of each bin, one-sided from DC to fs/2 or
+//! two-sided from -fs/2 with the negative frequencies moved down below DC, so
+//! bin k is always below bin k + 1 and nothing has to be flipped to draw it.
+//!
+//! [`Colormap`] turns a level from 0 to 1 into a colour and works anywhere,
+//! for drawing the spectrogram as an image. [`Spectrogram::heatmap`] lays the
+//! same data out the way plotly's HeatMap::new(x, y, z) takes it.
+//!
+//! ```
+//! use dsp::fft::Microfft;
+//! use dsp::spectrogram::Spectrogram;
+//! use dsp::spectrum::Sided;
+//! use dsp::stft::Stft;
+//! use dsp::window::Window;
+//! use heapless::consts::{U16, U8};
+//!
+//! // a tone at 1kHz, sampled at 8kHz
+//! let x = (0..64)
+//!     .map(|n| (core::f32::consts::PI * n as f32 / 4.0).cos())
+//!     .collect::<std::vec::Vec<f32>>();
+//! let mut stft = Stft::<Microfft, U16, U8>::new(Window::Hann, 8);
+//! stft.forward(&x).unwrap();
+//!
+//! let spectrogram = Spectrogram::<U16, U8>::new(&stft, 8000.0, Sided::One);
+//! assert_eq!(spectrogram.frequencies()[2], 1000.0);
+//! assert_eq!(spectrogram.times()[0], 0.001);
+//! assert!(spectrogram.frames().iter().all(|frame| frame[2] > frame[4]));
+//! ```
+
+use crate::fft::Cfft;
+use crate::spectrum::{fftshift, frequencies, Sided, Spectrum};
+use crate::stft::Stft;
+use crate::Complex32;
+use heapless::{ArrayLength, Vec};
+
+/// Colour scales from dark for quiet to bright for loud. Viridis and magma
+/// are matplotlib's perceptually uniform maps, interpolated from ten samples.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Colormap {
+    Viridis,
+    Magma,
+    Gray,
+}
+
+const VIRIDIS: [[u8; 3]; 10] = [
+    [0x44, 0x01, 0x54],
+    [0x48, 0x28, 0x78],
+    [0x3e, 0x4a, 0x89],
+    [0x31, 0x68, 0x8e],
+    [0x26, 0x82, 0x8e],
+    [0x1f, 0x9e, 0x89],
+    [0x35, 0xb7, 0x79],
+    [0x6d, 0xcd, 0x59],
+    [0xb4, 0xde, 0x2c],
+    [0xfd, 0xe7, 0x25],
+];
+
+const MAGMA: [[u8; 3]; 10] = [
+    [0x00, 0x00, 0x04],
+    [0x18, 0x0f, 0x3e],
+    [0x45, 0x10, 0x77],
+    [0x72, 0x1f, 0x81],
+    [0x9f, 0x2f, 0x7f],
+    [0xcd, 0x40, 0x71],
+    [0xf1, 0x60, 0x5d],
+    [0xfd, 0x95, 0x67],
+    [0xfe, 0xc9, 0x8d],
+    [0xfc, 0xfd, 0xbf],
+];
+
+impl Colormap {
+    /// Colour of x from 0 for the quietest to 1 for the loudest, anything
+    /// outside clamped
+    pub fn rgb(self, x: f32) -> [u8; 3] {
+        let x = if x.is_nan() { 0.0 } else { x.clamp(0.0, 1.0) };
+        let stops = match self {
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Magma => &MAGMA,
+            Colormap::Gray => return [(x * 255.0 + 0.5) as u8; 3],
+        };
+
+        let position = x * (stops.len() - 1) as f32;
+        let i = (position as usize).min(stops.len() - 2);
+        let t = position - i as f32;
+        let mut rgb = [0; 3];
+        for (c, rgb) in rgb.iter_mut().enumerate() {
+            let (a, b) = (stops[i][c] as f32, stops[i + 1][c] as f32);
+            *rgb = (a + t * (b - a) + 0.5) as u8;
+        }
+        rgb
+    }
+}
+
+/// STFT magnitudes of up to F frames of N bins, with their times and
+/// frequencies
+pub struct Spectrogram<N, F>
+where
+    N: ArrayLength<f32>,
+    F: ArrayLength<Vec<f32, N>> + ArrayLength<f32>,
+{
+    frames: Vec<Vec<f32, N>, F>,
+    times: Vec<f32, F>,
+    frequencies: Vec<f32, N>,
+    sided: Sided,
+}
+
+impl<N, F> Spectrogram<N, F>
+where
+    N: ArrayLength<f32> + ArrayLength<Complex32>,
+    F: ArrayLength<Vec<f32, N>> + ArrayLength<f32> + ArrayLength<Vec<Complex32, N>>,
+{
+    /// |X| of the frames of an STFT of a signal sampled at fs. Each frame is
+    /// timed from its middle, N/2 samples after it starts.
+    pub fn new<B: Cfft<N> + Spectrum>(stft: &Stft<B, N, F>, fs: f32, sided: Sided) -> Self {
+        let n = N::to_usize();
+        let bins = sided.len(n);
+
+        let mut frames = Vec::new();
+        for frame in stft.frames() {
+            let mut magnitude = Vec::<f32, N>::new();
+            magnitude.resize(bins, 0.0).unwrap();
+            B::magnitude(&frame[..bins], &mut magnitude);
+            if sided == Sided::Two {
+                fftshift(&mut magnitude);
+            }
+            frames.push(magnitude).ok();
+        }
+
+        let mut frequencies = frequencies(n, fs, sided).collect::<Vec<f32, N>>();
+        if sided == Sided::Two {
+            fftshift(&mut frequencies);
+        }
+
+        Self {
+            frames,
+            times: (0..stft.len())
+                .map(|m| (m * stft.hop() + n / 2) as f32 / fs)
+                .collect(),
+            frequencies,
+            sided,
+        }
+    }
+}
+
+impl<N, F> Spectrogram<N, F>
+where
+    N: ArrayLength<f32>,
+    F: ArrayLength<Vec<f32, N>> + ArrayLength<f32>,
+{
+    /// The magnitudes, `frames()[m][k]` is frame m at `frequencies()[k]`
+    pub fn frames(&self) -> &[Vec<f32, N>] {
+        &self.frames
+    }
+
+    /// Middle of each frame in seconds
+    pub fn times(&self) -> &[f32] {
+        &self.times
+    }
+
+    /// Frequency of each bin in Hz, ascending
+    pub fn frequencies(&self) -> &[f32] {
+        &self.frequencies
+    }
+
+    pub fn sided(&self) -> Sided {
+        self.sided
+    }
+
+    /// Number of frames
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Times, frequencies and the magnitudes a row per frequency from the
+    /// lowest, the x, y and z of plotly's HeatMap::new(x, y, z)
+    #[cfg(feature = "std")]
+    pub fn heatmap(
+        &self,
+    ) -> (
+        std::vec::Vec<f32>,
+        std::vec::Vec<f32>,
+        std::vec::Vec<std::vec::Vec<f32>>,
+    ) {
+        let z = (0..self.frequencies.len())
+            .map(|k| self.frames.iter().map(|frame| frame[k]).collect())
+            .collect();
+        (self.times.to_vec(), self.frequencies.to_vec(), z)
+    }
+}
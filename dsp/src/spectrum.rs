@@ -0,0 +1,263 @@
+//! Turning FFT output into a spectrum to read or plot.
+//!
+//! An FFT of N samples gives N bins of unscaled complex values in FFT order,
+//! DC first, then the positive frequencies up to just below fs/2, then the
+//! negative ones. For a real signal the negative half mirrors the positive
+//! one, so the one-sided spectrum from DC to fs/2 has everything.
+//!
+//! [`Spectrum::amplitude`] scales the bins so a tone reads as its amplitude.
+//! A tone of amplitude A on a bin comes out of the FFT of N windowed samples
+//! as A N g/2 in each of its two bins, with g the window's coherent gain (see
+//! [`crate::window::coherent_gain`]). The two-sided spectrum divides by N g
+//! and reads A/2, the one-sided one adds the negative half back in and reads
+//! A. DC and Nyquist have no mirror image and aren't doubled.
+//!
+//! The magnitudes come from the same backend types as the FFTs, [`Spectrum`]
+//! for `Cmsis` uses arm_cmplx_mag_f32 and the one for [`Microfft`] is plain
+//! rust. Everything works on slices in place or into a
+//! caller's buffer, so there's nothing to allocate.
+//!
+//! ```
+//! use dsp::fft::{Cfft, Microfft};
+//! use dsp::spectrum::{frequencies, Sided, Spectrum};
+//! use dsp::Complex32;
+//!
+//! // a cosine of amplitude 3 on bin 4
+//! let mut x = (0..64)
+//!     .map(|n| Complex32::new(3.0 * (core::f32::consts::PI * n as f32 / 8.0).cos(), 0.0))
+//!     .collect::<heapless::Vec<Complex32, heapless::consts::U64>>();
+//! Microfft::cfft(&mut x);
+//!
+//! let mut a = [0.0; 33];
+//! Microfft::amplitude(&x, Sided::One, 1.0, &mut a);
+//! assert!((a[4] - 3.0).abs() < 1e-4);
+//! assert_eq!(frequencies(64, 8000.0, Sided::One).nth(4), Some(500.0));
+//! ```
+
+use crate::fft::Microfft;
+#[cfg(feature = "cmsis")]
+use crate::fft::{arm, Cmsis};
+use crate::{Complex, Complex32};
+use core::ops::{Add, Rem, Sub};
+
+/// Whether a spectrum keeps the negative frequencies
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sided {
+    /// DC to fs/2, the N/2 + 1 bins a real signal needs
+    One,
+    /// All N bins
+    Two,
+}
+
+impl Sided {
+    /// Number of bins of an N point FFT
+    pub fn len(self, n: usize) -> usize {
+        match self {
+            Sided::One => n / 2 + 1,
+            Sided::Two => n,
+        }
+    }
+}
+
+/// Magnitudes of complex bins, the way each FFT backend does them
+pub trait Spectrum {
+    /// |x| of each bin into out
+    fn magnitude(x: &[Complex32], out: &mut [f32]);
+
+    /// |x|^2 of each bin into out
+    fn power(x: &[Complex32], out: &mut [f32]);
+
+    /// Amplitude spectrum of an N point FFT, in the units of the signal.
+    /// `gain` is the coherent gain of the window the samples were multiplied
+    /// by, 1 for none. out needs `sided.len(x.len())` bins.
+    fn amplitude(x: &[Complex32], sided: Sided, gain: f32, out: &mut [f32]) {
+        let n = x.len();
+        let bins = sided.len(n);
+        Self::magnitude(&x[..bins], out);
+        correct(&mut out[..bins], n, sided, gain);
+    }
+
+    /// |X[k]| of the N/2 + 1 bins of a real FFT packed like
+    /// [`crate::fft::Rfft`] leaves it, DC to Nyquist into out
+    fn magnitude_packed(packed: &[Complex32], out: &mut [f32]) {
+        let half = packed.len();
+        Self::magnitude(packed, out);
+        out[0] = packed[0].re.abs();
+        out[half] = packed[0].im.abs();
+    }
+
+    /// |X[k]|^2 of the N/2 + 1 bins of a packed real FFT into out
+    fn power_packed(packed: &[Complex32], out: &mut [f32]) {
+        let half = packed.len();
+        Self::power(packed, out);
+        out[0] = packed[0].re * packed[0].re;
+        out[half] = packed[0].im * packed[0].im;
+    }
+
+    /// One-sided amplitude spectrum of a packed real FFT, N/2 + 1 bins into
+    /// out
+    fn amplitude_packed(packed: &[Complex32], gain: f32, out: &mut [f32]) {
+        let half = packed.len();
+        Self::magnitude_packed(packed, out);
+        correct(&mut out[..=half], 2 * half, Sided::One, gain);
+    }
+}
+
+/// The N/2 + 1 bins of a packed real spectrum, from DC to Nyquist
+pub fn bins(packed: &[Complex32]) -> impl Iterator<Item = Complex32> + Clone + '_ {
+    let (dc, nyquist) = (packed[0].re, packed[0].im);
+    core::iter::once(Complex32::new(dc, 0.0))
+        .chain(packed[1..].iter().cloned())
+        .chain(core::iter::once(Complex32::new(nyquist, 0.0)))
+}
+
+impl Spectrum for Microfft {
+    fn magnitude(x: &[Complex32], out: &mut [f32]) {
+        assert!(out.len() >= x.len(), "out is shorter than x");
+        for (out, x) in out.iter_mut().zip(x.iter()) {
+            *out = libm::sqrtf(x.norm_sqr());
+        }
+    }
+
+    fn power(x: &[Complex32], out: &mut [f32]) {
+        assert!(out.len() >= x.len(), "out is shorter than x");
+        for (out, x) in out.iter_mut().zip(x.iter()) {
+            *out = x.norm_sqr();
+        }
+    }
+}
+
+/// arm_cmplx_mag_f32 and arm_cmplx_mag_squared_f32
+#[cfg(feature = "cmsis")]
+impl Spectrum for Cmsis {
+    fn magnitude(x: &[Complex32], out: &mut [f32]) {
+        assert!(out.len() >= x.len(), "out is shorter than x");
+        unsafe {
+            arm::arm_cmplx_mag_f32(x.as_ptr() as *const f32, out.as_mut_ptr(), x.len() as u32);
+        }
+    }
+
+    fn power(x: &[Complex32], out: &mut [f32]) {
+        assert!(out.len() >= x.len(), "out is shorter than x");
+        unsafe {
+            arm::arm_cmplx_mag_squared_f32(
+                x.as_ptr() as *const f32,
+                out.as_mut_ptr(),
+                x.len() as u32,
+            );
+        }
+    }
+}
+
+/// f32 or f64, the bits of each that [`phase`] and [`unwrap`] need so the
+/// FFT bins and [`crate::freqz`]'s f64 responses share them
+pub trait Float:
+    Copy + Default + PartialOrd + Add<Output = Self> + Sub<Output = Self> + Rem<Output = Self>
+{
+    const PI: Self;
+    fn abs(self) -> Self;
+    /// atan2(self, x), the angle of x + j self
+    fn atan2(self, x: Self) -> Self;
+}
+
+impl Float for f32 {
+    const PI: f32 = core::f32::consts::PI;
+    fn abs(self) -> f32 {
+        libm::fabsf(self)
+    }
+    fn atan2(self, x: f32) -> f32 {
+        libm::atan2f(self, x)
+    }
+}
+
+impl Float for f64 {
+    const PI: f64 = core::f64::consts::PI;
+    fn abs(self) -> f64 {
+        libm::fabs(self)
+    }
+    fn atan2(self, x: f64) -> f64 {
+        libm::atan2(self, x)
+    }
+}
+
+/// Phase of each bin in radians, -pi to pi
+pub fn phase<T: Float>(x: &[Complex<T>], out: &mut [T]) {
+    for (out, x) in out.iter_mut().zip(x.iter()) {
+        *out = x.im.atan2(x.re);
+    }
+}
+
+/// Remove the 2 pi jumps from a phase in place, the same way numpy's unwrap
+/// does. Each step between neighbours of pi or more is taken to be the
+/// smaller step the other way round the circle, so the phase has to move
+/// less than pi per bin for this to work, zero pad the FFT to get there.
+pub fn unwrap<T: Float>(phase: &mut [T]) {
+    let (zero, pi) = (T::default(), T::PI);
+    let mut correction = zero;
+    for i in 1..phase.len() {
+        let step = phase[i] - (phase[i - 1] - correction);
+        if step.abs() >= pi {
+            let mut wrapped = (step + pi) % (pi + pi);
+            if wrapped < zero {
+                wrapped = wrapped + pi + pi;
+            }
+            wrapped = wrapped - pi;
+            if wrapped == zero - pi && step > zero {
+                wrapped = pi;
+            }
+            correction = correction + wrapped - step;
+        }
+        phase[i] = phase[i] + correction;
+    }
+}
+
+/// Scale magnitudes of an N point FFT so tones read as their amplitude
+fn correct(x: &mut [f32], n: usize, sided: Sided, gain: f32) {
+    let scale = 1.0 / (n as f32 * gain);
+    x.iter_mut().for_each(|x| *x *= scale);
+    if sided == Sided::One {
+        // everything but DC and Nyquist has a mirror image
+        let end = n.div_ceil(2);
+        x[1..end].iter_mut().for_each(|x| *x *= 2.0);
+    }
+}
+
+/// Amplitudes to dB, 20 log10(x / reference). With the full scale amplitude
+/// as the reference that's dBFS. Zero becomes -infinity.
+pub fn db(x: &mut [f32], reference: f32) {
+    x.iter_mut()
+        .for_each(|x| *x = 20.0 * libm::log10f(*x / reference));
+}
+
+/// Powers to dB, 10 log10(x / reference)
+pub fn power_db(x: &mut [f32], reference: f32) {
+    x.iter_mut()
+        .for_each(|x| *x = 10.0 * libm::log10f(*x / reference));
+}
+
+/// Move the zero frequency bin from the start to the middle, so a two-sided
+/// spectrum runs from -fs/2 up. Odd lengths work like numpy.
+pub fn fftshift<T>(x: &mut [T]) {
+    let n = x.len();
+    x.rotate_right(n / 2);
+}
+
+/// Undo [`fftshift`]
+pub fn ifftshift<T>(x: &mut [T]) {
+    let n = x.len();
+    x.rotate_left(n / 2);
+}
+
+/// Frequency in Hz of each bin of an N point FFT at sample rate fs. Two-sided
+/// it's in FFT order like numpy's fftfreq, the negative frequencies after the
+/// positive, [`fftshift`] it along with the spectrum.
+pub fn frequencies(n: usize, fs: f32, sided: Sided) -> impl Iterator<Item = f32> + Clone {
+    let step = fs / n as f32;
+    (0..sided.len(n)).map(move |k| {
+        if k < n.div_ceil(2) || sided == Sided::One {
+            k as f32 * step
+        } else {
+            (k as f32 - n as f32) * step
+        }
+    })
+}
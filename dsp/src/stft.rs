@@ -0,0 +1,212 @@
+//! Short-time Fourier transform and its inverse.
+//!
+//! The forward transform slides a window of N samples along the signal, hop
+//! samples at a time, and keeps the complex FFT of every windowed frame. The
+//! frames can be looked at, or edited, masking bins to filter or denoise, and
+//! turned back into samples by [`Stft::inverse`].
+//!
+//! The inverse is weighted overlap-add. Each frame is transformed back,
+//! multiplied by the window again and added in at its place, and every sample
+//! is divided by the sum of the squared windows that covered it. Left alone
+//! the frames come back as exactly the signal, whatever the window, as long
+//! as something nonzero covers each sample. With a window that starts at zero,
+//! like Hann, only the first frame covers the first sample and it's lost, pad
+//! the signal with N - hop zeros at both ends to get everything back.
+//!
+//! Edited frames aren't consistent with any signal any more, and the overlap
+//! smooths between them. That's best behaved when the squared window overlap
+//! adds to a constant, which [`cola`] checks, the square root of a periodic
+//! Hann at half overlap for example, and Hann itself at a quarter.
+//!
+//! Frames are kept in a heapless::Vec of at most F frames, each N bins long
+//! and transformed with the complex FFT of backend B.
+//!
+//! ```
+//! use dsp::fft::Microfft;
+//! use dsp::stft::Stft;
+//! use dsp::window::Window;
+//! use heapless::consts::{U16, U8};
+//!
+//! let x = (0..64).map(|n| (n as f32 * 0.3).sin()).collect::<std::vec::Vec<f32>>();
+//! let mut stft = Stft::<Microfft, U16, U8>::new(Window::Hann, 8);
+//! stft.forward(&x).unwrap();
+//! assert_eq!(stft.len(), 7);
+//!
+//! let mut y = [0.0; 64];
+//! stft.inverse(&mut y);
+//! assert!(x[1..].iter().zip(&y[1..]).all(|(x, y)| (x - y).abs() < 1e-5));
+//! ```
+
+use crate::fft::Cfft;
+use crate::window::Window;
+use crate::Complex32;
+use core::marker::PhantomData;
+use heapless::{ArrayLength, Vec};
+
+/// The signal needs more than F frames
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TooManyFrames {
+    pub needed: usize,
+}
+
+/// STFT with frames of N samples every `hop` samples, up to F of them, using
+/// the complex FFT of backend B
+pub struct Stft<B, N, F>
+where
+    N: ArrayLength<Complex32> + ArrayLength<f32>,
+    F: ArrayLength<Vec<Complex32, N>>,
+{
+    window: Vec<f32, N>,
+    hop: usize,
+    frames: Vec<Vec<Complex32, N>, F>,
+    backend: PhantomData<B>,
+}
+
+impl<B, N, F> Stft<B, N, F>
+where
+    B: Cfft<N>,
+    N: ArrayLength<Complex32> + ArrayLength<f32>,
+    F: ArrayLength<Vec<Complex32, N>>,
+{
+    /// The periodic `window`, moved along by `hop` samples each frame
+    pub fn new(window: Window, hop: usize) -> Self {
+        Self::with_window(window.periodic(N::to_usize()), hop)
+    }
+
+    /// Any other window of N samples, like the square root of one that
+    /// overlap adds to a constant
+    pub fn with_window(window: impl IntoIterator<Item = f32>, hop: usize) -> Self {
+        let window = window.into_iter().collect::<Vec<f32, N>>();
+        assert_eq!(window.len(), N::to_usize(), "window must be N long");
+        assert!(hop > 0 && hop <= N::to_usize(), "hop must be 1 to N");
+        Self {
+            window,
+            hop,
+            frames: Vec::new(),
+            backend: PhantomData,
+        }
+    }
+
+    pub fn window(&self) -> &[f32] {
+        &self.window
+    }
+
+    pub fn hop(&self) -> usize {
+        self.hop
+    }
+
+    /// How many whole frames fit in `len` samples
+    pub fn frames_in(&self, len: usize) -> usize {
+        if len < N::to_usize() {
+            0
+        } else {
+            (len - N::to_usize()) / self.hop + 1
+        }
+    }
+
+    /// Number of frames held
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The frames, each the N bins of its FFT in FFT order
+    pub fn frames(&self) -> &[Vec<Complex32, N>] {
+        &self.frames
+    }
+
+    /// The frames to edit in place before [`Stft::inverse`]. Keep bins k and
+    /// N - k conjugates of each other if the result should stay real.
+    pub fn frames_mut(&mut self) -> &mut [Vec<Complex32, N>] {
+        &mut self.frames
+    }
+
+    /// Samples the held frames cover, and [`Stft::inverse`] writes
+    pub fn output_len(&self) -> usize {
+        match self.frames.len() {
+            0 => 0,
+            frames => (frames - 1) * self.hop + N::to_usize(),
+        }
+    }
+
+    /// Replace the frames with the STFT of x. Anything after the last whole
+    /// frame isn't used.
+    pub fn forward(&mut self, x: &[f32]) -> Result<(), TooManyFrames> {
+        let needed = self.frames_in(x.len());
+        if needed > F::to_usize() {
+            return Err(TooManyFrames { needed });
+        }
+
+        while self.frames.pop().is_some() {}
+        for m in 0..needed {
+            let start = m * self.hop;
+            let mut frame = x[start..start + N::to_usize()]
+                .iter()
+                .zip(self.window.iter())
+                .map(|(x, w)| Complex32::new(x * w, 0.0))
+                .collect::<Vec<Complex32, N>>();
+            B::cfft(&mut frame);
+            self.frames.push(frame).ok();
+        }
+        Ok(())
+    }
+
+    /// Weighted overlap-add of the frames into the first
+    /// [`Stft::output_len`] samples of out. Samples no window covers come
+    /// out zero.
+    pub fn inverse(&self, out: &mut [f32]) {
+        let n = N::to_usize();
+        let len = self.output_len();
+        assert!(out.len() >= len, "out is shorter than output_len");
+
+        out[..len].iter_mut().for_each(|y| *y = 0.0);
+        for (m, frame) in self.frames.iter().enumerate() {
+            let mut frame = frame.clone();
+            B::icfft(&mut frame);
+            let start = m * self.hop;
+            for ((y, x), w) in out[start..start + n]
+                .iter_mut()
+                .zip(frame.iter())
+                .zip(self.window.iter())
+            {
+                *y += x.re * w;
+            }
+        }
+
+        for (i, y) in out[..len].iter_mut().enumerate() {
+            let weight = self.weight(i);
+            if weight > f32::EPSILON {
+                *y /= weight;
+            } else {
+                *y = 0.0;
+            }
+        }
+    }
+
+    /// Sum of the squared windows over sample i
+    fn weight(&self, i: usize) -> f32 {
+        let n = N::to_usize();
+        let last = self.frames.len() - 1;
+        let first = (i + 1).saturating_sub(n).div_ceil(self.hop);
+        (first..=(i / self.hop).min(last))
+            .map(|m| self.window[i - m * self.hop] * self.window[i - m * self.hop])
+            .sum()
+    }
+}
+
+/// The sum of copies of w every `hop` samples, if it's the same everywhere
+/// once they all overlap. Square the window first to check it for
+/// [`Stft::inverse`].
+pub fn cola(w: &[f32], hop: usize) -> Option<f32> {
+    let sums = (0..hop).map(|i| w.iter().skip(i).step_by(hop).sum::<f32>());
+    let first = sums.clone().next()?;
+    let tolerance = 1e-4 * first.abs().max(f32::EPSILON);
+    if sums.clone().all(|sum| (sum - first).abs() <= tolerance) {
+        Some(first)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,239 @@
+//! Window functions and the numbers needed to correct for them.
+//!
+//! Every [`Window`] comes in two variants. The symmetric one is what filter
+//! design wants, it starts and ends on the same value. The periodic one is the
+//! symmetric window one longer with the last sample dropped, so it repeats
+//! cleanly with period N, which is what spectral analysis with the DFT wants
+//! and what Harris' tables are for.
+//!
+//! A window changes the spectrum it's applied to, and the metric functions
+//! take the samples and say by how much:
+//! - [`coherent_gain`], the amplitude of a tone on a bin is scaled by this,
+//!   divide by it to read amplitudes off a windowed spectrum
+//! - [`enbw`], the equivalent noise bandwidth in bins, divide a windowed power
+//!   spectrum by it to read noise densities
+//! - [`scalloping_loss`], how much lower a tone half way between two bins
+//!   comes out than one on a bin
+//! - [`sidelobe_level`], the highest sidelobe relative to the main lobe
+//!
+//! The shapes are calculated in f64 and handed out as f32 iterators, collect
+//! them into a heapless::Vec to keep them around.
+//!
+//! ```
+//! use dsp::window::{coherent_gain, Window};
+//!
+//! let hann = Window::Hann.periodic(64).collect::<heapless::Vec<f32, heapless::consts::U64>>();
+//! assert!((coherent_gain(&hann) - 0.5).abs() < 1e-6);
+//! ```
+
+use core::f64::consts::PI;
+
+/// The window shapes, with their parameter where they have one
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Window {
+    Rectangular,
+    Hann,
+    Hamming,
+    /// 0.42, 0.5, 0.08
+    Blackman,
+    /// The 4 term -92 dB one
+    BlackmanHarris,
+    /// The 5 term one matlab and scipy use, for amplitudes between bins
+    FlatTop,
+    /// Shape parameter beta, Harris' alpha times pi
+    Kaiser(f32),
+    /// Fraction of the window taken up by the cosine tapers, 0 is rectangular
+    /// and 1 or more is Hann
+    Tukey(f32),
+    /// Harris' alpha, half the length over the standard deviation
+    Gaussian(f32),
+    /// Dolph-Chebyshev with every sidelobe this many dB down
+    Chebyshev(f32),
+}
+
+/// sum (-1)^k a[k] cos(2 pi k n / (m - 1))
+fn cosine_sum(a: &[f64], n: f64, m: f64) -> f64 {
+    a.iter().enumerate().fold(0.0, |sum, (k, a)| {
+        let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+        sum + sign * a * libm::cos(2.0 * PI * k as f64 * n / (m - 1.0))
+    })
+}
+
+/// Modified Bessel function of the first kind, order zero
+fn i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let mut k = 1.0;
+    while term > 1e-17 * sum {
+        term *= (x / (2.0 * k)) * (x / (2.0 * k));
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+/// Chebyshev polynomial of the given order, outside of -1 to 1 too
+fn chebyshev(order: usize, x: f64) -> f64 {
+    if x > 1.0 {
+        libm::cosh(order as f64 * libm::acosh(x))
+    } else if x < -1.0 {
+        let sign = if order & 1 == 0 { 1.0 } else { -1.0 };
+        sign * libm::cosh(order as f64 * libm::acosh(-x))
+    } else {
+        libm::cos(order as f64 * libm::acos(x))
+    }
+}
+
+/// Point i of the length m Dolph-Chebyshev window before normalizing. The
+/// spectrum is a Chebyshev polynomial sampled at m points, the window is the
+/// real part of its DFT, shuffled around the centre the same way as scipy.
+fn dolph(attenuation: f64, i: usize, m: usize) -> f64 {
+    let order = m - 1;
+    let x0 = libm::cosh(libm::acosh(libm::pow(10.0, attenuation / 20.0)) / order as f64);
+    let even = m & 1 == 0;
+    let j = if !even {
+        i.abs_diff((m - 1) / 2)
+    } else {
+        let h = m / 2;
+        if i < h {
+            h - i
+        } else {
+            i - h + 1
+        }
+    };
+
+    (0..m).fold(0.0, |sum, k| {
+        let p = chebyshev(order, x0 * libm::cos(PI * k as f64 / m as f64));
+        // even lengths are centred half a sample over
+        let shift = if even { k as f64 / m as f64 } else { 0.0 };
+        sum + p * libm::cos(PI * shift - 2.0 * PI * (j * k) as f64 / m as f64)
+    })
+}
+
+impl Window {
+    /// Point n of the symmetric window of length m
+    fn sample(self, n: usize, m: usize) -> f64 {
+        let (x, len) = (n as f64, m as f64);
+        match self {
+            Window::Rectangular => 1.0,
+            Window::Hann => cosine_sum(&[0.5, 0.5], x, len),
+            Window::Hamming => cosine_sum(&[0.54, 0.46], x, len),
+            Window::Blackman => cosine_sum(&[0.42, 0.5, 0.08], x, len),
+            Window::BlackmanHarris => cosine_sum(&[0.35875, 0.48829, 0.14128, 0.01168], x, len),
+            Window::FlatTop => cosine_sum(
+                &[
+                    0.21557895,
+                    0.41663158,
+                    0.277263158,
+                    0.083578947,
+                    0.006947368,
+                ],
+                x,
+                len,
+            ),
+            Window::Kaiser(beta) => {
+                let r = 2.0 * x / (len - 1.0) - 1.0;
+                i0(beta as f64 * libm::sqrt(1.0 - r * r)) / i0(beta as f64)
+            }
+            Window::Tukey(alpha) => {
+                // the tapers can't take up more than the whole window, scipy
+                // makes it Hann from 1 up too
+                let alpha = (alpha as f64).min(1.0);
+                let r = x / (len - 1.0);
+                if alpha <= 0.0 {
+                    1.0
+                } else if r < alpha / 2.0 {
+                    0.5 * (1.0 + libm::cos(PI * (2.0 * r / alpha - 1.0)))
+                } else if r > 1.0 - alpha / 2.0 {
+                    0.5 * (1.0 + libm::cos(PI * (2.0 * r / alpha - 2.0 / alpha + 1.0)))
+                } else {
+                    1.0
+                }
+            }
+            Window::Gaussian(alpha) => {
+                let half = (len - 1.0) / 2.0;
+                let r = alpha as f64 * (x - half) / half;
+                libm::exp(-0.5 * r * r)
+            }
+            Window::Chebyshev(attenuation) => dolph(attenuation as f64, n, m),
+        }
+    }
+
+    /// The symmetric window of `len` points, w[n] = w[len - 1 - n]
+    pub fn symmetric(self, len: usize) -> impl Iterator<Item = f32> + Clone {
+        // the Chebyshev one comes out unnormalized, its peak is in the middle
+        let peak = match self {
+            Window::Chebyshev(_) if len > 1 => self.sample(len / 2, len),
+            _ => 1.0,
+        };
+        (0..len).map(move |n| {
+            if len == 1 {
+                1.0
+            } else {
+                (self.sample(n, len) / peak) as f32
+            }
+        })
+    }
+
+    /// The periodic window of `len` points, the symmetric one of len + 1
+    /// without its last point
+    pub fn periodic(self, len: usize) -> impl Iterator<Item = f32> + Clone {
+        self.symmetric(len + 1).take(len)
+    }
+}
+
+/// Sum of the window over its length, the gain it applies to a tone on a bin
+pub fn coherent_gain(w: &[f32]) -> f32 {
+    (w.iter().map(|w| *w as f64).sum::<f64>() / w.len() as f64) as f32
+}
+
+/// Equivalent noise bandwidth in bins, N sum w^2 / (sum w)^2
+pub fn enbw(w: &[f32]) -> f32 {
+    let sum = w.iter().map(|w| *w as f64).sum::<f64>();
+    let squares = w.iter().map(|w| *w as f64 * *w as f64).sum::<f64>();
+    (w.len() as f64 * squares / (sum * sum)) as f32
+}
+
+/// |W(f)| at f cycles per sample
+fn response(w: &[f32], f: f64) -> f64 {
+    let (re, im) = w.iter().enumerate().fold((0.0, 0.0), |(re, im), (n, w)| {
+        let (sin, cos) = libm::sincos(2.0 * PI * f * n as f64);
+        (re + *w as f64 * cos, im - *w as f64 * sin)
+    });
+    libm::sqrt(re * re + im * im)
+}
+
+/// How much lower in dB a tone half a bin off comes out than one on a bin
+pub fn scalloping_loss(w: &[f32]) -> f32 {
+    let n = w.len() as f64;
+    (20.0 * libm::log10(response(w, 0.0) / response(w, 0.5 / n))) as f32
+}
+
+/// Highest sidelobe in dB relative to the peak of the main lobe, negative.
+///
+/// The response is evaluated every 1/16 of a bin up to half the sample rate,
+/// the main lobe ending at the first dip below half its peak. That's 8N
+/// evaluations of an N point sum, so this is for the host or start up rather
+/// than anything in a loop.
+pub fn sidelobe_level(w: &[f32]) -> f32 {
+    const OVERSAMPLE: usize = 16;
+    let n = w.len();
+    let peak = response(w, 0.0);
+
+    let mut previous = peak;
+    let mut in_main_lobe = true;
+    let mut highest = 0.0f64;
+    for i in 1..=n * OVERSAMPLE / 2 {
+        let magnitude = response(w, i as f64 / (n * OVERSAMPLE) as f64);
+        if in_main_lobe {
+            if magnitude > previous && previous < peak / 2.0 {
+                in_main_lobe = false;
+                highest = magnitude;
+            }
+        } else {
+            highest = highest.max(magnitude);
+        }
+        previous = magnitude;
+    }
+    (20.0 * libm::log10(highest / peak)) as f32
+}
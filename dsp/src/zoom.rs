@@ -0,0 +1,222 @@
+//! Zoom FFT, fine resolution over a narrow band.
+//!
+//! An N point FFT over the whole band from 0 to fs/2 has bins fs/N apart, and
+//! two tones closer than a couple of bins come out as one peak. More
+//! resolution needs more samples in the FFT, but only the band around them is
+//! interesting. The zoom FFT gets there with a short FFT:
+//! - mix the signal down by the center frequency, multiplying by
+//!   e^(-j 2 pi center n/fs), so the band of interest sits around DC
+//! - low-pass it to fs/2D either side and keep every Dth sample, which is all
+//!   that's needed to describe a band that narrow
+//! - FFT N of the decimated samples, which covers fs/D around the center with
+//!   bins fs/(D N) apart, D times finer than the plain FFT
+//!
+//! It takes D times as many samples as the plain N point FFT, (N - 1) D + T
+//! with the filter warming up, but never more than N of them in memory.
+//! Samples go in one at a time through [`Zoom::push`], so it can be fed from
+//! the sampling interrupt on target with everything in the struct, or a block
+//! at a time with [`Zoom::extend`] on the host.
+//!
+//! The low-pass is a T tap Blackman windowed sinc cutting off at the band
+//! edges, where it's 6 dB down. Whatever is just outside the band folds back in
+//! there, so trust the middle of the band more than the outer bins.
+//!
+//! ```
+//! use dsp::fft::Microfft;
+//! use dsp::window::Window;
+//! use dsp::zoom::Zoom;
+//! use heapless::consts::{U128, U65};
+//!
+//! // 50 Hz either side of 200 Hz in 0.39 Hz bins, from samples at 1 kHz
+//! let mut zoom = Zoom::<Microfft, U128, U65>::new(200.0, 1000.0, 10, Window::Hann);
+//! assert_eq!(zoom.resolution(), 1000.0 / 1280.0);
+//! assert_eq!(zoom.frequencies().next(), Some(150.0));
+//!
+//! let x = (0..zoom.samples_needed())
+//!     .map(|n| (2.0 * core::f32::consts::PI * 210.0 * n as f32 / 1000.0).cos());
+//! for x in x {
+//!     zoom.push(x);
+//! }
+//! assert!(zoom.is_full());
+//! ```
+
+use crate::fft::Cfft;
+use crate::spectrum::fftshift;
+use crate::window::Window;
+use crate::Complex32;
+use core::f64::consts::PI;
+use core::marker::PhantomData;
+use heapless::{ArrayLength, Vec};
+
+/// Zoom FFT of N points after decimating by D with a T tap low-pass, using
+/// the complex FFT of backend B
+pub struct Zoom<B, N, T>
+where
+    N: ArrayLength<Complex32> + ArrayLength<f32>,
+    T: ArrayLength<f32> + ArrayLength<Complex32>,
+{
+    center: f32,
+    fs: f32,
+    decimation: usize,
+    /// e^(-j 2 pi center/fs), and where the mixer has got to
+    rotation: Complex32,
+    phasor: Complex32,
+    taps: Vec<f32, T>,
+    /// the last T mixed samples, newest at `newest`
+    history: Vec<Complex32, T>,
+    newest: usize,
+    /// input samples to go before the next output is kept, T - 1 to fill
+    /// the filter at first and D - 1 after that
+    wait: usize,
+    window: Vec<f32, N>,
+    /// 2 / sum of the window, so a tone on a bin reads its amplitude
+    scale: f32,
+    samples: Vec<Complex32, N>,
+    backend: PhantomData<B>,
+}
+
+impl<B, N, T> Zoom<B, N, T>
+where
+    B: Cfft<N>,
+    N: ArrayLength<Complex32> + ArrayLength<f32>,
+    T: ArrayLength<f32> + ArrayLength<Complex32>,
+{
+    /// The band fs/`decimation` wide around `center` Hz, from samples at fs,
+    /// with the periodic `window` on the decimated samples
+    pub fn new(center: f32, fs: f32, decimation: usize, window: Window) -> Self {
+        assert!(decimation > 0, "decimation must be at least 1");
+        let theta = -2.0 * PI * center as f64 / fs as f64;
+
+        // windowed sinc cutting off at 1/2D cycles per sample, unity at DC
+        let len = T::to_usize();
+        let middle = (len - 1) as f64 / 2.0;
+        let cutoff = 0.5 / decimation as f64;
+        let sinc = |n: usize| {
+            let t = n as f64 - middle;
+            if t == 0.0 {
+                2.0 * cutoff
+            } else {
+                libm::sin(2.0 * PI * cutoff * t) / (PI * t)
+            }
+        };
+        let shape = Window::Blackman
+            .symmetric(len)
+            .enumerate()
+            .map(|(n, w)| sinc(n) * w as f64);
+        let sum = shape.clone().sum::<f64>();
+        let taps = shape.map(|h| (h / sum) as f32).collect();
+
+        let mut history = Vec::new();
+        history.resize(len, Complex32::default()).unwrap();
+        let window = window.periodic(N::to_usize()).collect::<Vec<f32, N>>();
+        let scale = 2.0 / window.iter().sum::<f32>();
+        Self {
+            center,
+            fs,
+            decimation,
+            rotation: Complex32::new(libm::cos(theta) as f32, libm::sin(theta) as f32),
+            phasor: Complex32::new(1.0, 0.0),
+            taps,
+            history,
+            newest: 0,
+            wait: len - 1,
+            window,
+            scale,
+            samples: Vec::new(),
+            backend: PhantomData,
+        }
+    }
+
+    pub fn center(&self) -> f32 {
+        self.center
+    }
+
+    pub fn decimation(&self) -> usize {
+        self.decimation
+    }
+
+    /// Width of a bin in Hz, fs/(D N)
+    pub fn resolution(&self) -> f32 {
+        self.fs / (self.decimation * N::to_usize()) as f32
+    }
+
+    /// Frequency in Hz of each of the N bins [`Zoom::spectrum`] gives, from
+    /// the bottom of the band up with the center at N/2
+    pub fn frequencies(&self) -> impl Iterator<Item = f32> + Clone {
+        let (center, step) = (self.center, self.resolution());
+        let half = (N::to_usize() / 2) as f32;
+        (0..N::to_usize()).map(move |k| center + (k as f32 - half) * step)
+    }
+
+    /// Input samples it takes to fill the FFT from a reset, (N - 1) D + T
+    pub fn samples_needed(&self) -> usize {
+        (N::to_usize() - 1) * self.decimation + T::to_usize()
+    }
+
+    /// Whether N decimated samples are in, ready for [`Zoom::spectrum`]
+    pub fn is_full(&self) -> bool {
+        self.samples.len() == N::to_usize()
+    }
+
+    /// Start again from nothing
+    pub fn reset(&mut self) {
+        self.phasor = Complex32::new(1.0, 0.0);
+        self.history
+            .iter_mut()
+            .for_each(|z| *z = Complex32::default());
+        self.newest = 0;
+        self.wait = T::to_usize() - 1;
+        while self.samples.pop().is_some() {}
+    }
+
+    /// Feed one sample. Returns true once the FFT is full, anything pushed
+    /// after that is ignored until [`Zoom::reset`].
+    pub fn push(&mut self, x: f32) -> bool {
+        if self.is_full() {
+            return true;
+        }
+
+        let len = T::to_usize();
+        self.newest = (self.newest + 1) % len;
+        self.history[self.newest] = self.phasor * x;
+        self.phasor *= self.rotation;
+
+        // only every Dth output is kept, and only once the filter is full
+        if self.wait > 0 {
+            self.wait -= 1;
+        } else {
+            self.wait = self.decimation - 1;
+            let newest = self.newest;
+            let y = self
+                .taps
+                .iter()
+                .enumerate()
+                .map(|(j, h)| self.history[(newest + len - j) % len] * *h)
+                .fold(Complex32::default(), |sum, z| sum + z);
+            self.samples.push(y).ok();
+            // keep the mixer on the unit circle
+            self.phasor /= libm::sqrtf(self.phasor.norm_sqr());
+        }
+        self.is_full()
+    }
+
+    /// Push samples from x until the FFT is full. Returns whether it is.
+    pub fn extend(&mut self, x: &[f32]) -> bool {
+        x.iter().any(|x| self.push(*x)) || self.is_full()
+    }
+
+    /// The N bins over the band, lowest frequency first. Scaled so a tone on
+    /// a bin reads its amplitude as the magnitude.
+    pub fn spectrum(&self) -> Vec<Complex32, N> {
+        assert!(self.is_full(), "not enough samples yet");
+        let mut bins = self
+            .samples
+            .iter()
+            .zip(self.window.iter())
+            .map(|(z, w)| z * (w * self.scale))
+            .collect::<Vec<Complex32, N>>();
+        B::cfft(&mut bins);
+        fftshift(&mut bins);
+        bins
+    }
+}
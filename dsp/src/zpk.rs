@@ -0,0 +1,83 @@
+//! Zero-pole-gain representation of a transfer function.
+//!
+//! H(s) or H(z) = k * prod(s - z_i) / prod(s - p_j)
+
+use crate::poly;
+use crate::Complex64;
+use heapless::consts::{U32, U33};
+
+/// Largest number of zeros or poles a filter can carry. Prototypes are limited
+/// to order 16, band pass and band stop transforms double that.
+pub type MaxRoots = U32;
+/// One more coefficient than roots
+pub type MaxCoefficients = U33;
+
+pub type Roots = heapless::Vec<Complex64, MaxRoots>;
+pub type Coefficients = heapless::Vec<f64, MaxCoefficients>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Zpk {
+    pub z: Roots,
+    pub p: Roots,
+    pub k: f64,
+}
+
+impl Zpk {
+    pub fn new(z: &[Complex64], p: &[Complex64], k: f64) -> Self {
+        Self {
+            z: heapless::Vec::from_slice(z).unwrap(),
+            p: heapless::Vec::from_slice(p).unwrap(),
+            k,
+        }
+    }
+
+    /// Factor numerator and denominator polynomials in z^-1, the B and A of a
+    /// difference equation. The shorter one is padded at the end so any extra
+    /// delay shows up as roots at the origin. None if either polynomial is
+    /// longer than a [`Coefficients`] holds or couldn't be factored.
+    pub fn from_tf(b: &[f64], a: &[f64]) -> Option<Self> {
+        let len = b.len().max(a.len());
+        if len > Coefficients::new().capacity() {
+            return None;
+        }
+        let padded = |c: &[f64]| {
+            let mut c = Coefficients::from_slice(c).unwrap();
+            c.resize(len, 0.0).unwrap();
+            c
+        };
+        let (b, a) = (padded(b), padded(a));
+
+        let lead = |c: &[f64]| c.iter().find(|c| **c != 0.0).cloned().unwrap_or(0.0);
+        Some(Self {
+            z: poly::roots(&b)?,
+            p: poly::roots(&a)?,
+            k: lead(&b) / lead(&a),
+        })
+    }
+
+    /// Number of poles, which is the filter order for everything we design
+    pub fn order(&self) -> usize {
+        self.p.len().max(self.z.len())
+    }
+
+    /// Expand into numerator and denominator polynomials, (b, a) in matlab
+    /// speak. Both are padded to the same length so they can be used directly
+    /// as difference equation coefficients in z^-1.
+    pub fn to_tf(&self) -> (Coefficients, Coefficients) {
+        let mut b = poly::from_roots(&self.z);
+        let mut a = poly::from_roots(&self.p);
+        b.iter_mut().for_each(|b| *b *= self.k);
+
+        // pad with leading zeros so both have the same degree
+        while b.len() < a.len() {
+            b.push(0.0).unwrap();
+            b.rotate_right(1);
+        }
+        while a.len() < b.len() {
+            a.push(0.0).unwrap();
+            a.rotate_right(1);
+        }
+
+        (b, a)
+    }
+}
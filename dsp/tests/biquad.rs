@@ -0,0 +1,115 @@
+use dsp::biquad::{Biquad, Coefficients, Kind, Params};
+use dsp::Complex64;
+
+const FS: f32 = 16000.0;
+
+fn params(kind: Kind, f0: f32, q: f32) -> Params {
+    Params {
+        kind,
+        f0,
+        q,
+        fs: FS,
+    }
+}
+
+fn gain_db(c: &Coefficients, f: f32) -> f64 {
+    let w = 2.0 * core::f64::consts::PI * f as f64 / FS as f64;
+    let z1 = Complex64::new(w.cos(), -w.sin());
+    let z2 = z1 * z1;
+    let num = z1 * c.b[1] as f64 + z2 * c.b[2] as f64 + c.b[0] as f64;
+    let den = z1 * c.a[0] as f64 + z2 * c.a[1] as f64 + 1.0;
+    10.0 * (num / den).norm_sqr().log10()
+}
+
+fn assert_db(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 0.01,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn magnitude_at_f0() {
+    let c = params(Kind::Peaking(6.0), 1000.0, 2.0).coefficients();
+    assert_db(gain_db(&c, 1000.0), 6.0);
+    assert_db(gain_db(&c, 10.0), 0.0);
+
+    let c = params(Kind::Peaking(-9.0), 3000.0, 0.7).coefficients();
+    assert_db(gain_db(&c, 3000.0), -9.0);
+
+    let c = params(Kind::BandPass, 500.0, 4.0).coefficients();
+    assert_db(gain_db(&c, 500.0), 0.0);
+
+    // lowpass and highpass peak at Q right at f0
+    let q = 2.0;
+    let c = params(Kind::LowPass, 2000.0, q).coefficients();
+    assert_db(gain_db(&c, 2000.0), 20.0 * (q as f64).log10());
+    let c = params(Kind::HighPass, 2000.0, q).coefficients();
+    assert_db(gain_db(&c, 2000.0), 20.0 * (q as f64).log10());
+
+    let c = params(Kind::Notch, 100.0, 10.0).coefficients();
+    assert!(gain_db(&c, 100.0) < -60.0);
+
+    let c = params(Kind::AllPass, 1234.0, 0.5).coefficients();
+    for f in [10.0, 1234.0, 7000.0].iter() {
+        assert_db(gain_db(&c, *f), 0.0);
+    }
+}
+
+#[test]
+fn shelf_gains() {
+    let s = core::f32::consts::FRAC_1_SQRT_2;
+
+    let c = params(Kind::LowShelf(12.0), 300.0, s).coefficients();
+    assert_db(gain_db(&c, 1.0), 12.0);
+    assert_db(gain_db(&c, 300.0), 6.0);
+    assert_db(gain_db(&c, FS / 2.0), 0.0);
+
+    let c = params(Kind::HighShelf(-6.0), 4000.0, s).coefficients();
+    assert_db(gain_db(&c, 1.0), 0.0);
+    assert_db(gain_db(&c, 4000.0), -3.0);
+    assert_db(gain_db(&c, FS / 2.0), -6.0);
+}
+
+#[test]
+fn peak_gain() {
+    assert_eq!(Coefficients::IDENTITY.peak_gain(256), 1.0);
+
+    // the grid lands on 500hz, the top of the peak
+    let c = params(Kind::Peaking(12.0), 500.0, 4.0).coefficients();
+    assert_db(20.0 * (c.peak_gain(257) as f64).log10(), 12.0);
+
+    // a cut never goes above 0dB
+    let c = params(Kind::LowShelf(-12.0), 300.0, 0.707).coefficients();
+    assert_db(20.0 * (c.peak_gain(256) as f64).log10(), 0.0);
+}
+
+#[test]
+fn retune_without_glitch() {
+    // 100hz sine through a peaking filter, swap to a very different setting
+    // halfway and make sure the output never jumps more than the sine could
+    let sine = |n: usize| (2.0 * core::f32::consts::PI * 100.0 * n as f32 / FS).sin();
+    let from = params(Kind::Peaking(12.0), 100.0, 1.0).coefficients();
+    let to = params(Kind::Peaking(-12.0), 100.0, 1.0).coefficients();
+
+    let mut biquad = Biquad::new(from);
+    let mut last = 0.0;
+    let mut max_step = 0.0f32;
+    for n in 0..4000 {
+        if n == 2000 {
+            biquad.retune(to, 256);
+        }
+        let y = biquad.process(sine(n));
+        if n > 100 {
+            max_step = max_step.max((y - last).abs());
+        }
+        last = y;
+    }
+
+    // steepest slope of a 4x sine, plus a little for the transition
+    let slope = 4.0 * 2.0 * core::f32::consts::PI * 100.0 / FS;
+    assert!(max_step < 1.1 * slope, "{} {}", max_step, slope);
+    assert_eq!(biquad.coefficients(), to);
+}
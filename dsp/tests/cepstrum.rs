@@ -0,0 +1,96 @@
+mod common;
+
+use common::noise;
+use dsp::cepstrum::{complex, inverse, lifter, minimum_phase, peak, pitch, real, Lifter};
+use dsp::fft::Microfft;
+use heapless::consts::{U1024, U256};
+
+/// x plus a copies of it `delay` samples later
+fn echo(x: &[f32], delay: usize, a: f32) -> Vec<f32> {
+    (0..x.len() + delay)
+        .map(|n| {
+            let direct = x.get(n).cloned().unwrap_or(0.0);
+            let echo = n.checked_sub(delay).map_or(0.0, |n| x[n]);
+            direct + a * echo
+        })
+        .collect()
+}
+
+/// A pulse every `period` samples through a decaying resonance
+fn voiced(period: usize, len: usize) -> Vec<f32> {
+    (0..len)
+        .map(|n| {
+            let t = (n % period) as f32;
+            (-t / 12.0).exp() * (0.6 * t).cos()
+        })
+        .collect()
+}
+
+#[test]
+fn echo_delay() {
+    let x = echo(&noise(300), 100, 0.5);
+    let mut c = real::<Microfft, U1024>(&x);
+    assert_eq!(peak(&c, 20..512), 100);
+    // the second rahmonic, negative for an echo of a single reflection
+    assert!(c[200] < 0.0);
+
+    // the echo is all high quefrency
+    lifter(&mut c, Lifter::Low(50));
+    assert_ne!(peak(&c, 20..512), 100);
+    assert!(c[100].abs() < 1e-9 && c[1024 - 100].abs() < 1e-9);
+}
+
+#[test]
+fn pitch_of_pulse_train() {
+    let fs = 8000.0;
+    let x = voiced(80, 1024);
+    let c = real::<Microfft, U1024>(&x);
+    assert_eq!(peak(&c, 20..400), 80);
+    assert!((pitch(&c, fs, 50.0, 400.0).unwrap() - 100.0).abs() < 1e-3);
+    // asking for more than fs still skips quefrency 0
+    assert!(pitch(&c, fs, 50.0, 2.0 * fs).unwrap() <= fs);
+    // a 40 sample cepstrum only reaches 20 samples, 400 Hz, and above
+    assert_eq!(pitch(&c[..40], fs, 50.0, 300.0), None);
+    assert!(pitch(&c[..40], fs, 50.0, 400.0).is_some());
+
+    let mut envelope = c.clone();
+    lifter(&mut envelope, Lifter::Low(30));
+    let mut excitation = c.clone();
+    lifter(&mut excitation, Lifter::High(30));
+    for ((c, e), x) in c.iter().zip(envelope.iter()).zip(excitation.iter()) {
+        assert_eq!(c, &(e + x));
+    }
+}
+
+#[test]
+fn complex_round_trip() {
+    // zeros inside and outside the unit circle, a delay, and a negative sum
+    let x = [0.0, 0.0, 0.0, 1.0, -2.5, 1.0, 0.3, -0.2];
+    let c = complex::<Microfft, U256>(&x);
+    assert_eq!(c.sign, -1.0);
+    // 3 samples late, and the zero at about 1.93 is outside the unit circle
+    assert_eq!(c.delay, 4);
+    let y = inverse::<Microfft, U256>(&c);
+    for (n, y) in y.iter().enumerate() {
+        let x = x.get(n).cloned().unwrap_or(0.0);
+        assert!((x - y).abs() < 1e-5, "{} {} {}", n, x, y);
+    }
+
+    // a minimum phase signal has all of its complex cepstrum at positive
+    // quefrencies, and nothing to take out
+    let c = complex::<Microfft, U256>(&[1.0, 0.5, 0.25]);
+    assert_eq!((c.delay, c.sign), (0, 1.0));
+    assert!(c.cepstrum[129..].iter().all(|c| c.abs() < 1e-6));
+}
+
+#[test]
+fn minimum_phase_reverses_maximum_phase() {
+    // zeros at -2 and -2.5 are outside the unit circle, the minimum phase
+    // version has them reflected in, which is x backwards
+    let x = [0.2, 0.9, 1.0];
+    let y = minimum_phase::<Microfft, U256>(&x);
+    for (n, expected) in [1.0, 0.9, 0.2].iter().enumerate() {
+        assert!((y[n] - expected).abs() < 1e-3, "{} {}", n, y[n]);
+    }
+    assert!(y[3..].iter().all(|y| y.abs() < 1e-3));
+}
@@ -0,0 +1,89 @@
+//! Helpers shared by the integration tests, each test file that needs them
+//! pulls them in with `mod common;`.
+
+// every test file is its own crate and only uses some of these
+#![allow(dead_code)]
+
+use core::fmt::Debug;
+use dsp::Complex;
+
+/// Every value within `tolerance` of the one expected, either of them in
+/// f32 or f64
+#[track_caller]
+pub fn assert_close<A, E>(actual: &[A], expected: &[E], tolerance: f64)
+where
+    A: Copy + Into<f64> + Debug,
+    E: Copy + Into<f64> + Debug,
+{
+    assert_eq!(actual.len(), expected.len(), "lengths differ");
+    for (i, (a, e)) in actual.iter().zip(expected).enumerate() {
+        assert!(
+            ((*a).into() - (*e).into()).abs() < tolerance,
+            "{}: {:?} != {:?}",
+            i,
+            a,
+            e
+        );
+    }
+}
+
+/// Every value within `absolute` of the one expected, or within `relative`
+/// of it for the big ones
+#[track_caller]
+pub fn assert_relative(actual: &[f64], expected: &[f64], absolute: f64, relative: f64) {
+    assert_eq!(actual.len(), expected.len(), "lengths differ");
+    for (i, (a, e)) in actual.iter().zip(expected).enumerate() {
+        assert!(
+            (a - e).abs() <= absolute.max(relative * e.abs()),
+            "{}: {:?} != {:?}",
+            i,
+            actual,
+            expected
+        );
+    }
+}
+
+/// xorshift, enough randomness for property tests without pulling in a crate
+pub struct Random(pub u32);
+
+impl Random {
+    /// uniform in -1..1
+    pub fn sample(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+/// Every complex value within `tolerance` of the one expected, in f32 or f64
+#[track_caller]
+pub fn assert_complex<A, E>(actual: &[Complex<A>], expected: &[Complex<E>], tolerance: f64)
+where
+    A: Copy + Into<f64> + Debug,
+    E: Copy + Into<f64> + Debug,
+{
+    assert_eq!(actual.len(), expected.len(), "lengths differ");
+    for (i, (a, e)) in actual.iter().zip(expected).enumerate() {
+        let re = a.re.into() - e.re.into();
+        let im = a.im.into() - e.im.into();
+        assert!(re.hypot(im) < tolerance, "{}: {:?} != {:?}", i, a, e);
+    }
+}
+
+/// Uniform white noise from -1 to 1, variance 1/3, from a fixed LCG so the
+/// tests always see the same samples
+pub fn noise(len: usize) -> Vec<f32> {
+    seeded_noise(len, 0x2545_f491)
+}
+
+/// [`noise`] from another seed, for signals that mustn't be the same
+pub fn seeded_noise(len: usize, seed: u32) -> Vec<f32> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+        })
+        .collect()
+}
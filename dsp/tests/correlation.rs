@@ -0,0 +1,106 @@
+mod common;
+
+use common::{assert_close, seeded_noise};
+use core::f32::consts::PI;
+use dsp::correlation::{
+    autocorrelate, autocorrelate_fft, circular, circular_fft, correlate, correlate_fft, delay,
+    peak, Scale,
+};
+use dsp::fft::Microfft;
+use heapless::consts::{U1024, U256, U64};
+
+/// A few tones with awkward frequencies and phases, at time t in samples so
+/// it can be delayed by a fraction of one
+fn signal(t: f32) -> f32 {
+    [(0.013, 0.3), (0.047, 1.9), (0.081, 4.0), (0.112, 2.5)]
+        .iter()
+        .map(|(f, p)| (2.0 * PI * f * t + p).cos())
+        .sum()
+}
+
+const SCALES: [Scale; 4] = [
+    Scale::None,
+    Scale::Biased,
+    Scale::Unbiased,
+    Scale::Normalized,
+];
+
+#[test]
+fn fft_matches_direct() {
+    let x = seeded_noise(100, 1);
+    let y = seeded_noise(100, 2);
+    for scale in SCALES.iter() {
+        let mut direct = [0.0f32; 199];
+        let mut fft = [0.0f32; 199];
+        correlate(&x, &y, *scale, &mut direct);
+        correlate_fft::<Microfft, U256>(&x, &y, *scale, &mut fft);
+        assert_close(&direct, &fft, 1e-4);
+
+        autocorrelate(&x, *scale, &mut direct);
+        autocorrelate_fft::<Microfft, U256>(&x, *scale, &mut fft);
+        assert_close(&direct, &fft, 1e-4);
+
+        let mut direct = [0.0f32; 64];
+        let mut fft = [0.0f32; 64];
+        circular(&x[..64], &y[..64], *scale, &mut direct);
+        circular_fft::<Microfft, U64>(&x[..64], &y[..64], *scale, &mut fft);
+        assert_close(&direct, &fft, 1e-4);
+    }
+}
+
+#[test]
+fn scalings() {
+    let x = [1.0f32; 8];
+    let mut r = [0.0f32; 15];
+
+    // the overlap is a triangle, unbiased takes it back out
+    autocorrelate(&x, Scale::None, &mut r);
+    assert_eq!(r[0], 1.0);
+    assert_eq!(r[7], 8.0);
+    autocorrelate(&x, Scale::Biased, &mut r);
+    assert_eq!(r[3], 0.5);
+    autocorrelate(&x, Scale::Unbiased, &mut r);
+    assert!(r.iter().all(|r| *r == 1.0));
+
+    let x = seeded_noise(64, 3);
+    let mut r = [0.0f32; 127];
+    autocorrelate(&x, Scale::Normalized, &mut r);
+    assert!((r[63] - 1.0).abs() < 1e-6);
+    assert!(r.iter().all(|r| r.abs() <= 1.0 + 1e-6));
+    // white noise is only like itself at lag 0
+    assert!(r.iter().enumerate().all(|(i, r)| i == 63 || r.abs() < 0.4));
+
+    // a circular shift of 5 correlates perfectly at lag 5
+    let y = (0..64).map(|n| x[(n + 64 - 5) % 64]).collect::<Vec<f32>>();
+    let mut r = [0.0f32; 64];
+    circular(&y, &x, Scale::Normalized, &mut r);
+    assert!((r[5] - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn parabolic_peak() {
+    // a parabola peaking at 2.3 is found exactly
+    let r = (0..6)
+        .map(|i| 4.0 - (i as f32 - 2.3) * (i as f32 - 2.3))
+        .collect::<Vec<f32>>();
+    assert!((peak(&r) - 2.3).abs() < 1e-5);
+    assert_eq!(peak(&[3.0, 2.0, 1.0]), 0.0);
+}
+
+#[test]
+fn delay_between_noisy_copies() {
+    for d in [25.0f32, 7.4, -12.7].iter() {
+        // two noisy recordings of the same thing, y behind x by d
+        let x = (0..400)
+            .zip(seeded_noise(400, 4))
+            .map(|(n, noise)| signal(n as f32) + 0.5 * noise)
+            .collect::<Vec<f32>>();
+        let y = (0..400)
+            .zip(seeded_noise(400, 5))
+            .map(|(n, noise)| signal(n as f32 - d) + 0.5 * noise)
+            .collect::<Vec<f32>>();
+
+        let estimate = delay::<Microfft, U1024>(&x, &y);
+        assert!((estimate - d).abs() < 0.15, "{} {}", d, estimate);
+    }
+}
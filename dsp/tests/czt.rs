@@ -0,0 +1,107 @@
+mod common;
+
+use common::assert_complex;
+use core::f64::consts::PI;
+use dsp::czt::{Czt, TooShort};
+use dsp::fft::{Cfft, Microfft};
+use dsp::{Complex32, Complex64};
+use heapless::consts::{U2048, U256, U64};
+use heapless::Vec;
+
+/// O(N * M) DTFT in f64 at w0 + k dw
+fn dtft(x: &[Complex32], m: usize, w0: f64, dw: f64) -> std::vec::Vec<Complex64> {
+    (0..m)
+        .map(|k| {
+            x.iter()
+                .enumerate()
+                .fold(Complex64::new(0.0, 0.0), |sum, (n, x)| {
+                    let (s, c) = (-(w0 + dw * k as f64) * n as f64).sin_cos();
+                    let x = Complex64::new(x.re as f64, x.im as f64);
+                    sum + x * Complex64::new(c, s)
+                })
+        })
+        .collect()
+}
+
+fn signal(len: usize) -> std::vec::Vec<Complex32> {
+    (0..len)
+        .map(|n| {
+            let n = n as f32;
+            Complex32::new((0.3 * n).sin() + 0.5, (0.001 * n * n).cos())
+        })
+        .collect()
+}
+
+#[test]
+fn arbitrary_length_dft() {
+    // the accelerometer buffer length
+    let x = signal(1000);
+    let czt = Czt::<Microfft, U2048>::dft(1000).unwrap();
+    assert_eq!((czt.input_len(), czt.len()), (1000, 1000));
+    let mut out = Vec::new();
+    czt.transform(&x, &mut out);
+    assert_complex(&out, &dtft(&x, 1000, 0.0, 2.0 * PI / 1000.0), 1e-1);
+
+    // and something prime and short
+    let x = signal(37);
+    let czt = Czt::<Microfft, U256>::dft(37).unwrap();
+    let mut out = Vec::new();
+    czt.transform(&x, &mut out);
+    assert_complex(&out, &dtft(&x, 37, 0.0, 2.0 * PI / 37.0), 1e-3);
+}
+
+#[test]
+fn matches_fft() {
+    let x = signal(64).into_iter().collect::<Vec<Complex32, U64>>();
+    let mut expected = x.clone();
+    Microfft::cfft(&mut expected);
+
+    let mut out = Vec::new();
+    Czt::<Microfft, U256>::dft(64)
+        .unwrap()
+        .transform(&x, &mut out);
+    for (a, e) in out.iter().zip(expected.iter()) {
+        assert!((a - e).norm_sqr() < 1e-6, "{} != {}", a, e);
+    }
+}
+
+#[test]
+fn zoom() {
+    // 0.3 rad/sample sine at fs 1000 is 47.7 Hz, look at 40 to 56 Hz in
+    // 0.1 Hz steps, 16 times finer than the DFT bins
+    let x = signal(600);
+    let czt = Czt::<Microfft, U2048>::zoom(600, 161, 40.0, 0.1, 1000.0).unwrap();
+    let mut out = Vec::new();
+    czt.transform(&x, &mut out);
+    let w = 2.0 * PI / 1000.0;
+    assert_complex(&out, &dtft(&x, 161, 40.0 * w, 0.1 * w), 5e-2);
+
+    let peak = (0..out.len()).fold(0, |m, k| {
+        if out[k].norm_sqr() > out[m].norm_sqr() {
+            k
+        } else {
+            m
+        }
+    });
+    assert!((czt.frequency(peak, 1000.0) - 47.7).abs() < 0.1);
+
+    // real input is the same as complex with zero imaginary parts
+    let re = x.iter().map(|x| x.re).collect::<std::vec::Vec<_>>();
+    let mut real = Vec::new();
+    czt.transform_real(&re, &mut real);
+    let widened = re
+        .iter()
+        .map(|x| Complex32::new(*x, 0.0))
+        .collect::<std::vec::Vec<_>>();
+    czt.transform(&widened, &mut out);
+    assert_eq!(real, out);
+}
+
+#[test]
+fn too_short() {
+    assert_eq!(
+        Czt::<Microfft, U2048>::dft(1025).err(),
+        Some(TooShort { needed: 2049 })
+    );
+    assert!(Czt::<Microfft, U2048>::dft(1024).is_ok());
+}
@@ -0,0 +1,170 @@
+mod common;
+
+use common::{assert_close, Random};
+use core::f64::consts::PI;
+use dsp::dct::{sine, Dct};
+use dsp::fft::Microfft;
+use heapless::consts::{U1024, U16, U256, U4, U64};
+use heapless::{ArrayLength, Vec};
+
+fn random(len: usize) -> impl Iterator<Item = f32> {
+    let mut random = Random(0x2545_f491);
+    (0..len).map(move |_| random.sample())
+}
+
+/// O(N^2) orthonormal DCT-II in f64
+fn dct2(x: &[f32]) -> std::vec::Vec<f64> {
+    let n = x.len() as f64;
+    (0..x.len())
+        .map(|k| {
+            let s = if k == 0 {
+                (1.0 / n).sqrt()
+            } else {
+                (2.0 / n).sqrt()
+            };
+            s * x.iter().enumerate().fold(0.0, |sum, (i, x)| {
+                sum + *x as f64 * (PI / n * (i as f64 + 0.5) * k as f64).cos()
+            })
+        })
+        .collect()
+}
+
+/// O(N^2) orthonormal DCT-IV in f64
+fn dct4(x: &[f32]) -> std::vec::Vec<f64> {
+    let n = x.len() as f64;
+    (0..x.len())
+        .map(|k| {
+            (2.0 / n).sqrt()
+                * x.iter().enumerate().fold(0.0, |sum, (i, x)| {
+                    sum + *x as f64 * (PI / n * (i as f64 + 0.5) * (k as f64 + 0.5)).cos()
+                })
+        })
+        .collect()
+}
+
+/// O(N^2) MDCT of a 2N frame in f64, with the same sqrt(2/N) scaling
+fn mdct(x: &[f32]) -> std::vec::Vec<f64> {
+    let n = x.len() as f64 / 2.0;
+    (0..x.len() / 2)
+        .map(|k| {
+            (2.0 / n).sqrt()
+                * x.iter().enumerate().fold(0.0, |sum, (i, x)| {
+                    let t = PI / n * (i as f64 + 0.5 + n / 2.0) * (k as f64 + 0.5);
+                    sum + *x as f64 * t.cos()
+                })
+        })
+        .collect()
+}
+
+fn check<N: ArrayLength<f32>>()
+where
+    Microfft: Dct<N>,
+{
+    let x = random(N::to_usize()).collect::<Vec<f32, N>>();
+
+    let mut actual = x.clone();
+    Microfft::dct2(&mut actual);
+    assert_close(&actual, &dct2(&x), 1e-4);
+    Microfft::dct3(&mut actual);
+    let x64 = x.iter().map(|x| *x as f64).collect::<std::vec::Vec<_>>();
+    assert_close(&actual, &x64, 1e-5);
+
+    let mut actual = x.clone();
+    Microfft::dct4(&mut actual);
+    assert_close(&actual, &dct4(&x), 1e-4);
+    Microfft::dct4(&mut actual);
+    assert_close(&actual, &x64, 1e-5);
+}
+
+#[test]
+fn matches_direct() {
+    check::<U4>();
+    check::<U16>();
+    check::<U256>();
+    check::<U1024>();
+}
+
+#[test]
+fn orthonormal() {
+    // the basis vectors come out orthonormal
+    let basis = |transform: fn(&mut Vec<f32, U16>)| {
+        (0..16)
+            .map(|i| {
+                let mut e = Vec::<f32, U16>::new();
+                e.resize(16, 0.0).unwrap();
+                e[i] = 1.0;
+                transform(&mut e);
+                e
+            })
+            .collect::<std::vec::Vec<_>>()
+    };
+    for columns in [
+        basis(Microfft::dct2),
+        basis(Microfft::dct3),
+        basis(Microfft::dct4),
+    ]
+    .iter()
+    {
+        for (i, a) in columns.iter().enumerate() {
+            for (j, b) in columns.iter().enumerate() {
+                let dot = a.iter().zip(b.iter()).map(|(a, b)| a * b).sum::<f32>();
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((dot - expected).abs() < 1e-5, "{} {} {}", i, j, dot);
+            }
+        }
+    }
+
+    // so energy is kept
+    let x = random(1024).collect::<Vec<f32, U1024>>();
+    let energy = |x: &[f32]| x.iter().map(|x| x * x).sum::<f32>();
+    let mut y = x.clone();
+    Microfft::dct2(&mut y);
+    assert!((energy(&y) - energy(&x)).abs() < 1e-3 * energy(&x));
+    Microfft::dct4(&mut y);
+    assert!((energy(&y) - energy(&x)).abs() < 1e-3 * energy(&x));
+}
+
+#[test]
+fn mdct_matches_direct() {
+    let window = sine(128).collect::<std::vec::Vec<f32>>();
+    let frame = random(128)
+        .zip(window.iter())
+        .map(|(x, w)| x * w)
+        .collect::<std::vec::Vec<_>>();
+
+    let mut previous = frame[..64].iter().cloned().collect::<Vec<f32, U64>>();
+    let mut x = frame[64..].iter().cloned().collect::<Vec<f32, U64>>();
+    // the window is already applied
+    Microfft::mdct(&mut x, &[1.0; 128], &mut previous);
+    assert_close(&x, &mdct(&frame), 1e-4);
+    assert_eq!(&previous[..], &frame[64..]);
+}
+
+#[test]
+fn mdct_perfect_reconstruction() {
+    let window = sine(128).collect::<std::vec::Vec<f32>>();
+    let signal = random(64 * 20).collect::<std::vec::Vec<f32>>();
+
+    let mut previous = Vec::<f32, U64>::new();
+    let mut overlap = Vec::<f32, U64>::new();
+    let mut output = std::vec::Vec::new();
+    // one more block of zeros to flush the last frame out
+    for block in signal.chunks(64).chain(core::iter::once(&[0.0; 64][..])) {
+        let mut x = block.iter().cloned().collect::<Vec<f32, U64>>();
+        Microfft::mdct(&mut x, &window, &mut previous);
+        Microfft::imdct(&mut x, &window, &mut overlap);
+        output.extend_from_slice(&x);
+    }
+
+    // a block behind, after the frame that started from zeros
+    let expected = signal
+        .iter()
+        .map(|x| *x as f64)
+        .collect::<std::vec::Vec<_>>();
+    assert_close(&output[64..], &expected, 1e-5);
+
+    // and the window meets the TDAC condition
+    for (a, b) in window[..64].iter().zip(window[64..].iter()) {
+        assert!((a * a + b * b - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,257 @@
+mod common;
+
+use common::Random;
+use core::f64::consts::PI;
+use dsp::fft::{multiply, Cfft, Microfft, Rfft};
+use dsp::spectrum::{bins, Spectrum};
+use dsp::Complex32;
+use heapless::consts::{U16, U512, U64};
+use heapless::{ArrayLength, Vec};
+
+/// O(N^2) DFT in f64 to check against
+fn dft(x: &[Complex32], n: usize) -> std::vec::Vec<(f64, f64)> {
+    (0..n)
+        .map(|k| {
+            x.iter().enumerate().fold((0.0, 0.0), |(re, im), (m, x)| {
+                let w = -2.0 * PI * (k * m) as f64 / n as f64;
+                let (s, c) = w.sin_cos();
+                (
+                    re + x.re as f64 * c - x.im as f64 * s,
+                    im + x.re as f64 * s + x.im as f64 * c,
+                )
+            })
+        })
+        .collect()
+}
+
+fn check<N: ArrayLength<Complex32>>(len: usize)
+where
+    Microfft: Cfft<N>,
+{
+    let x = (0..len)
+        .map(|n| {
+            let n = n as f32;
+            Complex32::new((0.3 * n).sin() + 0.5, (0.05 * n * n).cos())
+        })
+        .collect::<Vec<Complex32, N>>();
+    let expected = dft(&x, N::to_usize());
+
+    let mut actual = x.clone();
+    Microfft::cfft(&mut actual);
+    assert_eq!(actual.len(), N::to_usize());
+    for (a, (re, im)) in actual.iter().zip(expected) {
+        let tolerance = 1e-4 * N::to_usize() as f64;
+        assert!(
+            (a.re as f64 - re).abs() < tolerance && (a.im as f64 - im).abs() < tolerance,
+            "{:?} != {} {}",
+            a,
+            re,
+            im
+        );
+    }
+}
+
+#[test]
+fn microfft_matches_dft() {
+    check::<U16>(16);
+    check::<U64>(64);
+    check::<U512>(512);
+}
+
+#[test]
+fn short_input_is_zero_padded() {
+    check::<U16>(5);
+    check::<U512>(300);
+}
+
+fn real_input<N: ArrayLength<f32>>() -> Vec<f32, N> {
+    (0..N::to_usize())
+        .map(|n| {
+            let n = n as f32;
+            (PI as f32 * n / 8.0).sin() + 0.5 * (0.3 * n).cos() + 0.25
+        })
+        .collect()
+}
+
+#[test]
+fn rfft_matches_cfft() {
+    let x = real_input::<U64>();
+    let mut complex = x
+        .iter()
+        .map(|x| Complex32::new(*x, 0.0))
+        .collect::<Vec<Complex32, U64>>();
+    Microfft::cfft(&mut complex);
+
+    let mut real = x.clone();
+    let packed = Microfft::rfft(&mut real);
+    assert_eq!(packed.len(), 32);
+    // DC and Nyquist share bin 0
+    assert!((packed[0].re - complex[0].re).abs() < 1e-4);
+    assert!((packed[0].im - complex[32].re).abs() < 1e-4);
+
+    let bins = bins(packed).collect::<std::vec::Vec<_>>();
+    assert_eq!(bins.len(), 33);
+    for (a, e) in bins.iter().zip(complex.iter()) {
+        assert!((a - e).norm_sqr().sqrt() < 1e-4, "{} != {}", a, e);
+    }
+    let (mut m, mut p) = ([0.0; 33], [0.0; 33]);
+    Microfft::magnitude_packed(packed, &mut m);
+    Microfft::power_packed(packed, &mut p);
+    for ((m, p), e) in m.iter().zip(p.iter()).zip(complex.iter()) {
+        assert!((m - e.norm_sqr().sqrt()).abs() < 1e-4);
+        assert!((p - e.norm_sqr()).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn rfft_round_trip() {
+    let x = real_input::<U512>();
+    let mut y = x.clone();
+    Microfft::rfft(&mut y);
+    Microfft::irfft(&mut y);
+    for (x, y) in x.iter().zip(y.iter()) {
+        assert!((x - y).abs() < 1e-5, "{} != {}", x, y);
+    }
+
+    // an impulse is flat and back again
+    let mut impulse = Vec::<f32, U16>::new();
+    impulse.push(1.0).unwrap();
+    let packed = Microfft::rfft(&mut impulse);
+    assert!(bins(packed).all(|b| (b - Complex32::new(1.0, 0.0)).norm_sqr() < 1e-12));
+    Microfft::irfft(&mut impulse);
+    assert!((impulse[0] - 1.0).abs() < 1e-6);
+    assert!(impulse[1..].iter().all(|x| x.abs() < 1e-6));
+}
+
+#[test]
+fn multiply_is_circular_convolution() {
+    let x = real_input::<U16>();
+    let h = [0.5f32, 0.25, 0.0, -0.125];
+    let expected = (0..16)
+        .map(|n| {
+            (0..h.len())
+                .map(|k| h[k] * x[(n + 16 - k) % 16])
+                .sum::<f32>()
+        })
+        .collect::<std::vec::Vec<_>>();
+
+    let mut y = x.clone();
+    let mut h = Vec::<f32, U16>::from_slice(&h).unwrap();
+    let h = Microfft::rfft(&mut h);
+    multiply(Microfft::rfft(&mut y), h);
+    Microfft::irfft(&mut y);
+    for (y, e) in y.iter().zip(expected) {
+        assert!((y - e).abs() < 1e-5, "{} != {}", y, e);
+    }
+}
+
+const TRIALS: usize = 20;
+
+fn complex_properties<N: ArrayLength<Complex32>>(random: &mut Random)
+where
+    Microfft: Cfft<N>,
+{
+    for _ in 0..TRIALS {
+        let x = (0..N::to_usize())
+            .map(|_| Complex32::new(random.sample(), random.sample()))
+            .collect::<Vec<Complex32, N>>();
+
+        let mut y = x.clone();
+        Microfft::cfft(&mut y);
+
+        // Parseval, sum |x|^2 == sum |X|^2 / N
+        let energy = x.iter().map(|x| x.norm_sqr() as f64).sum::<f64>();
+        let spectrum = y.iter().map(|y| y.norm_sqr() as f64).sum::<f64>() / N::to_usize() as f64;
+        assert!(
+            (energy - spectrum).abs() < 1e-5 * energy,
+            "{} {} {}",
+            N::to_usize(),
+            energy,
+            spectrum
+        );
+
+        Microfft::icfft(&mut y);
+        for (x, y) in x.iter().zip(y.iter()) {
+            assert!(
+                (x - y).norm_sqr().sqrt() < 1e-5,
+                "{}: {} != {}",
+                N::to_usize(),
+                x,
+                y
+            );
+        }
+    }
+}
+
+fn real_properties<N: ArrayLength<f32>>(random: &mut Random)
+where
+    Microfft: Rfft<N>,
+{
+    for _ in 0..TRIALS {
+        let x = (0..N::to_usize())
+            .map(|_| random.sample())
+            .collect::<Vec<f32, N>>();
+
+        let mut y = x.clone();
+        let packed = Microfft::rfft(&mut y);
+
+        // every bin but DC and Nyquist stands in for its negative frequency too
+        let energy = x.iter().map(|x| (x * x) as f64).sum::<f64>();
+        let mut power = std::vec![0.0; packed.len() + 1];
+        Microfft::power_packed(packed, &mut power);
+        let half = power
+            .iter()
+            .map(|p| *p as f64)
+            .collect::<std::vec::Vec<_>>();
+        let last = half.len() - 1;
+        let spectrum =
+            (half[0] + half[last] + 2.0 * half[1..last].iter().sum::<f64>()) / N::to_usize() as f64;
+        assert!(
+            (energy - spectrum).abs() < 1e-5 * energy,
+            "{} {} {}",
+            N::to_usize(),
+            energy,
+            spectrum
+        );
+
+        Microfft::irfft(&mut y);
+        for (x, y) in x.iter().zip(y.iter()) {
+            assert!((x - y).abs() < 1e-5, "{}: {} != {}", N::to_usize(), x, y);
+        }
+    }
+}
+
+#[test]
+fn complex_round_trip_and_parseval() {
+    use heapless::consts::*;
+    let mut random = Random(0x2545_f491);
+    complex_properties::<U2>(&mut random);
+    complex_properties::<U4>(&mut random);
+    complex_properties::<U8>(&mut random);
+    complex_properties::<U16>(&mut random);
+    complex_properties::<U32>(&mut random);
+    complex_properties::<U64>(&mut random);
+    complex_properties::<U128>(&mut random);
+    complex_properties::<U256>(&mut random);
+    complex_properties::<U512>(&mut random);
+    complex_properties::<U1024>(&mut random);
+    complex_properties::<U2048>(&mut random);
+    complex_properties::<U4096>(&mut random);
+}
+
+#[test]
+fn real_round_trip_and_parseval() {
+    use heapless::consts::*;
+    let mut random = Random(0x9e37_79b9);
+    real_properties::<U4>(&mut random);
+    real_properties::<U8>(&mut random);
+    real_properties::<U16>(&mut random);
+    real_properties::<U32>(&mut random);
+    real_properties::<U64>(&mut random);
+    real_properties::<U128>(&mut random);
+    real_properties::<U256>(&mut random);
+    real_properties::<U512>(&mut random);
+    real_properties::<U1024>(&mut random);
+    real_properties::<U2048>(&mut random);
+    real_properties::<U4096>(&mut random);
+}
@@ -0,0 +1,133 @@
+use core::f64::consts::{FRAC_PI_4, PI};
+use dsp::fixed::{limit_cycle, snr_db, Cascade, ErrorFeedback, Fixed, QuantizeError};
+use dsp::sos::{self, Norm, Section};
+
+// 2_23 low pass
+fn low_pass() -> sos::Sections {
+    sos::from_tf(&[0.002044, 0.004088, 0.002044], &[1.0, -1.819168, 0.827343]).unwrap()
+}
+
+/// the 2_23 input at half scale so it fits
+fn input() -> Vec<f64> {
+    (0..4096)
+        .map(|n| 0.25 * ((PI * n as f64 / 128.0).sin() + (FRAC_PI_4 * n as f64).sin()))
+        .collect()
+}
+
+fn snr<T: Fixed>(sections: &[Section], feedback: ErrorFeedback) -> f64 {
+    let mut reference = sos::Cascade::new(sections);
+    let mut cascade = Cascade::<T>::new(sections, feedback).unwrap();
+    let x = input();
+    let expected = x
+        .iter()
+        .map(|x| reference.process(*x as f32) as f64)
+        .collect::<Vec<_>>();
+    let actual = x
+        .iter()
+        .map(|x| cascade.process(T::from_f64(*x)).to_f64())
+        .collect::<Vec<_>>();
+    assert_eq!(cascade.saturations, 0);
+    snr_db(&expected, &actual)
+}
+
+#[test]
+fn quantization() {
+    assert_eq!(i16::from_f64(0.5), 16384);
+    assert_eq!(i16::from_f64(1.0), i16::MAX);
+    assert_eq!(i16::from_f64(-2.0), i16::MIN);
+    assert_eq!(i32::from_f64(-0.25), -(1 << 29));
+
+    // a1 = -1.819 needs one bit of postShift, like CMSIS
+    let cascade = Cascade::<i16>::new(&low_pass(), ErrorFeedback::None).unwrap();
+    assert_eq!(cascade.post_shift(), 1);
+    let quantized = cascade.quantized();
+    for (q, s) in quantized.iter().zip(low_pass().iter()) {
+        for (q, s) in
+            q.b.iter()
+                .chain(q.a.iter())
+                .zip(s.b.iter().chain(s.a.iter()))
+        {
+            assert!((q - s).abs() <= 2.0 / 32768.0, "{} {}", q, s);
+        }
+    }
+}
+
+#[test]
+fn signal_to_noise() {
+    let q15 = snr::<i16>(&low_pass(), ErrorFeedback::None);
+    let q31 = snr::<i32>(&low_pass(), ErrorFeedback::None);
+    assert!(q15 > 30.0, "{}", q15);
+    // beyond here it's the f32 reference that's noisy
+    assert!(q31 > 100.0, "{}", q31);
+
+    // shaping the noise out of the passband of the low pass helps
+    let shaped = snr::<i16>(&low_pass(), ErrorFeedback::FirstOrder);
+    assert!(shaped > q15 + 3.0, "{} {}", shaped, q15);
+}
+
+#[test]
+fn saturation_is_counted() {
+    // resonator with a peak gain around 50
+    let resonator = [Section {
+        b: [1.0, 0.0, 0.0],
+        a: [1.0, -1.6, 0.98],
+    }];
+    let x = input();
+
+    let mut cascade = Cascade::<i16>::new(&resonator, ErrorFeedback::None).unwrap();
+    x.iter().for_each(|x| {
+        cascade.process(i16::from_f64(*x));
+    });
+    assert!(cascade.saturations > 100, "{}", cascade.saturations);
+
+    // Linf scaling guarantees no sine can overflow. The input here is a sum
+    // of two sines so halve it again.
+    let mut scaled = resonator;
+    sos::scale(&mut scaled, Norm::LInf);
+    let mut cascade = Cascade::<i16>::new(&scaled, ErrorFeedback::None).unwrap();
+    x.iter().for_each(|x| {
+        cascade.process(i16::from_f64(x / 2.0));
+    });
+    assert_eq!(cascade.saturations, 0);
+}
+
+#[test]
+fn unquantizable_coefficients() {
+    let mut sections = [Section {
+        b: [1.0, 0.0, 0.0],
+        a: [1.0, -1.6, 0.98],
+    }];
+    sections[0].b[1] = f64::INFINITY;
+    let cascade = Cascade::<i16>::new(&sections, ErrorFeedback::None);
+    assert_eq!(cascade.err(), Some(QuantizeError::NotFinite));
+    sections[0].b[1] = f64::NAN;
+    let cascade = Cascade::<i32>::new(&sections, ErrorFeedback::None);
+    assert_eq!(cascade.err(), Some(QuantizeError::NotFinite));
+
+    // 2^15 would need a postShift of 16, leaving Q15 no fraction bits
+    sections[0].b[1] = 32768.0;
+    let cascade = Cascade::<i16>::new(&sections, ErrorFeedback::None);
+    assert_eq!(cascade.err(), Some(QuantizeError::TooLarge));
+    // just under still fits, with every bit as integer part
+    sections[0].b[1] = 32767.0;
+    let cascade = Cascade::<i16>::new(&sections, ErrorFeedback::None).unwrap();
+    assert_eq!(cascade.post_shift(), 15);
+    // Q31 has room for it
+    sections[0].b[1] = 32768.0;
+    assert!(Cascade::<i32>::new(&sections, ErrorFeedback::None).is_ok());
+}
+
+#[test]
+fn limit_cycles() {
+    // truncation leaves the low pass stuck at a DC offset
+    let mut cascade = Cascade::<i16>::new(&low_pass(), ErrorFeedback::None).unwrap();
+    let cycle = limit_cycle(&mut cascade, i16::from_f64(0.5), 2000).unwrap();
+    assert_eq!(cycle.period, Some(1));
+    assert!(cycle.amplitude > 10);
+
+    // which first order error feedback removes
+    let mut cascade = Cascade::<i16>::new(&low_pass(), ErrorFeedback::FirstOrder).unwrap();
+    for kick in [0.5, -0.5, 0.1, -0.9].iter() {
+        assert_eq!(limit_cycle(&mut cascade, i16::from_f64(*kick), 2000), None);
+    }
+}
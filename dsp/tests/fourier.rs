@@ -0,0 +1,136 @@
+use core::f32::consts::PI;
+use dsp::fft::{Cfft, Microfft};
+use dsp::fourier::Series;
+use dsp::Complex32;
+use heapless::consts::{U1, U16, U256, U8};
+
+/// square wave between -1 and 1
+fn square(period: usize) -> Vec<f32> {
+    (0..period)
+        .map(|n| if n < period / 2 { 1.0 } else { -1.0 })
+        .collect()
+}
+
+/// triangle wave between -1 and 1
+fn triangle(period: usize) -> Vec<f32> {
+    (0..period)
+        .map(|n| 1.0 - 4.0 * (n as f32 / period as f32 - 0.5).abs())
+        .collect()
+}
+
+fn rms(a: &[f32], b: &[f32]) -> f32 {
+    let sum = a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum::<f32>();
+    (sum / a.len() as f32).sqrt()
+}
+
+#[test]
+fn synthesis_rebuilds_signal() {
+    // 12 isn't a power of two, and 3 periods get averaged
+    let period = (0..12)
+        .map(|n| (n as f32 * 0.7).sin() + if n == 3 { 2.0 } else { 0.0 })
+        .collect::<Vec<f32>>();
+    let x = period.repeat(3);
+    let series = Series::<U8>::analyze(&x, 12);
+    assert_eq!(series.len(), 7);
+
+    let y = series
+        .synthesize(12, |_| true)
+        .take(1200)
+        .collect::<Vec<f32>>();
+    for (n, y) in y.iter().enumerate() {
+        assert!((y - period[n % 12]).abs() < 1e-5, "{} {}", n, y);
+    }
+    // started over every period, so the 100th is the first exactly
+    assert_eq!(y[..12], y[1188..]);
+}
+
+#[test]
+fn from_fft_matches_analyze() {
+    let x = square(16);
+    let mut bins = x
+        .iter()
+        .map(|x| Complex32::new(*x, 0.0))
+        .collect::<heapless::Vec<Complex32, U16>>();
+    Microfft::cfft(&mut bins);
+
+    let fft = Series::<U16>::from_fft(&bins);
+    let direct = Series::<U16>::analyze(&x, 16);
+    assert_eq!(fft.period(), 16);
+    assert_eq!(fft.len(), 9);
+    for (a, b) in fft.harmonics().iter().zip(direct.harmonics()) {
+        assert!((a - b).norm_sqr() < 1e-10, "{} {}", a, b);
+    }
+    assert!((fft.mean_square() - 1.0).abs() < 1e-5);
+    assert!((direct.mean_square() - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn amplitudes_phases_and_thd() {
+    let x = (0..40)
+        .map(|n| {
+            let w = 2.0 * PI * n as f32 / 20.0;
+            0.25 + w.cos() + 0.5 * (3.0 * w + 0.3).cos()
+        })
+        .collect::<Vec<f32>>();
+    let series = Series::<U8>::analyze(&x, 20);
+    assert!((series.amplitude(0) - 0.25).abs() < 1e-5);
+    assert!((series.amplitude(1) - 1.0).abs() < 1e-5);
+    assert!(series.amplitude(2) < 1e-5);
+    assert!((series.amplitude(3) - 0.5).abs() < 1e-5);
+    assert!((series.phase(3) - 0.3).abs() < 1e-4);
+    assert!((series.thd().unwrap() - 0.5).abs() < 1e-4);
+
+    // nothing to compare against without a fundamental
+    let dc = Series::<U8>::analyze(&[1.0; 20], 20);
+    assert_eq!(dc.thd(), None);
+    assert_eq!(Series::<U1>::analyze(&x, 20).thd(), None);
+    assert_eq!(Series::<U8>::analyze(&x[..1], 1).thd(), None);
+
+    // 1 + 1/9 + 1/25 ... over the fundamental, sqrt(pi^2/8 - 1) in the limit
+    let square = Series::<U256>::analyze(&square(1024), 1024);
+    let thd = square.thd().unwrap();
+    assert!((thd - 0.483).abs() < 0.01, "{}", thd);
+}
+
+#[test]
+fn error_per_harmonic() {
+    let x = triangle(64);
+    let series = Series::<U16>::analyze(&x, 64);
+    let mut last = f32::INFINITY;
+    for k in 0..16 {
+        let y = series
+            .synthesize(64, |j| j <= k)
+            .take(64)
+            .collect::<Vec<f32>>();
+        let error = series.error(k);
+        assert!((error - rms(&x, &y)).abs() < 1e-4, "{} {}", k, error);
+        assert!(error <= last);
+        last = error;
+    }
+    // only odd harmonics, so adding an even one changes nothing
+    assert!((series.error(3) - series.error(4)).abs() < 1e-6);
+    assert!(series.error(15) < 0.01);
+}
+
+#[test]
+fn gibbs() {
+    // nearly continuous, drawn 8 times finer than it was sampled
+    let square = Series::<U256>::analyze(&square(1024), 1024);
+    let triangle = Series::<U256>::analyze(&triangle(1024), 1024);
+    for harmonics in [17, 33, 65].iter() {
+        let peak = square
+            .synthesize(8192, |k| k <= *harmonics)
+            .take(8192)
+            .fold(f32::MIN, f32::max);
+        // about 9% of the jump of 2, however many harmonics
+        let overshoot = (peak - 1.0) / 2.0;
+        assert!((overshoot - 0.0895).abs() < 0.005, "{} {}", harmonics, peak);
+
+        // continuous, so the series never goes past it
+        let peak = triangle
+            .synthesize(8192, |k| k <= *harmonics)
+            .take(8192)
+            .fold(f32::MIN, f32::max);
+        assert!(peak <= 1.0 + 1e-4, "{} {}", harmonics, peak);
+    }
+}
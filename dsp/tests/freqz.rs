@@ -0,0 +1,68 @@
+use core::f64::consts::PI;
+use dsp::freqz::{db, freqz, group_delay, Response};
+use dsp::iir::{butter, Band};
+use dsp::spectrum::{phase, unwrap};
+
+#[test]
+fn butterworth_corner() {
+    let (b, a) = butter(4, Band::LowPass(0.25)).unwrap().to_tf();
+    assert!((db(freqz(&b, &a, 0.0))).abs() < 1e-9);
+    assert!((db(freqz(&b, &a, 0.25 * PI)) + 3.0103).abs() < 1e-3);
+    // zeros at Nyquist
+    assert!(db(freqz(&b, &a, PI)) < -200.0);
+}
+
+#[test]
+fn linear_phase_group_delay() {
+    // symmetric FIR with 8 taps delays everything by 3.5 samples
+    let h = [0.1f32, 0.2, 0.4, 0.8, 0.8, 0.4, 0.2, 0.1];
+    let response = Response::new(&h, &[1.0], 1000.0, 256);
+    for (gd, pd) in response
+        .group_delay
+        .iter()
+        .zip(response.phase_delay().iter())
+        .take(100)
+    {
+        assert!((gd - 3.5).abs() < 1e-9, "{}", gd);
+        assert!((pd - 3.5).abs() < 1e-9, "{}", pd);
+    }
+
+    // and the slope of the unwrapped phase agrees
+    let phase = response.unwrapped_phase();
+    let dw = 2.0 * PI * (response.hz[1] - response.hz[0]) / response.fs;
+    for p in phase.windows(2).take(100) {
+        assert!(((p[0] - p[1]) / dw - 3.5).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn first_order_group_delay() {
+    // 1 / (1 - a z^-1) has group delay (a cos w - a^2) / (1 - 2a cos w + a^2)
+    let a = 0.8;
+    for i in 0..10 {
+        let w = PI * i as f64 / 10.0;
+        let expected = (a * w.cos() - a * a) / (1.0 - 2.0 * a * w.cos() + a * a);
+        assert!((group_delay(&[1.0], &[1.0, -a], w) - expected).abs() < 1e-12);
+    }
+    // a zero on the unit circle has no group delay
+    assert!(group_delay(&[1.0, 1.0], &[1.0], PI).is_nan());
+}
+
+#[test]
+fn unwrapping() {
+    // a pure delay of 5 samples wraps around 2.5 times between DC and Nyquist
+    let h = (0..=100)
+        .map(|i| {
+            let w = PI * i as f64 / 100.0;
+            freqz(&[0.0, 0.0, 0.0, 0.0, 0.0, 1.0], &[1.0], w)
+        })
+        .collect::<Vec<_>>();
+    let mut phases = vec![0.0; h.len()];
+    phase(&h, &mut phases);
+    assert!(phases.iter().all(|p| p.abs() <= PI));
+    unwrap(&mut phases);
+    for (i, p) in phases.iter().enumerate() {
+        let w = PI * i as f64 / 100.0;
+        assert!((p + 5.0 * w).abs() < 1e-9, "{} {}", i, p);
+    }
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::assert_complex;
+use core::f32::consts::{FRAC_PI_4, PI};
+use dsp::fft::{Microfft, Rfft};
+use dsp::goertzel::{magnitude, phase, Bank, Goertzel};
+use dsp::spectrum::bins;
+use dsp::Complex32;
+use heapless::consts::{U4, U512};
+
+/// the 2_23 input
+fn input() -> heapless::Vec<f32, U512> {
+    (0..512)
+        .map(|n| (PI * n as f32 / 128.0).sin() + (FRAC_PI_4 * n as f32).sin())
+        .collect()
+}
+
+#[test]
+fn matches_fft_bins() {
+    let x = input();
+    let mut spectrum = x.clone();
+    let expected = bins(Microfft::rfft(&mut spectrum)).collect::<Vec<_>>();
+
+    // the two tones, DC, a bin in between and Nyquist
+    for k in [2, 64, 0, 17, 256].iter() {
+        let mut goertzel = Goertzel::new(*k as f32, 512);
+        let actual = goertzel.block(&x);
+        assert_complex(&[actual], &[expected[*k]], 1e-2);
+        assert!((goertzel.power() - expected[*k].norm_sqr()).abs() < 1.0);
+    }
+    assert!((magnitude(expected[64]) - 256.0).abs() < 1e-2);
+}
+
+#[test]
+fn fractional_bins() {
+    let x = input();
+    for k in [1.5f32, 2.25, 63.7].iter() {
+        // straight DTFT sum in f64
+        let w = 2.0 * core::f64::consts::PI * *k as f64 / 512.0;
+        let (re, im) = x.iter().enumerate().fold((0.0, 0.0), |(re, im), (n, x)| {
+            let (s, c) = (w * n as f64).sin_cos();
+            (re + *x as f64 * c, im - *x as f64 * s)
+        });
+        let expected = Complex32::new(re as f32, im as f32);
+        let actual = Goertzel::new(*k, 512).block(&x);
+        // the f32 resonator loses a little near DC where 2cos(w) is almost 2
+        let tolerance = 1e-3 * magnitude(expected) + 1e-2;
+        assert_complex(&[actual], &[expected], tolerance as f64);
+    }
+
+    // a cosine half way between bins comes back with its phase, 10.5 cycles
+    // so the negative frequency image sums to nothing
+    let w = 2.0 * PI * 10.5 / 512.0;
+    let x = (0..512)
+        .map(|n| (w * n as f32 + 0.5).cos())
+        .collect::<Vec<_>>();
+    let actual = Goertzel::new(10.5, 512).block(&x);
+    assert!((magnitude(actual) - 256.0).abs() < 0.1, "{}", actual);
+    assert!((phase(actual) - 0.5).abs() < 1e-3, "{}", actual);
+}
+
+#[test]
+fn streaming_matches_block() {
+    let x = input();
+    let mut block = Goertzel::frequency(1000.0, 16000.0, 128);
+    let mut streaming = block.clone();
+
+    let results = x
+        .iter()
+        .filter_map(|x| streaming.process(*x))
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 4);
+    let expected = x
+        .chunks(128)
+        .map(|chunk| block.block(chunk))
+        .collect::<Vec<_>>();
+    assert_complex(&results, &expected, 1e-3);
+}
+
+#[test]
+fn bank() {
+    let x = input();
+    let k = [2.0, 8.0, 64.0, 100.5];
+    let mut bank = Bank::<U4>::new(&k, 512);
+    let block = bank.block(&x);
+
+    let single = k
+        .iter()
+        .map(|k| Goertzel::new(*k, 512).block(&x))
+        .collect::<Vec<_>>();
+    assert_complex(&block, &single, 1e-3);
+
+    let streamed = x.iter().filter_map(|x| bank.process(*x)).last().unwrap();
+    assert_complex(&streamed, &block, 1e-3);
+
+    // only the tones show up
+    let magnitudes = block.iter().map(|b| magnitude(*b)).collect::<Vec<_>>();
+    assert!(
+        magnitudes[0] > 200.0 && magnitudes[2] > 200.0,
+        "{:?}",
+        magnitudes
+    );
+    assert!(
+        magnitudes[1] < 5.0 && magnitudes[3] < 5.0,
+        "{:?}",
+        magnitudes
+    );
+}
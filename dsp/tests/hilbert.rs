@@ -0,0 +1,115 @@
+use core::f32::consts::PI;
+use dsp::fft::Microfft;
+use dsp::hilbert::{analytic, envelope, frequency, phase, Fir};
+use dsp::window::Window;
+use dsp::Complex32;
+use heapless::consts::{U1024, U63};
+
+const LEN: usize = 1024;
+
+/// carrier on bin 128 with its amplitude swinging by half, 8 times over
+fn am() -> Vec<f32> {
+    (0..LEN)
+        .map(|n| {
+            let t = n as f32 / LEN as f32;
+            (1.0 + 0.5 * (2.0 * PI * 8.0 * t).cos()) * (2.0 * PI * 128.0 * t).cos()
+        })
+        .collect()
+}
+
+fn am_envelope(n: f32) -> f32 {
+    1.0 + 0.5 * (2.0 * PI * 8.0 * n / LEN as f32).cos()
+}
+
+/// linear chirp from 0.05 to 0.2 cycles per sample
+fn chirp() -> Vec<f32> {
+    (0..LEN)
+        .map(|n| {
+            let n = n as f32;
+            (2.0 * PI * (0.05 * n + 0.15 * n * n / (2.0 * LEN as f32))).cos()
+        })
+        .collect()
+}
+
+fn chirp_frequency(n: f32) -> f32 {
+    0.05 + 0.15 * n / LEN as f32
+}
+
+#[test]
+fn analytic_tone() {
+    let x = (0..LEN)
+        .map(|n| (2.0 * PI * 37.0 * n as f32 / LEN as f32 + 0.4).cos())
+        .collect::<Vec<f32>>();
+    let z = analytic::<Microfft, U1024>(&x);
+    for (n, z) in z.iter().enumerate() {
+        let expected = 2.0 * PI * 37.0 * n as f32 / LEN as f32 + 0.4;
+        let expected = Complex32::new(expected.cos(), expected.sin());
+        assert!((z - expected).norm_sqr() < 1e-8, "{} {}", n, z);
+    }
+    assert!((phase(z[0]) - 0.4).abs() < 1e-4);
+}
+
+#[test]
+fn am_envelope_fft() {
+    let x = am();
+    let z = analytic::<Microfft, U1024>(&x);
+    for (n, (z, x)) in z.iter().zip(x.iter()).enumerate() {
+        assert!((z.re - x).abs() < 1e-4);
+        assert!((envelope(*z) - am_envelope(n as f32)).abs() < 1e-4, "{}", n);
+    }
+}
+
+#[test]
+fn am_envelope_fir() {
+    let x = am();
+    let mut fir = Fir::<U63>::new(Window::Hamming);
+    assert_eq!(fir.delay(), 31);
+    for (n, x) in x.iter().enumerate() {
+        let z = fir.process(*x);
+        if n < 62 {
+            continue;
+        }
+        // the real part is the input, delayed
+        let delayed = n - fir.delay();
+        assert_eq!(z.re, am()[delayed]);
+        let error = envelope(z) - am_envelope(delayed as f32);
+        assert!(error.abs() < 0.01, "{} {}", n, error);
+    }
+}
+
+#[test]
+fn chirp_frequency_fft() {
+    let x = chirp();
+    let z = analytic::<Microfft, U1024>(&x);
+    // the chirp doesn't wrap round the block, so away from the ends
+    for n in 64..LEN - 64 {
+        let f = frequency(z[n - 1], z[n], 1.0);
+        assert!(
+            (f - chirp_frequency(n as f32 - 0.5)).abs() < 2e-3,
+            "{} {}",
+            n,
+            f
+        );
+        assert!((envelope(z[n]) - 1.0).abs() < 0.02);
+    }
+}
+
+#[test]
+fn chirp_frequency_fir() {
+    let x = chirp();
+    let mut fir = Fir::<U63>::new(Window::Hamming);
+    let mut previous = Complex32::default();
+    for (n, x) in x.iter().enumerate() {
+        let z = fir.process(*x);
+        if n > 62 {
+            let f = frequency(previous, z, 1000.0);
+            let expected = 1000.0 * chirp_frequency((n - fir.delay()) as f32 - 0.5);
+            assert!((f - expected).abs() < 2.0, "{} {} {}", n, f, expected);
+        }
+        previous = z;
+    }
+
+    // starts again from silence, nothing of the chirp is left
+    fir.reset();
+    assert_eq!(fir.process(0.0), Complex32::default());
+}
@@ -0,0 +1,312 @@
+//! Designs checked against reference values. The butter fixtures are
+//! scipy.signal output, the cheby1, cheby2 and ellip ones come from a python
+//! port of the same design steps using mpmath for the elliptic functions.
+
+mod common;
+
+use common::assert_relative;
+use dsp::iir::{butter, cheby1, cheby2, ellip, Band, DesignError};
+use dsp::zpk::Zpk;
+use dsp::Complex64;
+
+#[track_caller]
+fn assert_reference(actual: &[f64], expected: &[f64]) {
+    assert_relative(actual, expected, 1e-9, 1e-8);
+}
+
+fn assert_tf(zpk: Zpk, b: &[f64], a: &[f64]) {
+    let (actual_b, actual_a) = zpk.to_tf();
+    assert_reference(&actual_b, b);
+    assert_reference(&actual_a, a);
+}
+
+/// |H(e^jw)| in dB from the zeros and poles
+fn gain_db(zpk: &Zpk, wn: f64) -> f64 {
+    let w = core::f64::consts::PI * wn;
+    let z = Complex64::new(w.cos(), w.sin());
+    let h = zpk
+        .z
+        .iter()
+        .fold(Complex64::new(zpk.k, 0.0), |h, r| h * (z - r))
+        / zpk
+            .p
+            .iter()
+            .fold(Complex64::new(1.0, 0.0), |h, r| h * (z - r));
+    10.0 * h.norm_sqr().log10()
+}
+
+#[test]
+fn butter_lowpass() {
+    assert_tf(
+        butter(2, Band::LowPass(0.1)).unwrap(),
+        &[0.02008336556421, 0.04016673112842, 0.02008336556421],
+        &[1.0, -1.561018075801, 0.6413515380576],
+    );
+    assert_tf(
+        butter(4, Band::LowPass(0.2)).unwrap(),
+        &[
+            4.824343357716e-03,
+            1.929737343086e-02,
+            2.894606014630e-02,
+            1.929737343086e-02,
+            4.824343357716e-03,
+        ],
+        &[
+            1.0,
+            -2.369513007182,
+            2.313988414416,
+            -1.054665405879,
+            1.873794923682e-01,
+        ],
+    );
+    assert_tf(
+        butter(3, Band::LowPass(0.5)).unwrap(),
+        &[1.0 / 6.0, 0.5, 0.5, 1.0 / 6.0],
+        &[1.0, 0.0, 1.0 / 3.0, 0.0],
+    );
+}
+
+#[test]
+fn butter_highpass_bandpass_bandstop() {
+    assert_tf(
+        butter(3, Band::HighPass(0.3)).unwrap(),
+        &[
+            3.744526925902e-01,
+            -1.123358077770,
+            1.123358077770,
+            -3.744526925902e-01,
+        ],
+        &[
+            1.0,
+            -1.161917483672,
+            6.959427557897e-01,
+            -1.377613012599e-01,
+        ],
+    );
+
+    let a = [
+        1.0,
+        -1.942468776548,
+        2.119202397144,
+        -1.216651635516,
+        4.128015980962e-01,
+    ];
+    assert_tf(
+        butter(2, Band::BandPass(0.2, 0.4)).unwrap(),
+        &[
+            6.745527388907e-02,
+            0.0,
+            -1.349105477781e-01,
+            0.0,
+            6.745527388907e-02,
+        ],
+        &a,
+    );
+    assert_tf(
+        butter(2, Band::BandStop(0.2, 0.4)).unwrap(),
+        &[
+            6.389455251590e-01,
+            -1.579560206032,
+            2.254112944922,
+            -1.579560206032,
+            6.389455251590e-01,
+        ],
+        &a,
+    );
+}
+
+#[test]
+fn chebyshev() {
+    assert_tf(
+        cheby1(4, 1.0, Band::LowPass(0.2)).unwrap(),
+        &[
+            1.835550372011e-03,
+            7.342201488043e-03,
+            1.101330223206e-02,
+            7.342201488043e-03,
+            1.835550372011e-03,
+        ],
+        &[
+            1.0,
+            -3.054339676407,
+            3.828999227491,
+            -2.292451729406,
+            5.507445205809e-01,
+        ],
+    );
+    assert_tf(
+        cheby1(3, 0.5, Band::HighPass(0.4)).unwrap(),
+        &[
+            2.488725662628e-01,
+            -7.466176987884e-01,
+            7.466176987884e-01,
+            -2.488725662628e-01,
+        ],
+        &[
+            1.0,
+            -5.043332968679e-01,
+            5.289392467404e-01,
+            4.229201350592e-02,
+        ],
+    );
+    assert_tf(
+        cheby2(4, 40.0, Band::LowPass(0.3)).unwrap(),
+        &[
+            1.826742402014e-02,
+            -9.311100530913e-03,
+            2.566926612221e-02,
+            -9.311100530913e-03,
+            1.826742402014e-02,
+        ],
+        &[
+            1.0,
+            -2.656625709027,
+            2.807607396196,
+            -1.362899095639,
+            2.554993215701e-01,
+        ],
+    );
+    assert_tf(
+        cheby2(5, 30.0, Band::BandPass(0.2, 0.5)).unwrap(),
+        &[
+            4.831558377927e-02,
+            -1.327349096999e-01,
+            1.755950176785e-01,
+            -1.747394441445e-01,
+            1.319023774227e-01,
+            0.0,
+            -1.319023774227e-01,
+            1.747394441445e-01,
+            -1.755950176785e-01,
+            1.327349096999e-01,
+            -4.831558377927e-02,
+        ],
+        &[
+            1.0,
+            -3.790520970896,
+            8.292999700789,
+            -1.240236130049e+01,
+            1.405988960956e+01,
+            -1.223272067676e+01,
+            8.338220943393,
+            -4.353371575369,
+            1.709611692672,
+            -4.485894220547e-01,
+            6.837935795225e-02,
+        ],
+    );
+}
+
+#[test]
+fn elliptic() {
+    assert_tf(
+        ellip(4, 1.0, 40.0, Band::LowPass(0.25)).unwrap(),
+        &[
+            2.635923804932e-02,
+            -1.217528300403e-03,
+            3.806096474065e-02,
+            -1.217528300403e-03,
+            2.635923804932e-02,
+        ],
+        &[
+            1.0,
+            -2.692292418154,
+            3.230101021381,
+            -1.918870439124,
+            4.801858653464e-01,
+        ],
+    );
+    assert_tf(
+        ellip(5, 0.5, 60.0, Band::HighPass(0.3)).unwrap(),
+        &[
+            1.804703899696e-01,
+            -8.291663494992e-01,
+            1.591451093569,
+            -1.591451093569,
+            8.291663494992e-01,
+            -1.804703899696e-01,
+        ],
+        &[
+            1.0,
+            -1.512991783838,
+            1.710305637480,
+            -7.380707044323e-01,
+            2.876023244946e-01,
+            4.679478416905e-02,
+        ],
+    );
+    assert_tf(
+        ellip(3, 1.0, 50.0, Band::BandStop(0.3, 0.6)).unwrap(),
+        &[
+            3.377239093031e-01,
+            -3.519312785367e-01,
+            1.114233638388,
+            -7.105783981996e-01,
+            1.114233638388,
+            -3.519312785367e-01,
+            3.377239093031e-01,
+        ],
+        &[
+            1.0,
+            -6.994442165183e-01,
+            1.157786692659,
+            -5.970373808018e-01,
+            7.473318027655e-01,
+            -1.179593579527e-01,
+            -1.203400041933e-03,
+        ],
+    );
+}
+
+#[test]
+fn band_edges() {
+    // -3dB, -rp and -rs right at the edge
+    let zpk = butter(6, Band::LowPass(0.3)).unwrap();
+    assert!((gain_db(&zpk, 0.3) + 3.0103).abs() < 1e-3);
+
+    let zpk = cheby1(5, 2.0, Band::LowPass(0.3)).unwrap();
+    assert!((gain_db(&zpk, 0.3) + 2.0).abs() < 1e-9);
+
+    let zpk = cheby2(5, 50.0, Band::HighPass(0.3)).unwrap();
+    assert!((gain_db(&zpk, 0.3) + 50.0).abs() < 1e-9);
+
+    let zpk = ellip(6, 0.5, 70.0, Band::LowPass(0.3)).unwrap();
+    assert!((gain_db(&zpk, 0.3) + 0.5).abs() < 1e-9);
+    // equiripple stopband never comes back above -rs
+    assert!((0..100)
+        .map(|i| 0.5 + 0.5 * i as f64 / 100.0)
+        .all(|wn| gain_db(&zpk, wn) < -70.0 + 1e-6));
+}
+
+#[test]
+fn sections_match_scipy() {
+    // signal.butter(4, 0.2, output='sos')
+    let sections = dsp::sos::from_zpk(&butter(4, Band::LowPass(0.2)).unwrap());
+    assert_eq!(sections.len(), 2);
+    assert_reference(
+        &sections[0].b,
+        &[0.004824343357716, 0.009648686715432, 0.004824343357716],
+    );
+    assert_reference(&sections[0].a, &[1.0, -1.0485995763626, 0.29614035756167]);
+    assert_reference(&sections[1].b, &[1.0, 2.0, 1.0]);
+    assert_reference(&sections[1].a, &[1.0, -1.3209134308194, 0.63273879288528]);
+}
+
+#[test]
+fn invalid_designs() {
+    assert_eq!(butter(0, Band::LowPass(0.2)), Err(DesignError::Order));
+    assert_eq!(butter(17, Band::LowPass(0.2)), Err(DesignError::Order));
+    assert_eq!(butter(2, Band::LowPass(1.0)), Err(DesignError::Frequency));
+    assert_eq!(
+        butter(2, Band::BandPass(0.4, 0.2)),
+        Err(DesignError::Frequency)
+    );
+    assert_eq!(
+        cheby1(2, -1.0, Band::LowPass(0.2)),
+        Err(DesignError::Ripple)
+    );
+    assert_eq!(
+        ellip(2, 40.0, 1.0, Band::LowPass(0.2)),
+        Err(DesignError::Ripple)
+    );
+}
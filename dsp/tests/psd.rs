@@ -0,0 +1,135 @@
+mod common;
+
+use common::noise;
+use core::f32::consts::PI;
+use dsp::fft::Microfft;
+use dsp::psd::{detrend, Detrend, Welch};
+use dsp::window::{enbw, Window};
+use heapless::consts::{U256, U64};
+
+const FS: f32 = 1000.0;
+
+fn mean(x: &[f32]) -> f32 {
+    x.iter().sum::<f32>() / x.len() as f32
+}
+
+/// Spread of the bins relative to their mean
+fn spread(x: &[f32]) -> f32 {
+    let m = mean(x);
+    (x.iter().map(|x| (x - m) * (x - m)).sum::<f32>() / x.len() as f32).sqrt() / m
+}
+
+#[test]
+fn white_noise_level() {
+    // one-sided white noise of variance s^2 is 2 s^2 / fs
+    let x = noise(16384);
+    let expected = 2.0 / 3.0 / FS;
+
+    let mut psd = [0.0; 129];
+    for window in [Window::Rectangular, Window::Hann, Window::BlackmanHarris].iter() {
+        let welch = Welch::<Microfft, U256>::new(*window, 128, Detrend::Mean, FS);
+        assert_eq!(welch.estimate(&x, &mut psd), 127);
+        let level = mean(&psd[1..128]);
+        assert!(
+            (level / expected - 1.0).abs() < 0.03,
+            "{:?} {} {}",
+            window,
+            level,
+            expected
+        );
+    }
+}
+
+#[test]
+fn averaging_reduces_spread() {
+    let x = noise(16384);
+    let mut psd = [0.0; 129];
+
+    // a single periodogram is off by about 100% in every bin
+    let periodogram = Welch::<Microfft, U256>::periodogram(Window::Hann, FS);
+    periodogram.estimate(&x[..256], &mut psd);
+    let single = spread(&psd[1..128]);
+    assert!(single > 0.7 && single < 1.3, "{}", single);
+
+    // Bartlett's 64 segments bring that down by 8
+    let bartlett = Welch::<Microfft, U256>::bartlett(FS);
+    assert_eq!(bartlett.estimate(&x, &mut psd), 64);
+    let averaged = spread(&psd[1..128]);
+    assert!((averaged * 8.0 - 1.0).abs() < 0.3, "{}", averaged);
+
+    // and Welch's overlapping Hann segments a bit further
+    let welch = Welch::<Microfft, U256>::new(Window::Hann, 128, Detrend::None, FS);
+    welch.estimate(&x, &mut psd);
+    assert!(spread(&psd[1..128]) < averaged, "{}", spread(&psd[1..128]));
+}
+
+#[test]
+fn parseval() {
+    // the rectangular periodogram integrates to exactly the mean square
+    let x = noise(64);
+    let mut psd = [0.0; 33];
+    Welch::<Microfft, U64>::bartlett(FS).estimate(&x, &mut psd);
+    let integral = psd.iter().sum::<f32>() * FS / 64.0;
+    let mean_square = x.iter().map(|x| x * x).sum::<f32>() / 64.0;
+    assert!((integral - mean_square).abs() < 1e-5);
+}
+
+#[test]
+fn tone_power() {
+    // a tone of amplitude 2 has power 2, spread over the main lobe of the
+    // window by its ENBW
+    let x = (0..4096)
+        .map(|n| 2.0 * (2.0 * PI * 62.5 * n as f32 / FS).cos())
+        .collect::<std::vec::Vec<f32>>();
+    let welch = Welch::<Microfft, U256>::new(Window::Hann, 128, Detrend::Mean, FS);
+    let mut psd = [0.0; 129];
+    welch.estimate(&x, &mut psd);
+
+    // 62.5 Hz is bin 16
+    assert_eq!(welch.frequencies().nth(16), Some(62.5));
+    let hann = Window::Hann
+        .periodic(256)
+        .collect::<heapless::Vec<f32, U256>>();
+    let peak = psd[16] * welch.resolution() * enbw(&hann);
+    assert!((peak - 2.0).abs() < 1e-3, "{}", peak);
+    let total = psd.iter().sum::<f32>() * welch.resolution();
+    assert!((total - 2.0).abs() < 1e-3, "{}", total);
+}
+
+#[test]
+fn detrending() {
+    let mut line = (0..64)
+        .map(|n| 3.0 + 0.5 * n as f32)
+        .collect::<std::vec::Vec<f32>>();
+    let mut offset = line.clone();
+
+    detrend(&mut offset, Detrend::Mean);
+    assert!(mean(&offset).abs() < 1e-5);
+    detrend(&mut line, Detrend::Linear);
+    assert!(line.iter().all(|x| x.abs() < 1e-4));
+
+    // drift swamps the low bins unless it's taken out
+    let x = noise(4096)
+        .iter()
+        .enumerate()
+        .map(|(n, x)| x + 5.0 + n as f32 * 0.01)
+        .collect::<std::vec::Vec<f32>>();
+    let mut drifting = [0.0; 129];
+    let mut clean = [0.0; 129];
+    Welch::<Microfft, U256>::new(Window::Hann, 128, Detrend::Mean, FS).estimate(&x, &mut drifting);
+    Welch::<Microfft, U256>::new(Window::Hann, 128, Detrend::Linear, FS).estimate(&x, &mut clean);
+    let expected = 2.0 / 3.0 / FS;
+    assert!(drifting[1] > 10.0 * expected);
+    assert!(clean[1] < 3.0 * expected, "{}", clean[1] / expected);
+}
+
+#[test]
+fn segments() {
+    let welch = Welch::<Microfft, U64>::new(Window::Hann, 48, Detrend::None, FS);
+    assert_eq!(welch.step(), 16);
+    assert_eq!(welch.segments(63), 0);
+    assert_eq!(welch.segments(64), 1);
+    assert_eq!(welch.segments(79), 1);
+    assert_eq!(welch.segments(80), 2);
+    assert_eq!(welch.resolution(), FS / 64.0);
+}
@@ -0,0 +1,122 @@
+use dsp::iir::{butter, cheby1, ellip, Band};
+use dsp::pz::{analyze, Stability};
+use dsp::zpk::Zpk;
+use dsp::Complex64;
+
+/// every expected root has a match, both sorted the same way
+fn assert_roots(actual: &[Complex64], expected: &[Complex64], tolerance: f64) {
+    assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+    let sort = |r: &[Complex64]| {
+        let mut r = r.to_vec();
+        r.sort_by(|a, b| (a.re, a.im).partial_cmp(&(b.re, b.im)).unwrap());
+        r
+    };
+    for (a, e) in sort(actual).iter().zip(sort(expected).iter()) {
+        assert!((a - e).norm_sqr().sqrt() < tolerance, "{:?} != {:?}", a, e);
+    }
+}
+
+#[test]
+fn roots_of_difference_equations() {
+    // y[n] = x[n] + 2y[n-1], digital_system7 in 2_11
+    let zpk = Zpk::from_tf(&[1.0], &[1.0, -2.0]).unwrap();
+    assert_roots(&zpk.z, &[Complex64::new(0.0, 0.0)], 1e-12);
+    assert_roots(&zpk.p, &[Complex64::new(2.0, 0.0)], 1e-12);
+    assert_eq!(zpk.k, 1.0);
+
+    // pure delay, the leading zero isn't a root
+    let zpk = Zpk::from_tf(&[0.0, 0.0, 3.0], &[2.0]).unwrap();
+    assert!(zpk.z.is_empty());
+    assert_roots(&zpk.p, &[Complex64::new(0.0, 0.0); 2], 1e-12);
+    assert_eq!(zpk.k, 1.5);
+
+    // resonator at pi/3 with radius 0.9
+    let zpk = Zpk::from_tf(&[1.0, 0.0, -1.0], &[1.0, -0.9, 0.81]).unwrap();
+    let pole = Complex64::new(0.45, 0.9 * (3.0f64).sqrt() / 2.0);
+    assert_roots(&zpk.p, &[pole, pole.conj()], 1e-12);
+    assert_roots(
+        &zpk.z,
+        &[Complex64::new(1.0, 0.0), Complex64::new(-1.0, 0.0)],
+        1e-12,
+    );
+}
+
+#[test]
+fn too_long_to_factor() {
+    // a 64 tap moving average has more zeros than a Zpk holds
+    let fir = [1.0 / 64.0; 64];
+    assert_eq!(Zpk::from_tf(&fir, &[1.0]), None);
+}
+
+#[test]
+fn designs_round_trip() {
+    for zpk in [
+        butter(8, Band::LowPass(0.3)).unwrap(),
+        cheby1(6, 1.0, Band::BandPass(0.2, 0.4)).unwrap(),
+        ellip(5, 0.5, 60.0, Band::HighPass(0.3)).unwrap(),
+    ]
+    .iter()
+    {
+        let (b, a) = zpk.to_tf();
+        let factored = Zpk::from_tf(&b, &a).unwrap();
+        assert_roots(&factored.p, &zpk.p, 1e-8);
+        assert!((factored.k - zpk.k).abs() < 1e-12);
+        assert_eq!(analyze(&factored).stability, Stability::Stable);
+    }
+}
+
+#[test]
+fn repeated_roots() {
+    // 2_23, (1 + z^-1)^2 over a double real pole
+    let zpk = Zpk::from_tf(&[0.002044, 0.004088, 0.002044], &[1.0, -1.8, 0.81]).unwrap();
+    assert_roots(&zpk.z, &[Complex64::new(-1.0, 0.0); 2], 1e-6);
+    assert_roots(&zpk.p, &[Complex64::new(0.9, 0.0); 2], 1e-6);
+    assert!(zpk.p.iter().all(|p| p.im == 0.0));
+}
+
+#[test]
+fn verdicts() {
+    let analysis = analyze(&Zpk::from_tf(&[1.0], &[1.0, -2.0]).unwrap());
+    assert_eq!(analysis.stability, Stability::Unstable);
+    assert!((analysis.max_pole_radius - 2.0).abs() < 1e-12);
+
+    // 2_23 low pass and 2_24 high pass
+    let analysis = analyze(
+        &Zpk::from_tf(&[0.002044, 0.004088, 0.002044], &[1.0, -1.819168, 0.827343]).unwrap(),
+    );
+    assert_eq!(analysis.stability, Stability::Stable);
+    assert!(analysis.minimum_phase);
+    let analysis = analyze(
+        &Zpk::from_tf(
+            &[0.705514, -1.411028, 0.705514],
+            &[1.0, -1.359795, 0.462261],
+        )
+        .unwrap(),
+    );
+    assert_eq!(analysis.stability, Stability::Stable);
+
+    // accumulator and oscillator
+    let analysis = analyze(&Zpk::from_tf(&[1.0], &[1.0, -1.0]).unwrap());
+    assert_eq!(analysis.stability, Stability::Marginal);
+    let analysis = analyze(&Zpk::from_tf(&[1.0], &[1.0, -1.0, 1.0]).unwrap());
+    assert_eq!(analysis.stability, Stability::Marginal);
+    // double integrator ramps away
+    let analysis = analyze(&Zpk::from_tf(&[1.0], &[1.0, -2.0, 1.0]).unwrap());
+    assert_eq!(analysis.stability, Stability::Unstable);
+
+    // zero at 2 makes it mixed phase
+    let analysis = analyze(&Zpk::from_tf(&[1.0, -2.0], &[1.0, -0.5]).unwrap());
+    assert_eq!(analysis.stability, Stability::Stable);
+    assert!(!analysis.minimum_phase);
+}
+
+#[test]
+fn svg_marks_every_root() {
+    let zpk = butter(4, Band::LowPass(0.2)).unwrap();
+    let svg = dsp::pz::svg("butter", &zpk);
+    assert!(svg.starts_with("<svg"));
+    assert_eq!(svg.matches("<path").count(), zpk.p.len());
+    // the unit circle is a circle too
+    assert_eq!(svg.matches("<circle").count(), zpk.z.len() + 1);
+    assert!(svg.contains("Stable, minimum phase"));
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use common::noise;
+use core::f32::consts::PI;
+use dsp::fft::{Cfft, Microfft};
+use dsp::sdft::{Sdft, Window};
+use dsp::Complex32;
+use heapless::consts::{U4, U64};
+
+const BINS: [usize; 4] = [0, 3, 10, 63];
+
+/// Noise with a couple of tones on top, one on a bin and one between
+fn signal(len: usize) -> Vec<f32> {
+    noise(len)
+        .iter()
+        .enumerate()
+        .map(|(n, x)| {
+            let n = n as f32;
+            0.3 * x + (2.0 * PI * 3.0 * n / 64.0).cos() + 0.5 * (2.0 * PI * 9.6 * n / 64.0).sin()
+        })
+        .collect()
+}
+
+/// The bins of a windowed FFT of the 64 samples ending at `end`
+fn fft(x: &[f32], end: usize, window: Window) -> Vec<Complex32> {
+    let mut z = x[end - 64..end]
+        .iter()
+        .zip(dsp::window::Window::from(window).periodic(64))
+        .map(|(x, w)| Complex32::new(x * w, 0.0))
+        .collect::<heapless::Vec<Complex32, U64>>();
+    Microfft::cfft(&mut z);
+    BINS.iter().map(|k| z[*k]).collect()
+}
+
+/// Largest difference between the sliding bins and the FFT's
+fn error(sdft: &Sdft<U64, U4>, x: &[f32], end: usize, window: Window) -> f32 {
+    sdft.bins()
+        .iter()
+        .zip(fft(x, end, window))
+        .map(|(a, b)| (a - b).norm_sqr().sqrt())
+        .fold(0.0, f32::max)
+}
+
+#[test]
+fn matches_windowed_fft_every_hop() {
+    let x = signal(1000);
+    for window in [
+        Window::Rectangular,
+        Window::Hann,
+        Window::Hamming,
+        Window::Blackman,
+    ] {
+        let mut modulated = Sdft::<U64, U4>::new(&BINS, window);
+        let mut recursive = Sdft::<U64, U4>::recursive(&BINS, window, 1.0);
+        for (n, x_n) in x.iter().enumerate() {
+            modulated.process(*x_n);
+            recursive.process(*x_n);
+            let end = n + 1;
+            assert_eq!(modulated.is_full(), end >= 64);
+            // a hop of one sample, every output is checked
+            if end >= 64 {
+                let e = error(&modulated, &x, end, window);
+                assert!(e < 1e-3, "{:?} modulated at {}: {}", window, n, e);
+                let e = error(&recursive, &x, end, window);
+                assert!(e < 1e-3, "{:?} recursive at {}: {}", window, n, e);
+            }
+        }
+    }
+}
+
+#[test]
+fn drift_stays_bounded() {
+    let len = 1_000_000;
+    let x = signal(len);
+    let mut modulated = Sdft::<U64, U4>::new(&BINS, Window::Hann);
+    let mut undamped = Sdft::<U64, U4>::recursive(&BINS, Window::Hann, 1.0);
+    let mut damped = Sdft::<U64, U4>::recursive(&BINS, Window::Hann, 0.9999);
+    let mut early = 0.0;
+    let mut worst = 0.0f32;
+    for (n, x_n) in x.iter().enumerate() {
+        modulated.process(*x_n);
+        undamped.process(*x_n);
+        damped.process(*x_n);
+        if n + 1 == 1000 {
+            early = error(&damped, &x, n + 1, Window::Hann);
+        }
+        if (n + 1) & 0xffff == 0 {
+            worst = worst.max(error(&modulated, &x, n + 1, Window::Hann));
+        }
+    }
+    let drift = error(&undamped, &x, len, Window::Hann);
+    // the modulated one is as good after a million samples as after 64
+    assert!(worst < 1e-3, "{}", worst);
+    // the plain one has walked off
+    assert!(drift > 0.01, "{}", drift);
+    // damping stops the walk, what's left is the r^age weighting and that
+    // doesn't grow
+    let late = error(&damped, &x, len, Window::Hann);
+    assert!(late < 2.0 * early, "{} then {}", early, late);
+}
+
+#[test]
+fn frequencies_and_reset() {
+    let mut sdft = Sdft::<U64, U4>::frequencies(&[0.0, 46.0, 160.0, 985.0], 1000.0, Window::Hann);
+    assert_eq!(sdft.k().collect::<Vec<usize>>(), BINS);
+
+    let x = signal(100);
+    x.iter().for_each(|x| sdft.process(*x));
+    let bins = sdft.bins();
+    sdft.reset();
+    assert!(!sdft.is_full());
+    assert!(sdft.bins().iter().all(|z| z.norm_sqr() == 0.0));
+    x.iter().for_each(|x| sdft.process(*x));
+    assert_eq!(sdft.bins(), bins);
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use common::assert_relative;
+use dsp::freqz::freqz;
+use dsp::iir::{butter, cheby2, ellip, Band};
+use dsp::pz::{analyze, Stability};
+use dsp::sos::{self, Cascade, Norm};
+use dsp::zpk::Zpk;
+use dsp::Complex64;
+
+fn sorted(roots: &[Complex64]) -> Vec<Complex64> {
+    let mut roots = roots.to_vec();
+    roots.sort_by(|a, b| (a.re, a.im).partial_cmp(&(b.re, b.im)).unwrap());
+    roots
+}
+
+fn designs() -> Vec<Zpk> {
+    vec![
+        butter(5, Band::LowPass(0.3)).unwrap(),
+        cheby2(4, 40.0, Band::BandPass(0.2, 0.5)).unwrap(),
+        ellip(7, 0.5, 60.0, Band::HighPass(0.4)).unwrap(),
+    ]
+}
+
+#[test]
+fn zpk_round_trip() {
+    for zpk in designs() {
+        let back = sos::to_zpk(&sos::from_zpk(&zpk)).unwrap();
+        assert!((back.k - zpk.k).abs() <= 1e-12 * zpk.k.abs());
+        for (roots, expected) in [(&back.z, &zpk.z), (&back.p, &zpk.p)].iter() {
+            assert_eq!(roots.len(), expected.len());
+            for (a, e) in sorted(roots).iter().zip(sorted(expected).iter()) {
+                assert!((a - e).norm_sqr().sqrt() < 1e-9, "{:?} != {:?}", a, e);
+            }
+        }
+    }
+}
+
+#[test]
+fn tf_round_trip() {
+    for zpk in designs() {
+        let (b, a) = zpk.to_tf();
+        let sections = sos::from_tf(&b, &a).unwrap();
+        assert_eq!(sections.len(), zpk.order().div_ceil(2));
+        let (b2, a2) = sos::to_tf(&sections);
+        assert_relative(&b2, &b, 1e-9, 1e-9);
+        assert_relative(&a2, &a, 1e-9, 1e-9);
+    }
+}
+
+#[test]
+fn scaling_keeps_partial_gains_at_one() {
+    for by in [Norm::L2, Norm::LInf].iter() {
+        let mut sections = sos::from_zpk(&ellip(6, 1.0, 50.0, Band::LowPass(0.2)).unwrap());
+        let original = sections.clone();
+        let gain = sos::scale(&mut sections, *by);
+
+        for i in 1..=sections.len() {
+            let gains = (0..1024).map(|k| {
+                let w = core::f64::consts::PI * (k as f64 + 0.5) / 1024.0;
+                sections[..i]
+                    .iter()
+                    .map(|s| freqz(&s.b, &s.a, w).norm_sqr().sqrt())
+                    .product::<f64>()
+            });
+            let norm = match by {
+                Norm::L2 => (gains.map(|g| g * g).sum::<f64>() / 1024.0).sqrt(),
+                Norm::LInf => gains.fold(0.0, f64::max),
+            };
+            assert!((norm - 1.0).abs() < 1e-9, "{:?} {} {}", by, i, norm);
+        }
+
+        // and the output gain puts the response back
+        for k in 0..20 {
+            let w = core::f64::consts::PI * k as f64 / 20.0;
+            let h = |s: &[sos::Section]| {
+                s.iter()
+                    .fold(Complex64::new(1.0, 0.0), |h, s| h * freqz(&s.b, &s.a, w))
+            };
+            assert!((h(&sections) * gain - h(&original)).norm_sqr().sqrt() < 1e-9);
+        }
+    }
+}
+
+#[test]
+fn tenth_order_needs_sections() {
+    let zpk = butter(10, Band::LowPass(0.05)).unwrap();
+    let (b, a) = zpk.to_tf();
+
+    // Rounding the expanded polynomial to f32 pushes poles outside the unit
+    // circle
+    let b32 = b.iter().map(|b| *b as f32).collect::<Vec<_>>();
+    let a32 = a.iter().map(|a| *a as f32).collect::<Vec<_>>();
+    let direct = Zpk::from_tf(
+        &b32.iter().map(|b| *b as f64).collect::<Vec<_>>(),
+        &a32.iter().map(|a| *a as f64).collect::<Vec<_>>(),
+    )
+    .unwrap();
+    assert_eq!(analyze(&direct).stability, Stability::Unstable);
+
+    // while every section rounded to f32 is still fine
+    let sections = sos::from_zpk(&zpk);
+    for s in sections.iter() {
+        let a = s.a.iter().map(|a| *a as f32 as f64).collect::<Vec<_>>();
+        assert_eq!(
+            analyze(&Zpk::from_tf(&[1.0], &a).unwrap()).stability,
+            Stability::Stable
+        );
+    }
+
+    // and running both on a step shows it
+    let mut x = vec![0f32; a32.len()];
+    let mut y = vec![0f32; a32.len()];
+    let mut cascade = Cascade::new(&sections);
+    let mut direct_peak = 0f32;
+    let mut cascade_last = 0f32;
+    for _ in 0..2000 {
+        x.rotate_right(1);
+        y.rotate_right(1);
+        x[0] = 1.0;
+        y[0] = 0.0;
+        let out = (0..b32.len()).map(|i| b32[i] * x[i]).sum::<f32>()
+            - (1..a32.len()).map(|i| a32[i] * y[i]).sum::<f32>();
+        y[0] = out;
+        direct_peak = direct_peak.max(out.abs());
+        cascade_last = cascade.process(1.0);
+    }
+    // NaN once it overflows, which fails any comparison
+    assert!(direct_peak.is_nan() || direct_peak > 1e3, "{}", direct_peak);
+    assert!((cascade_last - 1.0).abs() < 1e-3, "{}", cascade_last);
+}
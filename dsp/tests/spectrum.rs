@@ -0,0 +1,145 @@
+use core::f32::consts::PI;
+use dsp::fft::{Cfft, Microfft, Rfft};
+use dsp::spectrum::{
+    db, fftshift, frequencies, ifftshift, phase, power_db, unwrap, Sided, Spectrum,
+};
+use dsp::window::{coherent_gain, Window};
+use dsp::Complex32;
+use heapless::consts::U64;
+use heapless::Vec;
+
+/// 0.5 DC, 2.0 on bin 10, 1.0 on bin 20 and 0.25 at Nyquist
+fn signal() -> Vec<f32, U64> {
+    (0..64)
+        .map(|n| {
+            let n = n as f32;
+            0.5 + 2.0 * (2.0 * PI * 10.0 * n / 64.0).cos()
+                + (2.0 * PI * 20.0 * n / 64.0 + 1.0).sin()
+                + 0.25 * (PI * n).cos()
+        })
+        .collect()
+}
+
+#[test]
+fn amplitude_corrected() {
+    let hann = Window::Hann.periodic(64).collect::<Vec<f32, U64>>();
+    let gain = coherent_gain(&hann);
+    let x = signal();
+
+    let mut windowed = x
+        .iter()
+        .zip(hann.iter())
+        .map(|(x, w)| Complex32::new(x * w, 0.0))
+        .collect::<Vec<Complex32, U64>>();
+    Microfft::cfft(&mut windowed);
+
+    let mut one = [0.0f32; 33];
+    Microfft::amplitude(&windowed, Sided::One, gain, &mut one);
+    for (k, expected) in [(0, 0.5), (10, 2.0), (20, 1.0), (32, 0.25)].iter() {
+        assert!((one[*k] - expected).abs() < 1e-5, "{} {}", k, one[*k]);
+    }
+
+    // two-sided splits the tones between the positive and negative bins
+    let mut two = [0.0f32; 64];
+    Microfft::amplitude(&windowed, Sided::Two, gain, &mut two);
+    for (k, expected) in [
+        (0, 0.5),
+        (10, 1.0),
+        (54, 1.0),
+        (20, 0.5),
+        (44, 0.5),
+        (32, 0.25),
+    ]
+    .iter()
+    {
+        assert!((two[*k] - expected).abs() < 1e-5, "{} {}", k, two[*k]);
+    }
+
+    // and the real FFT packing gives the same one-sided spectrum
+    let mut real = x
+        .iter()
+        .zip(hann.iter())
+        .map(|(x, w)| x * w)
+        .collect::<Vec<f32, U64>>();
+    let mut packed = [0.0f32; 33];
+    Microfft::amplitude_packed(Microfft::rfft(&mut real), gain, &mut packed);
+    for (a, b) in packed.iter().zip(one.iter()) {
+        assert!((a - b).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn decibels() {
+    // a full scale 12 bit tone, and one 6 dB down
+    let mut x = [2048.0, 1024.0, 0.0];
+    db(&mut x, 2048.0);
+    assert_eq!(x[0], 0.0);
+    assert!((x[1] + 6.0206).abs() < 1e-3);
+    assert_eq!(x[2], f32::NEG_INFINITY);
+
+    let bins = [Complex32::new(3.0, 4.0), Complex32::new(0.3, 0.4)];
+    let mut p = [0.0; 2];
+    Microfft::power(&bins, &mut p);
+    assert_eq!(p[0], 25.0);
+    power_db(&mut p, 25.0);
+    assert!(p[0].abs() < 1e-6 && (p[1] + 20.0).abs() < 1e-4);
+}
+
+#[test]
+fn shifts_and_axis() {
+    // numpy's fftshift and fftfreq
+    let mut even = [0, 1, 2, 3, 4, 5];
+    fftshift(&mut even);
+    assert_eq!(even, [3, 4, 5, 0, 1, 2]);
+    ifftshift(&mut even);
+    assert_eq!(even, [0, 1, 2, 3, 4, 5]);
+
+    let mut odd = [0, 1, 2, 3, 4];
+    fftshift(&mut odd);
+    assert_eq!(odd, [3, 4, 0, 1, 2]);
+    ifftshift(&mut odd);
+    assert_eq!(odd, [0, 1, 2, 3, 4]);
+
+    let hz = |n, sided| frequencies(n, 8.0, sided).collect::<std::vec::Vec<_>>();
+    assert_eq!(
+        hz(8, Sided::Two),
+        [0.0, 1.0, 2.0, 3.0, -4.0, -3.0, -2.0, -1.0]
+    );
+    assert_eq!(hz(8, Sided::One), [0.0, 1.0, 2.0, 3.0, 4.0]);
+    let mut shifted = hz(5, Sided::Two);
+    fftshift(&mut shifted);
+    assert_eq!(shifted, [-3.2, -1.6, 0.0, 1.6, 3.2]);
+}
+
+#[test]
+fn unwrapped_phase() {
+    // an impulse 3 samples in has a phase of -2 pi 3 k / 64 that wraps round
+    // twice over the one-sided spectrum
+    let mut x = Vec::<Complex32, U64>::new();
+    x.resize(64, Complex32::default()).unwrap();
+    x[3] = Complex32::new(1.0, 0.0);
+    Microfft::cfft(&mut x);
+
+    let mut p = [0.0; 33];
+    phase(&x[..33], &mut p);
+    assert!(p.iter().all(|p| p.abs() <= PI));
+    unwrap(&mut p);
+    for (k, p) in p.iter().enumerate() {
+        let expected = -2.0 * PI * 3.0 * k as f32 / 64.0;
+        assert!((p - expected).abs() < 1e-4, "{} {} {}", k, p, expected);
+    }
+
+    // the f64 responses from freqz unwrap the same
+    let mut wrapped = (0..200)
+        .map(|n| ((0.7 * n as f32 + PI) % (2.0 * PI)) - PI)
+        .collect::<std::vec::Vec<f32>>();
+    let mut wrapped64 = wrapped
+        .iter()
+        .map(|p| *p as f64)
+        .collect::<std::vec::Vec<_>>();
+    unwrap(&mut wrapped);
+    unwrap(&mut wrapped64);
+    for (a, b) in wrapped.iter().zip(wrapped64) {
+        assert!((*a as f64 - b).abs() < 1e-3);
+    }
+}
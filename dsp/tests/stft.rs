@@ -0,0 +1,141 @@
+mod common;
+
+use common::assert_close;
+use core::f32::consts::PI;
+use dsp::fft::Microfft;
+use dsp::stft::{cola, Stft, TooManyFrames};
+use dsp::window::Window;
+use dsp::Complex32;
+use heapless::consts::{U128, U16, U256, U32, U48, U512, U8};
+
+/// The chirp from 4_10, 0 to pi radians per sample over 1024 samples
+fn chirp() -> std::vec::Vec<f32> {
+    (0..1024)
+        .map(|n| {
+            let n = n as f32;
+            (PI * n * n / (2.0 * 1023.0)).cos()
+        })
+        .collect()
+}
+
+#[test]
+fn chirp_perfect_reconstruction() {
+    let x = chirp();
+
+    // 16 point Hann frames every 8 samples cover all 1024, except the first
+    // sample which is only under the zero at the start of the first window
+    let mut stft = Stft::<Microfft, U16, U128>::new(Window::Hann, 8);
+    stft.forward(&x).unwrap();
+    assert_eq!(stft.len(), 127);
+    assert_eq!(stft.output_len(), 1024);
+    let mut y = vec![1.0; 1024];
+    stft.inverse(&mut y);
+    assert_eq!(y[0], 0.0);
+    assert_close(&y[1..], &x[1..], 1e-5);
+
+    // padding with N - hop zeros at both ends gets that back too
+    let padded = [0.0; 8]
+        .iter()
+        .chain(x.iter())
+        .chain([0.0; 8].iter())
+        .cloned()
+        .collect::<std::vec::Vec<f32>>();
+    let mut stft = Stft::<Microfft, U16, U256>::new(Window::Hann, 8);
+    stft.forward(&padded).unwrap();
+    let mut y = vec![0.0; stft.output_len()];
+    stft.inverse(&mut y);
+    assert_close(&y[8..1032], &x, 1e-5);
+}
+
+#[test]
+fn any_window_and_hop() {
+    let x = chirp();
+    let mut y = vec![0.0; 1024];
+    for (window, hop) in [
+        (Window::Hamming, 4),
+        (Window::BlackmanHarris, 3),
+        (Window::Rectangular, 16),
+        (Window::Kaiser(8.0), 5),
+    ]
+    .iter()
+    {
+        let mut stft = Stft::<Microfft, U16, U512>::new(*window, *hop);
+        stft.forward(&x).unwrap();
+        let len = stft.output_len();
+        stft.inverse(&mut y);
+        // BlackmanHarris and Kaiser don't go quite to zero at the start
+        assert_close(&y[1..len], &x[1..len], 1e-4);
+    }
+}
+
+#[test]
+fn too_many_frames() {
+    let mut stft = Stft::<Microfft, U16, U8>::new(Window::Hann, 8);
+    assert_eq!(stft.frames_in(72), 8);
+    assert_eq!(stft.forward(&[0.0; 72]), Ok(()));
+    assert_eq!(stft.forward(&[0.0; 80]), Err(TooManyFrames { needed: 9 }));
+}
+
+#[test]
+fn masking() {
+    // tones on bins 2 and 8 of the 32 point frames. A Hann windowed tone on a
+    // bin only reaches its two neighbours, so zeroing 7 to 9 takes all of the
+    // second one out and none of the first.
+    let wanted = (0..512)
+        .map(|n| (2.0 * PI * 2.0 * n as f32 / 32.0).sin())
+        .collect::<std::vec::Vec<f32>>();
+    let x = wanted
+        .iter()
+        .enumerate()
+        .map(|(n, x)| x + 0.5 * (2.0 * PI * 8.0 * n as f32 / 32.0 + 1.0).cos())
+        .collect::<std::vec::Vec<f32>>();
+
+    // Hann squared overlap adds to a constant at a quarter of the length
+    let hann = Window::Hann
+        .periodic(32)
+        .map(|w| w * w)
+        .collect::<std::vec::Vec<f32>>();
+    assert!(cola(&hann, 8).is_some());
+
+    let mut stft = Stft::<Microfft, U32, U128>::new(Window::Hann, 8);
+    stft.forward(&x).unwrap();
+    for frame in stft.frames_mut() {
+        for k in 7..=9 {
+            frame[k] = Complex32::default();
+            frame[32 - k] = Complex32::default();
+        }
+    }
+    let mut y = vec![0.0; 512];
+    stft.inverse(&mut y);
+    // the last few samples are only under the tail of one window, which
+    // scales the rounding up
+    assert_close(&y[1..], &wanted[1..], 1e-3);
+}
+
+#[test]
+fn cola_windows() {
+    let window = |w: Window, n| w.periodic(n).collect::<std::vec::Vec<f32>>();
+    let close = |sum: Option<f32>, expected: f32| (sum.unwrap() - expected).abs() < 1e-4;
+
+    // Hann at any hop that divides N by 2 or more sums to N / 2 hop
+    assert!(close(cola(&window(Window::Hann, 16), 8), 1.0));
+    assert!(close(cola(&window(Window::Hann, 16), 4), 2.0));
+    assert_eq!(cola(&window(Window::Hann, 16), 16), None);
+    assert_eq!(cola(&window(Window::Hann, 16), 3), None);
+    assert!(close(cola(&window(Window::Hamming, 16), 8), 1.08));
+    assert!(close(cola(&window(Window::Rectangular, 16), 16), 1.0));
+
+    // Blackman needs a third
+    assert_eq!(cola(&window(Window::Blackman, 48), 24), None);
+    assert!(close(cola(&window(Window::Blackman, 48), 16), 1.26));
+
+    // the square root of Hann is the usual WOLA window, its square is Hann
+    let root = Window::Hann
+        .periodic(32)
+        .map(|w| w.sqrt())
+        .collect::<heapless::Vec<f32, U32>>();
+    let squares = root.iter().map(|w| w * w).collect::<std::vec::Vec<f32>>();
+    assert!(close(cola(&squares, 16), 1.0));
+    let stft = Stft::<Microfft, U32, U48>::with_window(root.iter().cloned(), 16);
+    assert_eq!(stft.window(), &root[..]);
+}
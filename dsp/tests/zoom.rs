@@ -0,0 +1,106 @@
+use core::f32::consts::PI;
+use dsp::fft::{Microfft, Rfft};
+use dsp::spectrum::Spectrum;
+use dsp::window::Window;
+use dsp::zoom::Zoom;
+use heapless::consts::{U129, U256, U512, U65};
+
+const FS: f32 = 1000.0;
+
+fn tones(tones: &[(f32, f32)], len: usize) -> Vec<f32> {
+    (0..len)
+        .map(|n| {
+            tones
+                .iter()
+                .map(|(a, f)| a * (2.0 * PI * f * n as f32 / FS).cos())
+                .sum()
+        })
+        .collect()
+}
+
+/// Indices of the local maxima above `floor`
+fn peaks(x: &[f32], floor: f32) -> Vec<usize> {
+    (1..x.len() - 1)
+        .filter(|&k| x[k] > floor && x[k] > x[k - 1] && x[k] >= x[k + 1])
+        .collect()
+}
+
+fn magnitudes(bins: &[dsp::Complex32]) -> Vec<f32> {
+    bins.iter().map(|z| z.norm_sqr().sqrt()).collect()
+}
+
+#[test]
+fn frequency_axis() {
+    let zoom = Zoom::<Microfft, U256, U65>::new(120.0, FS, 8, Window::Hann);
+    let f = zoom.frequencies().collect::<Vec<f32>>();
+    assert_eq!(zoom.resolution(), FS / 2048.0);
+    assert_eq!(f.len(), 256);
+    assert_eq!(f[0], 120.0 - FS / 16.0);
+    assert_eq!(f[128], 120.0);
+    assert!((f[255] - f[254] - zoom.resolution()).abs() < 1e-4);
+    assert_eq!(zoom.samples_needed(), 255 * 8 + 65);
+}
+
+#[test]
+fn tone_on_a_bin() {
+    let mut zoom = Zoom::<Microfft, U256, U65>::new(120.0, FS, 8, Window::Hann);
+    let f = 120.0 + 7.0 * zoom.resolution();
+    let x = tones(&[(0.7, f)], 3000);
+    assert!(zoom.extend(&x));
+
+    let spectrum = magnitudes(&zoom.spectrum());
+    let (k, peak) =
+        spectrum.iter().enumerate().fold(
+            (0, 0.0),
+            |(k, max), (j, m)| if *m > max { (j, *m) } else { (k, max) },
+        );
+    assert_eq!(zoom.frequencies().nth(k), Some(f));
+    assert!((peak - 0.7).abs() < 0.01, "{}", peak);
+
+    // and again after a reset, pushed one at a time
+    zoom.reset();
+    assert!(!zoom.is_full());
+    let needed = zoom.samples_needed();
+    for (n, x) in x.iter().enumerate() {
+        if zoom.push(*x) {
+            assert_eq!(n + 1, needed);
+            break;
+        }
+    }
+    assert_eq!(magnitudes(&zoom.spectrum()), spectrum);
+}
+
+#[test]
+fn resolves_close_tones() {
+    // 0.6 Hz apart, the plain FFT's bins are nearly 2 Hz
+    let x = tones(&[(1.0, 200.0), (0.8, 200.6)], 10000);
+
+    let mut plain = x[..512]
+        .iter()
+        .cloned()
+        .collect::<heapless::Vec<f32, U512>>();
+    let hann = Window::Hann.periodic(512).collect::<Vec<f32>>();
+    plain.iter_mut().zip(hann.iter()).for_each(|(x, w)| *x *= w);
+    let mut spectrum = [0.0f32; 257];
+    Microfft::amplitude_packed(Microfft::rfft(&mut plain), 0.5, &mut spectrum);
+    let band = &spectrum[95..110];
+    assert_eq!(peaks(band, 0.1).len(), 1);
+
+    let mut zoom = Zoom::<Microfft, U512, U129>::new(200.3, FS, 16, Window::Hann);
+    assert!((zoom.resolution() - 0.122).abs() < 1e-3);
+    assert!(zoom.extend(&x));
+    let spectrum = magnitudes(&zoom.spectrum());
+    let found = peaks(&spectrum, 0.3);
+    assert_eq!(found.len(), 2, "{:?}", found);
+
+    let frequencies = zoom.frequencies().collect::<Vec<f32>>();
+    let (low, high) = (frequencies[found[0]], frequencies[found[1]]);
+    assert!((low - 200.0).abs() < zoom.resolution(), "{}", low);
+    assert!((high - 200.6).abs() < zoom.resolution(), "{}", high);
+    // a proper dip between them
+    let dip = spectrum[found[0]..found[1]]
+        .iter()
+        .cloned()
+        .fold(f32::MAX, f32::min);
+    assert!(dip < 0.5 * spectrum[found[1]]);
+}
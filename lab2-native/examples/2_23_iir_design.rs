@@ -0,0 +1,91 @@
+//! This project is used for designing the IIR filters used in 2_23 and 2_25
+//! instead of copying coefficients out of matlab. The filter is designed as an
+//! analog prototype, moved to the wanted band, bilinear transformed and then
+//! split into second order sections which is how it should be run.
+//!
+//! The coefficients in 2_23 turn out to be a first order Butterworth low pass
+//! at Wn = 0.0301 applied twice, and the 2_24 ones a first order high pass at
+//! Wn = 0.1199 applied twice, both regenerated below.
+//!
+//! Runs entirely locally without hardware. Rounding might be different than on
+//! device. Except for when printing you must be vigilent to not become reliant
+//! on any std tools that can't otherwise port over no no_std without alloc.
+//!
+//! `cargo run --example 2_23_iir_design`
+
+use textplots::{Chart, Plot, Shape};
+
+use core::f32::consts::{FRAC_PI_4, PI};
+use dsp::iir::{butter, cheby1, cheby2, ellip, Band};
+use dsp::sos::{self, Cascade};
+use dsp::zpk::Zpk;
+use typenum::Unsigned;
+
+type N = heapless::consts::U512;
+
+fn main() {
+    // 2_23 B and A
+    let zpk = squared(butter(1, Band::LowPass(0.030_120_5)).unwrap());
+    let (b, a) = zpk.to_tf();
+    println!("2_23 low pass b: {:.6?} a: {:.6?}", &b[..], &a[..]);
+
+    // 2_24 B and A
+    let zpk = squared(butter(1, Band::HighPass(0.119_870_2)).unwrap());
+    let (b, a) = zpk.to_tf();
+    println!("2_24 high pass b: {:.6?} a: {:.6?}", &b[..], &a[..]);
+
+    let x = (0..N::to_usize())
+        .map(|n| (PI * n as f32 / 128.0).sin() + (FRAC_PI_4 * n as f32).sin())
+        .collect::<heapless::Vec<f32, N>>();
+    display::<N, _>("x", x.iter().cloned());
+
+    // Keep the slow sine, cut the pi/4 one. Band edge between the two, at Wn
+    // 0.1 ie pi/10 rad/sample
+    let designs = [
+        ("butter", butter(4, Band::LowPass(0.1)).unwrap()),
+        ("cheby1", cheby1(4, 1.0, Band::LowPass(0.1)).unwrap()),
+        ("cheby2", cheby2(4, 40.0, Band::LowPass(0.15)).unwrap()),
+        ("ellip", ellip(4, 1.0, 40.0, Band::LowPass(0.1)).unwrap()),
+    ];
+
+    for (name, zpk) in designs.iter() {
+        let sections = sos::from_zpk(zpk);
+        println!("{} sos:", name);
+        for s in sections.iter() {
+            println!("    b: {:.6?} a: {:.6?}", s.b, s.a);
+        }
+
+        let mut cascade = Cascade::new(&sections);
+        let y = x
+            .iter()
+            .map(|x| cascade.process(*x))
+            .collect::<heapless::Vec<f32, N>>();
+        display::<N, _>(name, y.iter().cloned());
+    }
+}
+
+/// Same filter twice in a row, double up every root and square the gain
+fn squared(zpk: Zpk) -> Zpk {
+    let z = zpk.z.iter().chain(zpk.z.iter()).cloned().collect::<Vec<_>>();
+    let p = zpk.p.iter().chain(zpk.p.iter()).cloned().collect::<Vec<_>>();
+    Zpk::new(&z, &p, zpk.k * zpk.k)
+}
+
+// Points isn't a great representation as you can lose the line in the graph,
+// however while Lines occasionally looks good it also can be terrible.
+// Continuous requires to be in a fn pointer closure which cant capture any
+// external data so not useful without lots of code duplication.
+fn display<N, I>(name: &str, input: I)
+where
+    N: Unsigned,
+    I: Iterator<Item = f32> + core::clone::Clone,
+{
+    println!("{:?}:", name);
+    let display = input
+        .enumerate()
+        .map(|(n, y)| (n as f32, y))
+        .collect::<Vec<(f32, f32)>>();
+    Chart::new(120, 60, 0.0, N::to_usize() as f32)
+        .lineplot(&Shape::Lines(&display[..]))
+        .display();
+}
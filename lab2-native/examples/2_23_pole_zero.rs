@@ -0,0 +1,49 @@
+//! This project is used for checking difference equations before running them.
+//! The B and A coefficients are factored into zeros and poles, which tells us
+//! whether the filter is stable and minimum phase, and the z-plane is drawn
+//! in the terminal with x for poles and o for zeros. Each plot is also saved
+//! as an svg in the current directory.
+//!
+//! digital_system7 from 2_11, y[n] = x[n] + 2y[n-1], has its pole at 2 which
+//! is why its output blows up.
+//!
+//! Runs entirely locally without hardware. Rounding might be different than on
+//! device. Except for when printing you must be vigilent to not become reliant
+//! on any std tools that can't otherwise port over no no_std without alloc.
+//!
+//! `cargo run --example 2_23_pole_zero`
+
+use dsp::iir::{ellip, Band};
+use dsp::pz;
+use dsp::zpk::Zpk;
+
+// 2_23 low pass
+static B: &[f32] = &[0.002044, 0.004088, 0.002044];
+static A: &[f32] = &[1.0, -1.819168, 0.827343];
+
+// 2_24 high pass
+static B_HP: &[f32] = &[0.705514, -1.411028, 0.705514];
+static A_HP: &[f32] = &[1.0, -1.359795, 0.462261];
+
+fn main() {
+    analyze("digital_system7", &from_f32(&[1.0], &[1.0, -2.0]));
+    analyze("2_23_low_pass", &from_f32(B, A));
+    analyze("2_24_high_pass", &from_f32(B_HP, A_HP));
+    analyze("ellip", &ellip(4, 1.0, 40.0, Band::LowPass(0.1)).unwrap());
+}
+
+fn from_f32(b: &[f32], a: &[f32]) -> Zpk {
+    let b = b.iter().map(|b| *b as f64).collect::<Vec<_>>();
+    let a = a.iter().map(|a| *a as f64).collect::<Vec<_>>();
+    Zpk::from_tf(&b, &a).unwrap()
+}
+
+fn analyze(name: &str, zpk: &Zpk) {
+    println!("{} zeros: {:.4?}", name, &zpk.z[..]);
+    println!("{} poles: {:.4?}", name, &zpk.p[..]);
+    pz::plot(name, zpk);
+
+    let path = format!("{}.svg", name);
+    std::fs::write(&path, pz::svg(name, zpk)).unwrap();
+    println!("wrote {}", path);
+}
@@ -0,0 +1,118 @@
+//! This project is used for showing why high order IIR filters are run as
+//! second order sections. A 10th order Butterworth low pass is expanded into a
+//! single B and A pair and rounded to f32 like the coefficients in 2_23. That
+//! moves its poles outside the unit circle and the direct form output blows
+//! up, while the same filter split into sections works fine in f32.
+//!
+//! The sections are also scaled for fixed point with the L2 and Linf norms, and
+//! the 2_23 coefficients are converted back and forth between forms.
+//!
+//! Runs entirely locally without hardware. Rounding might be different than on
+//! device. Except for when printing you must be vigilent to not become reliant
+//! on any std tools that can't otherwise port over no no_std without alloc.
+//!
+//! `cargo run --example 2_23_sos_conversion`
+
+use textplots::{Chart, Plot, Shape};
+
+use core::f32::consts::{FRAC_PI_4, PI};
+use dsp::iir::{butter, Band};
+use dsp::pz;
+use dsp::sos::{self, Cascade, Norm};
+use dsp::zpk::Zpk;
+use typenum::Unsigned;
+
+type N = heapless::consts::U512;
+
+// 2_23 low pass
+static B: &[f64] = &[0.002044, 0.004088, 0.002044];
+static A: &[f64] = &[1.0, -1.819168, 0.827343];
+
+fn main() {
+    let sections = sos::from_tf(B, A).unwrap();
+    println!("2_23 as sections: {:.6?}", &sections[..]);
+    let zpk = sos::to_zpk(&sections).unwrap();
+    println!("2_23 zeros {:.4?} poles {:.4?}", &zpk.z[..], &zpk.p[..]);
+    let (b, a) = sos::to_tf(&sections);
+    println!("2_23 back again b: {:.6?} a: {:.6?}", &b[..], &a[..]);
+
+    let zpk = butter(10, Band::LowPass(0.05)).unwrap();
+    let (b, a) = zpk.to_tf();
+    let b32 = b.iter().map(|b| *b as f32).collect::<Vec<_>>();
+    let a32 = a.iter().map(|a| *a as f32).collect::<Vec<_>>();
+    println!("butter(10) b: {:?}", b32);
+    println!("butter(10) a: {:?}", a32);
+
+    // what the filter really is once its coefficients are f32
+    let rounded = Zpk::from_tf(
+        &b32.iter().map(|b| *b as f64).collect::<Vec<_>>(),
+        &a32.iter().map(|a| *a as f64).collect::<Vec<_>>(),
+    )
+    .unwrap();
+    pz::plot("direct form in f32", &rounded);
+
+    let sections = sos::from_zpk(&zpk);
+    for s in sections.iter() {
+        let a = s.a.iter().map(|a| *a as f32 as f64).collect::<Vec<_>>();
+        let analysis = pz::analyze(&Zpk::from_tf(&[1.0], &a).unwrap());
+        println!(
+            "section a: {:.6?} {:?} pole radius {:.6}",
+            s.a, analysis.stability, analysis.max_pole_radius
+        );
+    }
+
+    let x = (0..N::to_usize())
+        .map(|n| (PI * n as f32 / 128.0).sin() + (FRAC_PI_4 * n as f32).sin())
+        .collect::<heapless::Vec<f32, N>>();
+    display::<N, _>("x", x.iter().cloned());
+
+    // same loop as 2_23 only with more taps
+    let mut y = heapless::Vec::<f32, N>::new();
+    for n in 0..N::to_usize() {
+        let mut out = 0.0;
+        for k in 0..b32.len().min(n + 1) {
+            out += b32[k] * x[n - k];
+        }
+        for k in 1..a32.len().min(n + 1) {
+            out -= a32[k] * y[n - k];
+        }
+        y.push(out).unwrap();
+    }
+    // clamp so the chart stays readable, it is well past this anyway
+    display::<N, _>("direct form", y.iter().map(|y| y.clamp(-10.0, 10.0)));
+
+    let mut cascade = Cascade::new(&sections);
+    let y = x
+        .iter()
+        .map(|x| cascade.process(*x))
+        .collect::<heapless::Vec<f32, N>>();
+    display::<N, _>("sections", y.iter().cloned());
+
+    for norm in [Norm::L2, Norm::LInf].iter() {
+        let mut scaled = sections.clone();
+        let gain = sos::scale(&mut scaled, *norm);
+        println!("{:?} scaled, output gain {:.4}:", norm, gain);
+        for s in scaled.iter() {
+            println!("    b: {:.6?} a: {:.6?}", s.b, s.a);
+        }
+    }
+}
+
+// Points isn't a great representation as you can lose the line in the graph,
+// however while Lines occasionally looks good it also can be terrible.
+// Continuous requires to be in a fn pointer closure which cant capture any
+// external data so not useful without lots of code duplication.
+fn display<N, I>(name: &str, input: I)
+where
+    N: Unsigned,
+    I: Iterator<Item = f32> + core::clone::Clone,
+{
+    println!("{:?}:", name);
+    let display = input
+        .enumerate()
+        .map(|(n, y)| (n as f32, y))
+        .collect::<Vec<(f32, f32)>>();
+    Chart::new(120, 60, 0.0, N::to_usize() as f32)
+        .lineplot(&Shape::Lines(&display[..]))
+        .display();
+}
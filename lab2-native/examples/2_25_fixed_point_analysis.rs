@@ -0,0 +1,82 @@
+//! This project is used for checking what happens to the 2_23 and 2_24 IIR
+//! filters when they run in Q15 or Q31 like arm_biquad_cascade_df1_q15/q31
+//! would on the board. Each filter is run on the 2_23 input and compared to
+//! the f32 version for its signal to noise ratio, and then given zero input
+//! after a single kick to see whether the output dies out or gets stuck in a
+//! limit cycle. Both are repeated with first and second order error feedback.
+//!
+//! Runs entirely locally without hardware. Rounding might be different than on
+//! device. Except for when printing you must be vigilent to not become reliant
+//! on any std tools that can't otherwise port over no no_std without alloc.
+//!
+//! `cargo run --example 2_25_fixed_point_analysis`
+
+use core::f32::consts::{FRAC_PI_4, PI};
+use dsp::fixed::{limit_cycle, snr_db, Cascade, ErrorFeedback, Fixed};
+use dsp::sos::{self, Section};
+use typenum::Unsigned;
+
+type N = heapless::consts::U512;
+
+// 2_23 low pass
+static B: &[f64] = &[0.002044, 0.004088, 0.002044];
+static A: &[f64] = &[1.0, -1.819168, 0.827343];
+
+// 2_24 high pass
+static B_HP: &[f64] = &[0.705514, -1.411028, 0.705514];
+static A_HP: &[f64] = &[1.0, -1.359795, 0.462261];
+
+fn main() {
+    // halved, the two sines add up to 2 which doesn't fit in Q15 or Q31
+    let x = (0..N::to_usize())
+        .map(|n| 0.5 * ((PI * n as f32 / 128.0).sin() + (FRAC_PI_4 * n as f32).sin()))
+        .collect::<heapless::Vec<f32, N>>();
+
+    for (name, b, a) in [("2_23 low pass", B, A), ("2_24 high pass", B_HP, A_HP)].iter() {
+        let sections = sos::from_tf(b, a).unwrap();
+        println!("{}", name);
+        analyze::<i16>("q15", &sections, &x);
+        analyze::<i32>("q31", &sections, &x);
+    }
+}
+
+fn analyze<T: Fixed>(format: &str, sections: &[Section], x: &[f32]) {
+    let mut reference = sos::Cascade::new(sections);
+    let expected = x
+        .iter()
+        .map(|x| reference.process(*x) as f64)
+        .collect::<Vec<_>>();
+
+    for feedback in [
+        ErrorFeedback::None,
+        ErrorFeedback::FirstOrder,
+        ErrorFeedback::SecondOrder,
+    ]
+    .iter()
+    {
+        let mut cascade = Cascade::<T>::new(sections, *feedback).unwrap();
+        let actual = x
+            .iter()
+            .map(|x| cascade.process(T::from_f64(*x as f64)).to_f64())
+            .collect::<Vec<_>>();
+
+        println!(
+            "    {} {:?}: postShift {}, snr {:.1}dB, {} saturations",
+            format,
+            feedback,
+            cascade.post_shift(),
+            snr_db(&expected, &actual),
+            cascade.saturations
+        );
+
+        for kick in [0.9, 0.5, 0.1, -0.1, -0.5, -0.9].iter() {
+            match limit_cycle(&mut cascade, T::from_f64(*kick), 2000) {
+                Some(cycle) => println!(
+                    "        kick {:4}: limit cycle of {} lsb, period {:?}",
+                    kick, cycle.amplitude, cycle.period
+                ),
+                None => println!("        kick {:4}: dies out", kick),
+            }
+        }
+    }
+}
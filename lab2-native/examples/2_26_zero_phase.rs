@@ -0,0 +1,67 @@
+//! This project is used for post processing a captured signal without the
+//! delay a causal filter adds. The 2_23 low pass is run over the 2_23 input
+//! once like on the board and then forwards and backwards with filtfilt, which
+//! keeps the slow sine lined up with the input and doesn't start with a
+//! transient.
+//!
+//! Runs entirely locally without hardware. Rounding might be different than on
+//! device. Except for when printing you must be vigilent to not become reliant
+//! on any std tools that can't otherwise port over no no_std without alloc.
+//!
+//! `cargo run --example 2_26_zero_phase`
+
+use textplots::{Chart, Plot, Shape};
+
+use core::f64::consts::{FRAC_PI_4, PI};
+use dsp::filtfilt::{filtfilt, lfilter, lfilter_zi};
+use typenum::Unsigned;
+
+type N = heapless::consts::U512;
+
+// 2_23 low pass
+static B: &[f64] = &[0.002044, 0.004088, 0.002044];
+static A: &[f64] = &[1.0, -1.819168, 0.827343];
+
+fn main() {
+    // offset so the start up transient shows
+    let x = (0..N::to_usize())
+        .map(|n| 1.0 + (PI * n as f64 / 128.0).sin() + (FRAC_PI_4 * n as f64).sin())
+        .collect::<Vec<_>>();
+    display::<N, _>("x", x.iter().map(|x| *x as f32));
+
+    let mut causal = x.clone();
+    lfilter(B, A, &mut [0.0; 2], &mut causal);
+    display::<N, _>("causal", causal.iter().map(|y| *y as f32));
+
+    println!("steady state for a unit step: {:?}", &lfilter_zi(B, A)[..]);
+
+    let zero_phase = filtfilt(B, A, &x).unwrap();
+    display::<N, _>("filtfilt", zero_phase.iter().map(|y| *y as f32));
+
+    let peak = |y: &[f64]| (256..384).fold(256, |m, n| if y[n] > y[m] { n } else { m });
+    println!(
+        "slow sine peak at {} in x, {} causal, {} filtfilt",
+        peak(&x),
+        peak(&causal),
+        peak(&zero_phase)
+    );
+}
+
+// Points isn't a great representation as you can lose the line in the graph,
+// however while Lines occasionally looks good it also can be terrible.
+// Continuous requires to be in a fn pointer closure which cant capture any
+// external data so not useful without lots of code duplication.
+fn display<N, I>(name: &str, input: I)
+where
+    N: Unsigned,
+    I: Iterator<Item = f32> + core::clone::Clone,
+{
+    println!("{:?}:", name);
+    let display = input
+        .enumerate()
+        .map(|(n, y)| (n as f32, y))
+        .collect::<Vec<(f32, f32)>>();
+    Chart::new(120, 60, 0.0, N::to_usize() as f32)
+        .lineplot(&Shape::Lines(&display[..]))
+        .display();
+}
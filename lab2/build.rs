@@ -1,3 +1,6 @@
+use dsp::iir::{butter, Band};
+use std::fmt::Write;
+
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-changed=libarm_cortexM4lf_math.a");
@@ -8,4 +11,25 @@ fn main() {
         std::env::var("CARGO_MANIFEST_DIR").unwrap()
     );
     println!("cargo:rustc-link-lib=static=arm_cortexM4lf_math");
+
+    iir_coefficients();
+}
+
+// Design filters at compile time so the examples can include! the second
+// order sections instead of carrying copied coefficients around
+fn iir_coefficients() {
+    let zpk = butter(4, Band::LowPass(0.1)).unwrap();
+    let sections = dsp::sos::from_zpk(&zpk);
+
+    let mut out = String::new();
+    writeln!(out, "// butter(4, Band::LowPass(0.1)) from build.rs").unwrap();
+    writeln!(out, "static SOS: &[dsp::sos::Section] = &[").unwrap();
+    for s in sections.iter() {
+        writeln!(out, "    dsp::sos::Section {{ b: {:?}, a: {:?} }},", s.b, s.a).unwrap();
+    }
+    writeln!(out, "];").unwrap();
+
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    let path = std::path::Path::new(&out_dir).join("iir_coefficients.rs");
+    std::fs::write(path, out).unwrap();
 }
@@ -24,11 +24,13 @@ type N = heapless::consts::U512;
 //todo derive this from N
 const N_CONST: usize = 512;
 
-// high pass filter coefficients
+// low pass filter coefficients, a first order Butterworth at Wn 0.0301 applied
+// twice. Regenerate with lab2-native 2_23_iir_design
 static B: &[f32] = &[0.002044, 0.004088, 0.002044];
 static A: &[f32] = &[1.0, -1.819168, 0.827343];
 
-// low pass filter coefficients for 2_24
+// high pass filter coefficients for 2_24, first order Butterworth at Wn 0.1199
+// applied twice
 // static B: &[f32] = &[0.705514, -1.411028, 0.705514];
 // static A: &[f32] = &[1.0, -1.359795, 0.462261];
 
@@ -0,0 +1,81 @@
+//! This project is used for running IIR filters designed with the dsp crate
+//! instead of hand copied coefficients. The Butterworth filter is designed at
+//! compile time in build.rs, the Chebyshev one at runtime on the board. Both
+//! run as a cascade of second order sections.
+//!
+//! Requires `cargo install probe-run`
+//! `cargo run --release --example 2_26_designed_iir_filtering`
+
+#![no_std]
+#![no_main]
+
+use panic_break as _;
+use stm32f4xx_hal as hal;
+
+use core::f32::consts::{FRAC_PI_4, PI};
+use dsp::iir::{cheby1, Band};
+use dsp::sos::{self, Cascade};
+use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, stm32};
+use micromath::F32Ext;
+use rtt_target::{rprintln, rtt_init_print};
+use typenum::Unsigned;
+
+type N = heapless::consts::U512;
+
+// static SOS
+include!(concat!(env!("OUT_DIR"), "/iir_coefficients.rs"));
+
+#[cortex_m_rt::entry]
+fn main() -> ! {
+    rtt_init_print!(BlockIfFull, 128);
+
+    let dp = stm32::Peripherals::take().unwrap();
+    let cp = cortex_m::peripheral::Peripherals::take().unwrap();
+
+    // Set up the system clock.
+    let rcc = dp.RCC.constrain();
+
+    let clocks = rcc
+        .cfgr
+        .use_hse(8.mhz()) //discovery board has 8 MHz crystal for HSE
+        .sysclk(168.mhz())
+        .freeze();
+
+    // Create a delay abstraction based on DWT cycle counter
+    let dwt = cp.DWT.constrain(cp.DCB, clocks);
+
+    let x = (0..N::to_usize())
+        .map(|n| (PI * n as f32 / 128.0).sin() + (FRAC_PI_4 * n as f32).sin())
+        .collect::<heapless::Vec<f32, N>>();
+
+    // compile time design
+    let mut butter = Cascade::new(SOS);
+    let y = x
+        .iter()
+        .map(|x| butter.process(*x))
+        .collect::<heapless::Vec<f32, N>>();
+    rprintln!("butter y: {:?}", y);
+
+    // runtime design, f64 is software float on the m4 so this is slow but
+    // only happens once
+    let mut sections = None;
+    let time: ClockDuration = dwt.measure(|| {
+        sections = Some(sos::from_zpk(&cheby1(4, 1.0, Band::LowPass(0.1)).unwrap()));
+    });
+    rprintln!("design ticks: {:?}", time.as_ticks());
+
+    let mut chebyshev = Cascade::new(&sections.unwrap());
+    let mut y = [0.0; 512];
+    let time: ClockDuration = dwt.measure(|| {
+        y.iter_mut()
+            .zip(x.iter())
+            .for_each(|(y, x)| *y = chebyshev.process(*x));
+    });
+    rprintln!("cheby1 y: {:?}", y);
+    rprintln!("filter ticks: {:?}", time.as_ticks());
+
+    // signal to probe-run to exit
+    loop {
+        cortex_m::asm::bkpt()
+    }
+}
@@ -8,12 +8,11 @@
 //! device. Except for when printing you must be vigilent to not become reliant
 //! on any std tools that can't otherwise port over no no_std without alloc.
 //!
-//! `cargo run --example 4_10_stft_calculations`
+//! `cargo run --example 4_13_fif_calculations`
 
 use textplots::{Chart, Plot, Shape};
 
-use core::f32::consts::PI;
-use microfft::{complex::cfft_512, Complex32};
+use dsp::fft::{multiply, Microfft, Rfft};
 use typenum::Unsigned;
 
 type N = heapless::consts::U512;
@@ -22,54 +21,48 @@ const W1: f32 = core::f32::consts::PI / 128.0;
 const W2: f32 = core::f32::consts::PI / 4.0;
 
 fn main() {
-    // Complex sum of sinusoidal signals
+    // Sum of sinusoidal signals, real so the real FFT will do
     let s1 = (0..N::to_usize()).map(|val| (W1 * val as f32).sin());
     let s2 = (0..N::to_usize()).map(|val| (W2 * val as f32).sin());
     let s = s1.zip(s2).map(|(ess1, ess2)| ess1 + ess2);
 
-    let mut s_complex = s
-        .clone()
-        .map(|f| Complex32 { re: f, im: 0.0 })
-        .collect::<heapless::Vec<Complex32, N>>();
+    let mut y = s.clone().collect::<heapless::Vec<f32, N>>();
 
-    // Complex impulse response of filter
-    let mut df_complex = H
-        .iter()
-        .cloned()
-        .map(|f| Complex32 { re: f, im: 0.0 })
-        .chain(core::iter::repeat(Complex32 { re: 0.0, im: 0.0 }))
-        //fill rest with zeros up to N
-        .take(N::to_usize())
-        .collect::<heapless::Vec<Complex32, N>>();
+    // Impulse response of filter, the rest is filled with zeros up to N
+    let mut df = H.iter().cloned().collect::<heapless::Vec<f32, N>>();
+
+    println!(
+        "{} bytes per buffer instead of {} for complex",
+        core::mem::size_of_val(&y),
+        core::mem::size_of::<heapless::Vec<dsp::Complex32, N>>()
+    );
 
     // Finding the FFT of the filter
-    let _ = cfft_512(&mut df_complex[..]);
+    let df_freq = Microfft::rfft(&mut df);
 
-    // Finding the FFT of the input signal
-    let _ = cfft_512(&mut s_complex[..]);
+    // Finding the FFT of the input signal and filtering in the frequency domain
+    multiply(Microfft::rfft(&mut y), df_freq);
 
-    // Filtering in the frequency domain
-    let y_complex = s_complex
-        .iter()
-        .zip(df_complex.iter())
-        //multiply complex
-        .map(|(s, df)| Complex32 {
-            re: s.re * df.re - s.im * df.im,
-            im: s.re * df.im + s.im * df.re,
-        });
-
-    // Finding the complex result in time domain
-    // supposed to be inverse transform but microfft doesnt have it
-    // Could patch it in. inverse DFT is the same as the DFT, but with the
-    // opposite sign in the exponent and a 1/N factor, any FFT algorithm can
-    // easily be adapted for it.
-    // just dtfse approx instead for now
-    let y_freq = dtfse::<N, _>(y_complex.clone(), 15).collect::<heapless::Vec<f32, N>>();
-    display::<N, _>("freq", y_freq.iter().cloned());
+    // Finding the result in time domain
+    Microfft::irfft(&mut y);
+    display::<N, _>("freq", y.iter().cloned());
 
     //y_time via convolution_sum developed in 2.14 to compare
     let y_time = convolution_sum(s).collect::<heapless::Vec<f32, N>>();
     display::<N, _>("time", y_time.iter().cloned());
+
+    // the FFT convolution is circular so the start wraps around the end of
+    // the signal until the filter is full
+    let difference = y
+        .iter()
+        .zip(y_time.iter())
+        .skip(H.len())
+        .fold(0f32, |m, (f, t)| m.max((f - t).abs()));
+    println!(
+        "largest difference after the first {} samples: {}",
+        H.len(),
+        difference
+    );
 }
 
 static H: &[f32] = &[
@@ -83,25 +76,6 @@ static H: &[f32] = &[
     0.001448,
 ];
 
-fn dtfse<N: Unsigned, I: Iterator<Item = Complex32> + Clone>(
-    coeff: I,
-    k_var: usize,
-) -> impl Iterator<Item = f32> {
-    let size = N::to_usize() as f32;
-    (0..N::to_usize()).map(move |n| {
-        coeff
-            .clone()
-            .take(k_var + 1)
-            .enumerate()
-            .map(|(k, complex)| {
-                let a = (complex.re * complex.re + complex.im * complex.im).sqrt();
-                let p = complex.im.atan2(complex.re);
-                a * ((2.0 * PI * k as f32 * n as f32 / size) + p).cos() / size
-            })
-            .sum::<f32>()
-    })
-}
-
 pub fn convolution_sum<I>(x: I) -> impl Iterator<Item = f32> + Clone
 where
     I: Iterator<Item = f32>
@@ -134,6 +108,6 @@ where
         .map(|(n, y)| (n as f32, y))
         .collect::<Vec<(f32, f32)>>();
     Chart::new(120, 60, 0.0, N::to_usize() as f32)
-        .lineplot(Shape::Lines(&display[..]))
+        .lineplot(&Shape::Lines(&display[..]))
         .display();
 }
@@ -1,7 +1,11 @@
 //! This project is used for explaining the linear phase property of digital
 //! filters. Here we have a low-pass filter represented by h array. First its
 //! FFT is calculated using the arm_cfft_f32 function. Then the magnitude and
-//! phase of the FFT are stored in Mag and Phase arrays.
+//! phase of the FFT are stored in Mag and Phase arrays. The phase is then
+//! unwrapped from a zero padded FFT and its slope read off as the delay. After
+//! that the response is evaluated exactly with freqz at a fine grid, plotted
+//! in Hz, and the group delay is checked. A symmetric FIR of length N delays
+//! every frequency by (N-1)/2 samples, which is what linear phase means.
 //!
 //! Runs entirely locally without hardware. Rounding might be different than on
 //! device. Except for when printing you must be vigilent to not become reliant
@@ -11,12 +15,18 @@
 
 use textplots::{Chart, Plot, Shape};
 
-use core::f32::consts::PI;
+use dsp::fft::{Cfft, Microfft};
+use dsp::freqz::Response;
+use dsp::spectrum::{phase, unwrap, Spectrum};
+use dsp::Complex32;
 use itertools::Itertools;
-use microfft::{complex::cfft_64, Complex32};
 use typenum::Unsigned;
 
 type N = heapless::consts::U64;
+type Padded = heapless::consts::U512;
+
+// any rate works, the response just scales with it
+const FS: f64 = 16000.0;
 
 fn main() {
     // Complex impulse response of filter
@@ -26,27 +36,62 @@ fn main() {
         .map(|h| Complex32 { re: h, im: 0.0 })
         .collect::<heapless::Vec<Complex32, N>>();
 
-    let _ = cfft_64(&mut dtfsecoef[..]);
+    Microfft::cfft(&mut dtfsecoef);
 
     // Magnitude calculation
-    let mag = dtfsecoef
-        .iter()
-        .map(|complex| (complex.re * complex.re + complex.im * complex.im).sqrt())
-        .collect::<heapless::Vec<f32, N>>();
+    let mut mag = heapless::Vec::<f32, N>::new();
+    mag.resize(N::to_usize(), 0.0).unwrap();
+    Microfft::magnitude(&dtfsecoef, &mut mag);
     display::<N, _>("mag", mag.iter().cloned());
 
-    let phase = dtfsecoef
+    // With only 64 bins the phase moves by nearly pi from one bin to the next,
+    // too much to unwrap. Zero padded to 512 it moves by pi/8 or so.
+    let mut padded = H
         .iter()
-        .cloned()
-        .map(|complex| complex.re.atan2(complex.im));
+        .map(|h| Complex32 { re: *h, im: 0.0 })
+        .collect::<heapless::Vec<Complex32, Padded>>();
+    Microfft::cfft(&mut padded);
 
-    // not sure why yet, but this is how they display in the matlab file
-    let phase_graph = phase
-        .clone()
-        .enumerate()
-        .map(|(i, phase)| if i < 33 { phase } else { phase - PI });
+    let half = Padded::to_usize() / 2 + 1;
+    let mut phases = heapless::Vec::<f32, Padded>::new();
+    phases.resize(half, 0.0).unwrap();
+    phase(&padded[..half], &mut phases);
+    unwrap(&mut phases);
+    display::<Padded, _>("phase", phases.iter().cloned());
 
-    display::<N, _>("phase", phase_graph.clone());
+    // The slope through the pass band is the delay, -2 pi delay / 512 per bin.
+    // In the stop band the zeros on the unit circle flip the sign which the
+    // unwrap can't tell from a step of pi either way.
+    let mut padded_mag = heapless::Vec::<f32, Padded>::new();
+    padded_mag.resize(half, 0.0).unwrap();
+    Microfft::magnitude(&padded[..half], &mut padded_mag);
+    let edge = padded_mag.iter().position(|m| *m < 0.5).unwrap() - 1;
+    let slope = (phases[edge] - phases[0]) / edge as f32;
+    println!(
+        "pass band delay from the phase slope {:.2} samples",
+        -slope * Padded::to_usize() as f32 / (2.0 * core::f32::consts::PI)
+    );
+
+    let response = Response::new(H, &[1.0], FS, 512);
+    response.plot("H");
+
+    // Group delay is (N-1)/2 everywhere except right at the zeros on the unit
+    // circle in the stop band where there is no phase to speak of
+    let expected = (H.len() - 1) as f64 / 2.0;
+    let worst = response
+        .group_delay
+        .iter()
+        .zip(response.magnitude().iter())
+        .filter(|(_, mag)| **mag > 1e-3)
+        .map(|(gd, _)| (gd - expected).abs())
+        .fold(0.0, f64::max);
+    println!(
+        "group delay {} samples, {:.2}ms, worst deviation {:e}",
+        expected,
+        expected / FS * 1000.0,
+        worst
+    );
+    assert!(worst < 1e-6);
 }
 
 // Points isn't a great representation as you can lose the line in the graph,
@@ -64,7 +109,7 @@ where
         .map(|(n, y)| (n as f32, y))
         .collect::<Vec<(f32, f32)>>();
     Chart::new(120, 60, 0.0, N::to_usize() as f32)
-        .lineplot(Shape::Lines(&display[..]))
+        .lineplot(&Shape::Lines(&display[..]))
         .display();
 }
 
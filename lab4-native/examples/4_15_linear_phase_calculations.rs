@@ -1,7 +1,10 @@
 //! This project is used for explaining the linear phase property of digital
 //! filters. Here we have a low-pass filter represented by h array. First its
 //! FFT is calculated using the arm_cfft_f32 function. Then the magnitude and
-//! phase of the FFT are stored in Mag and Phase arrays.
+//! phase of the FFT are stored in Mag and Phase arrays. After that the
+//! response is evaluated exactly with freqz at a fine grid, plotted in Hz, and
+//! the group delay is checked. This filter isn't symmetric, so unlike
+//! 4_14 its group delay changes with frequency.
 //!
 //! Runs entirely locally without hardware. Rounding might be different than on
 //! device. Except for when printing you must be vigilent to not become reliant
@@ -11,12 +14,18 @@
 
 use textplots::{Chart, Plot, Shape};
 
+use dsp::fft::{Cfft, Microfft};
+use dsp::freqz::Response;
+use dsp::spectrum::{phase, Spectrum};
+use dsp::Complex32;
 use itertools::Itertools;
-use microfft::{complex::cfft_64, Complex32};
 use typenum::Unsigned;
 
 type N = heapless::consts::U64;
 
+// any rate works, the response just scales with it
+const FS: f64 = 16000.0;
+
 fn main() {
     // Complex impulse response of filter
     let mut dtfsecoef = H
@@ -25,21 +34,37 @@ fn main() {
         .map(|h| Complex32 { re: h, im: 0.0 })
         .collect::<heapless::Vec<Complex32, N>>();
 
-    let _ = cfft_64(&mut dtfsecoef[..]);
+    Microfft::cfft(&mut dtfsecoef);
 
     // Magnitude calculation
-    let mag = dtfsecoef
-        .iter()
-        .map(|complex| (complex.re * complex.re + complex.im * complex.im).sqrt())
-        .collect::<heapless::Vec<f32, N>>();
+    let mut mag = heapless::Vec::<f32, N>::new();
+    mag.resize(N::to_usize(), 0.0).unwrap();
+    Microfft::magnitude(&dtfsecoef, &mut mag);
     display::<N, _>("mag", mag.iter().cloned());
 
-    let phase = dtfsecoef
-        .iter()
-        .cloned()
-        .map(|complex| complex.re.atan2(complex.im));
+    // With only 64 bins the phase moves by nearly pi from one bin to the next,
+    // too much to unwrap, so the exact response below is evaluated on a finer
+    // grid.
+    let mut phases = heapless::Vec::<f32, N>::new();
+    phases.resize(N::to_usize(), 0.0).unwrap();
+    phase(&dtfsecoef, &mut phases);
+    display::<N, _>("phase", phases.iter().cloned());
+
+    let response = Response::new(H, &[1.0], FS, 512);
+    response.plot("H");
 
-    display::<N, _>("phase", phase.clone());
+    // away from the nulls in the stop band where it spikes
+    let gd = response
+        .group_delay
+        .iter()
+        .zip(response.magnitude())
+        .filter(|(_, mag)| *mag > 1e-2)
+        .map(|(gd, _)| *gd);
+    println!(
+        "group delay from {:.2} to {:.2} samples",
+        gd.clone().fold(f64::INFINITY, f64::min),
+        gd.fold(f64::NEG_INFINITY, f64::max)
+    );
 }
 
 // Points isn't a great representation as you can lose the line in the graph,
@@ -57,7 +82,7 @@ where
         .map(|(n, y)| (n as f32, y))
         .collect::<Vec<(f32, f32)>>();
     Chart::new(120, 60, 0.0, N::to_usize() as f32)
-        .lineplot(Shape::Lines(&display[..]))
+        .lineplot(&Shape::Lines(&display[..]))
         .display();
 }
 
@@ -0,0 +1,117 @@
+//! This project is used for explaining the cepstrum. First a short burst of
+//! a chirp is convolved with a copy of itself 120 samples later at 0.6 of the
+//! amplitude, like an echo off a wall. The echo is a ripple across the whole
+//! spectrum, which the real cepstrum turns into a spike at a quefrency of 120
+//! samples, and the delay is read off that. Then a pulse train with a period
+//! of 80 samples is passed through a decaying resonance, like a voiced sound.
+//! Its cepstrum has the resonance at low quefrencies and a spike at 80
+//! samples, which at 8kHz is a pitch of 100 Hz. Liftering the low quefrencies
+//! back out leaves only the spike. Last the resonance on its own, run
+//! backwards so its energy comes at the end, is turned into the minimum phase
+//! signal with the same magnitude spectrum, which is it back the right way
+//! round.
+//!
+//! Runs entirely locally without hardware. Rounding might be different than on
+//! device. Except for when printing you must be vigilent to not become reliant
+//! on any std tools that can't otherwise port over no no_std without alloc.
+//!
+//! `cargo run --example 4_22_cepstrum_calculations`
+
+use textplots::{Chart, Plot, Shape};
+
+use dsp::cepstrum::{lifter, minimum_phase, peak, pitch, real, Lifter};
+use dsp::fft::Microfft;
+use itertools::Itertools;
+use typenum::Unsigned;
+
+type N = heapless::consts::U1024;
+type NDIV2 = heapless::consts::U512;
+type Pulse = heapless::consts::U64;
+
+// sampling frequency in Hz
+const FS: f32 = 8000.0;
+
+const ECHO_DELAY: usize = 120;
+const ECHO_GAIN: f32 = 0.6;
+const PERIOD: usize = 80;
+
+fn main() {
+    // the chirp burst and its echo
+    let burst = (0..200).map(|n| {
+        let n = n as f32;
+        (0.2 * n + 0.004 * n * n).cos()
+    });
+    let echoed = burst
+        .clone()
+        .chain(core::iter::repeat_n(0.0, ECHO_DELAY))
+        .zip(core::iter::repeat_n(0.0, ECHO_DELAY).chain(burst.map(|x| ECHO_GAIN * x)))
+        .map(|(x, echo)| x + echo)
+        .collect::<heapless::Vec<f32, N>>();
+    display::<N, _>("echoed", echoed.iter().cloned());
+
+    // the cepstrum is symmetric, only the first half is worth looking at
+    let c = real::<Microfft, N>(&echoed);
+    display::<NDIV2, _>("echo cepstrum", c.iter().cloned().take(NDIV2::to_usize()));
+    // skip the low quefrencies, that's the shape of the burst's spectrum
+    println!(
+        "echo delay: {} samples, actually {}",
+        peak(&c, 20..NDIV2::to_usize()),
+        ECHO_DELAY
+    );
+
+    // a decaying resonance at 0.6 radians per sample, once every PERIOD
+    let resonance = |n: usize| {
+        let t = n as f32;
+        (-t / 12.0).exp() * (0.6 * t).cos()
+    };
+    let voiced = (0..N::to_usize())
+        .map(|n| resonance(n % PERIOD))
+        .collect::<heapless::Vec<f32, N>>();
+    display::<N, _>("voiced", voiced.iter().cloned());
+
+    let mut c = real::<Microfft, N>(&voiced);
+    display::<NDIV2, _>("voiced cepstrum", c.iter().cloned().take(NDIV2::to_usize()));
+    // anywhere from 50 to 400 Hz
+    println!(
+        "pitch: {} Hz, actually {}",
+        pitch(&c, FS, 50.0, 400.0).unwrap(),
+        FS / PERIOD as f32
+    );
+
+    lifter(&mut c, Lifter::High(20));
+    display::<NDIV2, _>(
+        "voiced cepstrum liftered",
+        c.iter().cloned().take(NDIV2::to_usize()),
+    );
+
+    // the resonance backwards, all its energy at the end
+    let backwards = (0..Pulse::to_usize())
+        .rev()
+        .map(resonance)
+        .collect::<heapless::Vec<f32, Pulse>>();
+    display::<Pulse, _>("backwards", backwards.iter().cloned());
+    let minimum = minimum_phase::<Microfft, N>(&backwards);
+    display::<Pulse, _>(
+        "minimum phase",
+        minimum.iter().cloned().take(Pulse::to_usize()),
+    );
+}
+
+// Points isn't a great representation as you can lose the line in the graph,
+// however while Lines occasionally looks good it also can be terrible.
+// Continuous requires to be in a fn pointer closure which cant capture any
+// external data so not useful without lots of code duplication.
+fn display<N, I>(name: &str, input: I)
+where
+    N: Unsigned,
+    I: Iterator<Item = f32> + core::clone::Clone + std::fmt::Debug,
+{
+    println!("{:?}: {:.4?}", name, input.clone().format(", "));
+    let display = input
+        .enumerate()
+        .map(|(idx, y)| (idx as f32, y))
+        .collect::<Vec<(f32, f32)>>();
+    Chart::new(120, 60, 0.0, N::to_usize() as f32)
+        .lineplot(&Shape::Points(&display[..]))
+        .display();
+}
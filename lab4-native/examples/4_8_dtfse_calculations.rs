@@ -13,12 +13,14 @@
 
 use textplots::{Chart, Plot, Shape};
 
-use core::f32::consts::PI;
+use dsp::fft::{Cfft, Microfft};
+use dsp::fourier::Series;
+use dsp::Complex32;
 use itertools::Itertools;
-use microfft::{complex::cfft_16, Complex32};
 use typenum::Unsigned;
 
 type N = heapless::consts::U16;
+type Fine = heapless::consts::U128;
 
 fn main() {
     //square signal
@@ -32,40 +34,37 @@ fn main() {
 
     // Coefficient calculation with CFFT function
     // arm_cfft_f32 uses a forward transform with enables bit reversal of output
-    // well use microfft uses an in place Radix-2 FFT, for some reasons returns itself we dont need
-    let _ = cfft_16(&mut dtfsecoef[..]);
+    // well use microfft, an in place Radix-2 FFT picked by the length of the Vec
+    Microfft::cfft(&mut dtfsecoef);
     println!("dtfsecoef: {:?}", &dtfsecoef[..]);
 
-    //dtfse to reclaim our original signal, note this is a bad approximation for our square wave
-    let y_real = dtfse::<N, _>(dtfsecoef.iter().cloned(), 1).collect::<heapless::Vec<f32, N>>();
-    display::<N, _>("y_real 1", y_real.iter().cloned());
+    // the harmonics, negative ones included, from the coefficients
+    let series = Series::<N>::from_fft(&dtfsecoef);
+    println!("thd: {:?}", series.thd());
 
-    //a bit better
-    let y_real = dtfse::<N, _>(dtfsecoef.iter().cloned(), 5).collect::<heapless::Vec<f32, N>>();
-    display::<N, _>("y_real 5", y_real.iter().cloned());
+    //dtfse to reclaim our original signal, note 1 is a bad approximation for
+    //our square wave, 5 a bit better and 15 all of it
+    for k_var in [1, 5, 15].iter() {
+        let y_real = series
+            .synthesize(N::to_usize(), |k| k <= *k_var)
+            .take(N::to_usize())
+            .collect::<heapless::Vec<f32, N>>();
+        println!("rms error {}: {}", k_var, series.error(*k_var));
+        display::<N, _>(&format!("y_real {}", k_var), y_real.iter().cloned());
+    }
 
-    //good
-    let y_real = dtfse::<N, _>(dtfsecoef.iter().cloned(), 15).collect::<heapless::Vec<f32, N>>();
-    display::<N, _>("y_real 15", y_real.iter().cloned());
-}
-
-fn dtfse<N: Unsigned, I: Iterator<Item = Complex32> + Clone>(
-    coeff: I,
-    k_var: usize,
-) -> impl Iterator<Item = f32> {
-    let size = N::to_usize() as f32;
-    (0..N::to_usize()).map(move |n| {
-        coeff
-            .clone()
-            .take(k_var + 1)
-            .enumerate()
-            .map(|(k, complex)| {
-                let a = (complex.re * complex.re + complex.im * complex.im).sqrt();
-                let p = complex.im.atan2(complex.re);
-                a * ((2.0 * PI * k as f32 * n as f32 / size) + p).cos() / size
-            })
-            .sum::<f32>()
-    })
+    // every harmonic, drawn 8 times finer, rings either side of the jumps and
+    // overshoots them, Gibbs. A continuous square settles to about 9% of the
+    // jump however many harmonics, with only 8 this one is further off
+    let fine = series
+        .synthesize(Fine::to_usize(), |_| true)
+        .take(Fine::to_usize())
+        .collect::<heapless::Vec<f32, Fine>>();
+    println!(
+        "overshoot: {}",
+        fine.iter().cloned().fold(f32::MIN, f32::max) - 1.0
+    );
+    display::<Fine, _>("in between the samples", fine.iter().cloned());
 }
 
 // Points isn't a great representation as you can lose the line in the graph,
@@ -83,6 +82,6 @@ where
         .map(|(idx, y)| (idx as f32, y))
         .collect::<Vec<(f32, f32)>>();
     Chart::new(120, 60, 0.0, N::to_usize() as f32)
-        .lineplot(Shape::Points(&display[..]))
+        .lineplot(&Shape::Points(&display[..]))
         .display();
 }
@@ -0,0 +1,289 @@
+//! Drawing spectrograms as images, shared by the examples that save one.
+//!
+//! [`render`] draws in dB with the quiet end clipped so the noise floor
+//! doesn't take up the colour scale, with the axes labelled in seconds and Hz
+//! and a colour bar in dB. It returns plain RGB pixels rather than a file,
+//! save them with the image crate like
+//! `image::save_buffer(path, &image.rgb, image.width, image.height, image::ColorType::Rgb8)`.
+//! Nothing opens a window, so it works headless.
+
+// pulled in with mod by the examples and the tests, none of which use all of it
+#![allow(dead_code)]
+
+use dsp::spectrogram::{Colormap, Spectrogram};
+use heapless::{ArrayLength, Vec as HVec};
+
+/// How [`render`] draws a spectrogram
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Style {
+    pub colormap: Colormap,
+    /// dB below the loudest bin where the colour scale bottoms out
+    pub dynamic_range: f32,
+    /// Size of the plot in pixels, the axes and colour bar go around it
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            colormap: Colormap::Viridis,
+            dynamic_range: 60.0,
+            width: 512,
+            height: 256,
+        }
+    }
+}
+
+/// RGB pixels, 3 bytes each, a row at a time from the top left
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub rgb: Vec<u8>,
+}
+
+impl Image {
+    fn new(width: u32, height: u32, rgb: [u8; 3]) -> Self {
+        Self {
+            width,
+            height,
+            rgb: rgb
+                .iter()
+                .cycle()
+                .take((width * height * 3) as usize)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+        let i = ((y * self.width + x) * 3) as usize;
+        [self.rgb[i], self.rgb[i + 1], self.rgb[i + 2]]
+    }
+
+    fn set(&mut self, x: i64, y: i64, rgb: [u8; 3]) {
+        if x >= 0 && y >= 0 && x < self.width as i64 && y < self.height as i64 {
+            let i = ((y as u32 * self.width + x as u32) * 3) as usize;
+            self.rgb[i..i + 3].copy_from_slice(&rgb);
+        }
+    }
+
+    fn rect(&mut self, x: i64, y: i64, width: i64, height: i64, rgb: [u8; 3]) {
+        for y in y..y + height {
+            for x in x..x + width {
+                self.set(x, y, rgb);
+            }
+        }
+    }
+
+    /// Text in the 3x5 font doubled, top left at x, y
+    fn text(&mut self, x: i64, y: i64, text: &str) {
+        for (i, c) in text.chars().enumerate() {
+            let x = x + i as i64 * ADVANCE;
+            for (row, bits) in glyph(c).iter().enumerate() {
+                for column in 0..3 {
+                    if bits & (0b100 >> column) != 0 {
+                        let (x, y) = (x + column * 2, y + row as i64 * 2);
+                        self.rect(x, y, 2, 2, BLACK);
+                    }
+                }
+            }
+        }
+    }
+}
+
+const BLACK: [u8; 3] = [0, 0, 0];
+/// Pixels from one character to the next
+const ADVANCE: i64 = 8;
+const GLYPH_HEIGHT: i64 = 10;
+const LEFT: i64 = 64;
+const TOP: i64 = 20;
+const BOTTOM: i64 = 36;
+const GAP: i64 = 16;
+const BAR: i64 = 16;
+const RIGHT: i64 = GAP + BAR + 8 + 6 * ADVANCE;
+const TICK: i64 = 4;
+
+/// Rows of a 3 pixel wide character, the most significant bit on the left.
+/// Only what axis labels need.
+fn glyph(c: char) -> [u8; 5] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'z' => [0b000, 0b111, 0b001, 0b010, 0b111],
+        's' => [0b000, 0b011, 0b110, 0b001, 0b110],
+        'd' => [0b001, 0b001, 0b111, 0b101, 0b111],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        _ => [0; 5],
+    }
+}
+
+/// Round numbers about five to a span, 1, 2 or 5 times a power of ten apart
+fn ticks(lo: f32, hi: f32) -> (f32, Vec<f32>) {
+    let raw = (hi - lo) / 5.0;
+    let magnitude = 10f32.powf(raw.log10().floor());
+    let step = match raw / magnitude {
+        r if r < 1.5 => 1.0,
+        r if r < 3.5 => 2.0,
+        r if r < 7.5 => 5.0,
+        _ => 10.0,
+    } * magnitude;
+
+    let first = (lo / step - 1e-3).ceil();
+    let last = (hi / step + 1e-3).floor();
+    let ticks = (first as i64..=last as i64)
+        .map(|i| i as f32 * step)
+        .collect();
+    (step, ticks)
+}
+
+/// A tick value with as many decimals as the step needs
+fn label(value: f32, step: f32) -> String {
+    let decimals = if step >= 1.0 {
+        0
+    } else {
+        (-step.log10() - 1e-3).ceil() as usize
+    };
+    // no -0
+    let value = if value.abs() < step * 1e-3 {
+        0.0
+    } else {
+        value
+    };
+    format!("{:.*}", decimals, value)
+}
+
+/// Edges of evenly spaced bin centres, half a bin out from each end
+fn extent(centres: &[f32]) -> (f32, f32) {
+    let (first, last) = (centres[0], centres[centres.len() - 1]);
+    let spacing = if centres.len() > 1 {
+        (last - first) / (centres.len() - 1) as f32
+    } else {
+        1.0
+    };
+    (first - spacing / 2.0, last + spacing / 2.0)
+}
+
+/// [`render`] a [`Spectrogram`]
+pub fn spectrogram<N, F>(spectrogram: &Spectrogram<N, F>, style: &Style) -> Image
+where
+    N: ArrayLength<f32>,
+    F: ArrayLength<HVec<f32, N>> + ArrayLength<f32>,
+{
+    render(
+        spectrogram.frames(),
+        spectrogram.times(),
+        spectrogram.frequencies(),
+        style,
+    )
+}
+
+/// Draw `frames` of amplitudes as a spectrogram.
+///
+/// `frames[m][k]` is the amplitude of frame m, centred on `times[m]`
+/// seconds, at `frequencies[k]` Hz. Both axes have to be evenly spaced and
+/// ascending, so fftshift a two-sided spectrum and its frequencies first. The
+/// amplitudes are shown as 20 log10, the loudest at the top of the colour
+/// scale and anything more than the style's dynamic range below it at the
+/// bottom.
+pub fn render<S: AsRef<[f32]>>(
+    frames: &[S],
+    times: &[f32],
+    frequencies: &[f32],
+    style: &Style,
+) -> Image {
+    assert!(!frames.is_empty(), "nothing to draw");
+    assert_eq!(frames.len(), times.len(), "a time for each frame");
+    let bins = frequencies.len();
+    assert!(
+        frames.iter().all(|frame| frame.as_ref().len() >= bins),
+        "a frequency for each bin"
+    );
+
+    let (width, height) = (style.width as i64, style.height as i64);
+    let mut image = Image::new(
+        (LEFT + width + RIGHT) as u32,
+        (TOP + height + BOTTOM) as u32,
+        [255; 3],
+    );
+
+    let db = |a: f32| 20.0 * a.log10();
+    let mut top = frames
+        .iter()
+        .flat_map(|frame| frame.as_ref()[..bins].iter())
+        .map(|a| db(*a))
+        .fold(f32::NEG_INFINITY, f32::max);
+    if !top.is_finite() {
+        top = 0.0;
+    }
+    let bottom = top - style.dynamic_range;
+    let level = |db: f32| (db - bottom) / style.dynamic_range;
+
+    // nearest frame and bin for each pixel, low frequencies at the bottom
+    for y in 0..height {
+        let k = ((height - 1 - y) * bins as i64 / height) as usize;
+        for x in 0..width {
+            let m = (x * frames.len() as i64 / width) as usize;
+            let rgb = style.colormap.rgb(level(db(frames[m].as_ref()[k])));
+            image.set(LEFT + x, TOP + y, rgb);
+        }
+    }
+
+    // frame and axes
+    image.rect(LEFT - 1, TOP - 1, width + 2, 1, BLACK);
+    image.rect(LEFT - 1, TOP + height, width + 2, 1, BLACK);
+    image.rect(LEFT - 1, TOP - 1, 1, height + 2, BLACK);
+    image.rect(LEFT + width, TOP - 1, 1, height + 2, BLACK);
+
+    let (t0, t1) = extent(times);
+    let (step, t) = ticks(t0, t1);
+    for t in t {
+        let x = LEFT + (((t - t0) / (t1 - t0)) * width as f32) as i64;
+        image.rect(x, TOP + height + 1, 1, TICK, BLACK);
+        let text = label(t, step);
+        let x = x - (text.len() as i64 * ADVANCE - 2) / 2;
+        image.text(x, TOP + height + TICK + 4, &text);
+    }
+    image.text(LEFT + width + 4, TOP + height + TICK + 4, "s");
+
+    let (f0, f1) = extent(frequencies);
+    let (step, f) = ticks(f0, f1);
+    for f in f {
+        let y = TOP + height - 1 - (((f - f0) / (f1 - f0)) * height as f32) as i64;
+        image.rect(LEFT - 1 - TICK, y, TICK, 1, BLACK);
+        let text = label(f, step);
+        let x = LEFT - TICK - 4 - (text.len() as i64 * ADVANCE - 2);
+        image.text(x, y - GLYPH_HEIGHT / 2, &text);
+    }
+    image.text(LEFT - 2 * ADVANCE - 4, TOP + height + TICK + 4, "Hz");
+
+    // colour bar, the same dB range from the bottom up
+    let x = LEFT + width + GAP;
+    for y in 0..height {
+        let rgb = style
+            .colormap
+            .rgb((height - 1 - y) as f32 / (height - 1).max(1) as f32);
+        image.rect(x, TOP + y, BAR, 1, rgb);
+    }
+    let (step, levels) = ticks(bottom, top);
+    for l in levels {
+        let y = TOP + height - 1 - (level(l) * (height - 1) as f32) as i64;
+        image.rect(x + BAR, y, TICK, 1, BLACK);
+        image.text(x + BAR + TICK + 4, y - GLYPH_HEIGHT / 2, &label(l, step));
+    }
+    image.text(x, TOP - GLYPH_HEIGHT - 4, "dB");
+
+    image
+}
@@ -0,0 +1,106 @@
+#[path = "../examples/render/mod.rs"]
+mod render;
+
+use core::f32::consts::PI;
+use dsp::fft::Microfft;
+use dsp::spectrogram::{Colormap, Spectrogram};
+use dsp::spectrum::Sided;
+use dsp::stft::Stft;
+use dsp::window::Window;
+use heapless::consts::{U128, U16};
+use render::{render, spectrogram, Image, Style};
+
+/// Find the plot, the first colour that isn't white, black or grey from the
+/// left and top through the middle
+fn plot_area(image: &Image) -> (u32, u32, u32, u32) {
+    let inside = |x, y| {
+        let [r, g, b] = image.pixel(x, y);
+        !(r == g && g == b)
+    };
+    let y = image.height / 2;
+    let left = (0..image.width).find(|x| inside(*x, y)).unwrap();
+    let right = (left..image.width).find(|x| !inside(*x, y)).unwrap();
+    let x = (left + right) / 2;
+    let top = (0..image.height).find(|y| inside(x, *y)).unwrap();
+    let bottom = (top..image.height).find(|y| !inside(x, *y)).unwrap();
+    (left, right, top, bottom)
+}
+
+#[test]
+fn orientation_and_clipping() {
+    // 4 frames of 8 bins, loud on the lowest bin of the first frame and the
+    // highest of the last, and 80 dB down everywhere else
+    let mut frames = vec![vec![1e-4f32; 8]; 4];
+    frames[0][0] = 1.0;
+    frames[3][7] = 0.1;
+    let times = [0.0, 0.5, 1.0, 1.5];
+    let frequencies = (0..8).map(|k| k as f32 * 100.0).collect::<Vec<_>>();
+
+    let style = Style {
+        dynamic_range: 40.0,
+        width: 64,
+        height: 32,
+        ..Style::default()
+    };
+    let image = render(&frames, &times, &frequencies, &style);
+    assert_eq!(image.rgb.len() as u32, image.width * image.height * 3);
+    let (left, right, top, bottom) = plot_area(&image);
+    assert_eq!((right - left, bottom - top), (64, 32));
+
+    // time goes right and frequency up, the loudest is the top of the scale
+    assert_eq!(image.pixel(left, bottom - 1), Colormap::Viridis.rgb(1.0));
+    assert_eq!(image.pixel(right - 1, top), Colormap::Viridis.rgb(0.5));
+    // the rest is more than 40 dB down and clipped to the bottom
+    assert_eq!(image.pixel(left, top), Colormap::Viridis.rgb(0.0));
+    assert_eq!(
+        image.pixel(right - 1, bottom - 1),
+        Colormap::Viridis.rgb(0.0)
+    );
+
+    // and there are labels in the margins
+    let black = |x0: u32, x1: u32, y0: u32, y1: u32| {
+        (y0..y1).any(|y| (x0..x1).any(|x| image.pixel(x, y) == [0; 3]))
+    };
+    assert!(black(0, left - 6, top, bottom));
+    assert!(black(left, right, bottom + 6, image.height));
+    assert!(black(right + 40, image.width, top, bottom));
+}
+
+#[test]
+fn silence() {
+    // nothing at all doesn't divide by zero, it's all the bottom of the scale
+    let frames = [[0.0f32; 4]; 2];
+    let style = Style {
+        colormap: Colormap::Magma,
+        width: 32,
+        height: 32,
+        ..Style::default()
+    };
+    let image = render(&frames, &[0.0, 1.0], &[0.0, 1.0, 2.0, 3.0], &style);
+    let (left, right, top, bottom) = plot_area(&image);
+    assert_eq!((right - left, bottom - top), (32, 32));
+    for y in top..bottom {
+        for x in left..right {
+            assert_eq!(image.pixel(x, y), Colormap::Magma.rgb(0.0));
+        }
+    }
+}
+
+#[test]
+fn spectrogram_matches_its_frames() {
+    // the chirp from 4_10, the image is drawn from the frames the same way up
+    let chirp = (0..1024)
+        .map(|n| {
+            let n = n as f32;
+            (PI * n * n / (2.0 * 1023.0)).cos()
+        })
+        .collect::<Vec<f32>>();
+    let mut stft = Stft::<Microfft, U16, U128>::new(Window::Hamming, 8);
+    stft.forward(&chirp).unwrap();
+    let s = Spectrogram::<U16, U128>::new(&stft, 8000.0, Sided::One);
+    let style = Style::default();
+    assert_eq!(
+        spectrogram(&s, &style),
+        render(s.frames(), s.times(), s.frequencies(), &style)
+    );
+}
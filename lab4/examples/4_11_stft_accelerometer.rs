@@ -16,21 +16,20 @@
 use panic_break as _;
 use stm32f4xx_hal as hal;
 
-use cmsis_dsp_sys::{arm_cfft_f32, arm_cfft_sR_f32_len16, arm_cmplx_mag_f32};
-use core::f32::consts::PI;
-use cty::uint32_t;
+// links the CMSIS-DSP library dsp::fft::Cmsis calls into
+use cmsis_dsp_sys as _;
+use dsp::fft::{Cfft, Cmsis};
+use dsp::spectrum::Spectrum;
+use dsp::window::Window;
+use dsp::Complex32;
 use hal::{prelude::*, spi, stm32};
-use itertools::Itertools;
 use lis3dsh::{accelerometer::RawAccelerometer, Lis3dsh};
 use micromath::F32Ext;
 use rtt_target::{rprintln, rtt_init_print};
-use typenum::{Sum, Unsigned};
+use typenum::Unsigned;
 
 type N = heapless::consts::U1024;
 type WINDOW = heapless::consts::U16;
-type WINDOWCOMPLEX = Sum<WINDOW, WINDOW>;
-//todo derive this from WINDOW
-const WINDOW_CONST: usize = 16;
 
 #[cortex_m_rt::entry]
 fn main() -> ! {
@@ -87,8 +86,10 @@ fn main() -> ! {
 
     rprintln!("computing");
 
-    let hamming = (0..WINDOW::to_usize())
-        .map(|m| 0.54 - 0.46 * (2.0 * PI * m as f32 / WINDOW::to_usize() as f32).cos());
+    // worked out once, the window is f64 inside and that's soft float here
+    let hamming = Window::Hamming
+        .periodic(WINDOW::to_usize())
+        .collect::<heapless::Vec<f32, WINDOW>>();
 
     // get 64 input at a time, overlapping 32
     // windowing is easier to do on slices
@@ -98,28 +99,22 @@ fn main() -> ! {
         inc: WINDOW::to_usize() / 2,
     };
 
-    let mut xst: heapless::Vec<[f32; WINDOW_CONST], N> = heapless::Vec::new();
-
-    let mut mag = [0f32; WINDOW_CONST];
+    let mut xst: heapless::Vec<heapless::Vec<f32, WINDOW>, N> = heapless::Vec::new();
 
     for chirp_win in overlapping_chirp_windows {
         // 64-0=64 of input to 64-64=0, so input * chirp.rev
         let mut dtfsecoef = hamming
-            .clone()
+            .iter()
             .zip(chirp_win.iter().rev())
-            .map(|(v, x)| v * x)
-            .interleave_shortest(core::iter::repeat(0.0))
-            .collect::<heapless::Vec<f32, WINDOWCOMPLEX>>();
-
-        unsafe {
-            //Finding the FFT of window
-            arm_cfft_f32(&arm_cfft_sR_f32_len16, dtfsecoef.as_mut_ptr(), 0, 1);
-            arm_cmplx_mag_f32(
-                dtfsecoef.as_ptr(),
-                mag.as_mut_ptr(),
-                WINDOW_CONST as uint32_t,
-            );
-        }
+            .map(|(v, x)| Complex32 { re: v * x, im: 0.0 })
+            .collect::<heapless::Vec<Complex32, WINDOW>>();
+
+        //Finding the FFT of window
+        Cmsis::cfft(&mut dtfsecoef);
+
+        let mut mag = heapless::Vec::<f32, WINDOW>::new();
+        mag.resize(WINDOW::to_usize(), 0.0).unwrap();
+        Cmsis::magnitude(&dtfsecoef, &mut mag);
 
         xst.push(mag).ok();
     }
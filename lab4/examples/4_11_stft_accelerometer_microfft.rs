@@ -16,11 +16,12 @@
 use panic_break as _;
 use stm32f4xx_hal as hal;
 
-use core::f32::consts::PI;
+use dsp::fft::{Cfft, Microfft};
+use dsp::spectrum::Spectrum;
+use dsp::window::Window;
+use dsp::Complex32;
 use hal::{prelude::*, spi, stm32};
 use lis3dsh::{accelerometer::RawAccelerometer, Lis3dsh};
-use microfft::{complex::cfft_16, Complex32};
-use micromath::F32Ext;
 use rtt_target::{rprintln, rtt_init_print};
 use typenum::Unsigned;
 
@@ -83,8 +84,10 @@ fn main() -> ! {
 
     rprintln!("computing");
 
-    let hamming = (0..WINDOW::to_usize())
-        .map(|m| 0.54 - 0.46 * (2.0 * PI * m as f32 / WINDOW::to_usize() as f32).cos());
+    // worked out once, the window is f64 inside and that's soft float here
+    let hamming = Window::Hamming
+        .periodic(WINDOW::to_usize())
+        .collect::<heapless::Vec<f32, WINDOW>>();
 
     // get 64 input at a time, overlapping 32
     // windowing is easier to do on slices
@@ -97,19 +100,18 @@ fn main() -> ! {
     let xst = overlapping_chirp_windows
         .map(|chirp_win| {
             let mut dtfsecoef = hamming
-                .clone()
+                .iter()
                 .zip(chirp_win.iter().rev())
                 .map(|(v, x)| Complex32 { re: v * x, im: 0.0 })
                 .collect::<heapless::Vec<Complex32, WINDOW>>();
 
-            // todo pick the right size based on WINDOW
-            let _ = cfft_16(&mut dtfsecoef[..]);
+            Microfft::cfft(&mut dtfsecoef);
 
             // Magnitude calculation
-            dtfsecoef
-                .iter()
-                .map(|complex| (complex.re * complex.re + complex.im * complex.im).sqrt())
-                .collect::<heapless::Vec<f32, WINDOW>>()
+            let mut mag = heapless::Vec::<f32, WINDOW>::new();
+            mag.resize(WINDOW::to_usize(), 0.0).unwrap();
+            Microfft::magnitude(&dtfsecoef, &mut mag);
+            mag
         })
         .collect::<heapless::Vec<heapless::Vec<_, WINDOW>, NDIV2>>();
 
@@ -13,18 +13,15 @@
 use panic_break as _;
 use stm32f4xx_hal as hal;
 
-use cmsis_dsp_sys::{arm_cfft_f32, arm_cfft_sR_f32_len512, arm_cmplx_mult_cmplx_f32};
-use cty::uint32_t;
+// links the CMSIS-DSP library dsp::fft::Cmsis calls into
+use cmsis_dsp_sys as _;
+use dsp::fft::{multiply, Cmsis, Rfft};
 use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, stm32};
-use itertools::Itertools;
 use micromath::F32Ext;
 use rtt_target::{rprintln, rtt_init_print};
-use typenum::{Sum, Unsigned};
+use typenum::Unsigned;
 
 type N = heapless::consts::U512;
-type NCOMPLEX = Sum<N, N>;
-//todo derive this from N
-const N_CONST: usize = 512;
 
 const W1: f32 = core::f32::consts::PI / 128.0;
 const W2: f32 = core::f32::consts::PI / 4.0;
@@ -48,56 +45,33 @@ fn main() -> ! {
     // Create a delay abstraction based on DWT cycle counter
     let dwt = cp.DWT.constrain(cp.DCB, clocks);
 
-    // Complex sum of sinusoidal signals
+    // Sum of sinusoidal signals, real so the real FFT will do
     let s1 = (0..N::to_usize()).map(|val| (W1 * val as f32).sin());
     let s2 = (0..N::to_usize()).map(|val| (W2 * val as f32).sin());
-
-    //we wont use complex this time since, but just interleave the zeros for the imaginary part
-    let mut s_complex = s1
+    let mut y = s1
         .zip(s2)
         .map(|(ess1, ess2)| ess1 + ess2)
-        .interleave_shortest(core::iter::repeat(0.0))
-        .collect::<heapless::Vec<f32, NCOMPLEX>>();
-
-    // Complex impulse response of filter
-    let mut df_complex = H
-        .iter()
-        .cloned()
-        .interleave_shortest(core::iter::repeat(0.0))
-        .chain(core::iter::repeat(0.0))
-        //fill rest with zeros up to N*2
-        .take(NCOMPLEX::to_usize())
-        .collect::<heapless::Vec<f32, NCOMPLEX>>();
+        .collect::<heapless::Vec<f32, N>>();
 
-    // Finding the FFT of the filter
-    unsafe {
-        arm_cfft_f32(&arm_cfft_sR_f32_len512, df_complex.as_mut_ptr(), 0, 1);
-    }
+    // Impulse response of filter, the rest is filled with zeros up to N
+    let mut df = H.iter().cloned().collect::<heapless::Vec<f32, N>>();
 
-    let mut y_complex = [0f32; N_CONST * 2];
+    // Finding the FFT of the filter
+    let df_freq = Cmsis::rfft(&mut df);
 
     let time: ClockDuration = dwt.measure(|| {
-        // Finding the FFT of the input signal
-        unsafe {
-            arm_cfft_f32(&arm_cfft_sR_f32_len512, s_complex.as_mut_ptr(), 0, 1);
-        }
-
-        // Filtering in the frequency domain
-        unsafe {
-            arm_cmplx_mult_cmplx_f32(
-                s_complex.as_ptr(),
-                df_complex.as_ptr(),
-                y_complex.as_mut_ptr(),
-                N_CONST as uint32_t,
-            );
-        }
-
-        // Finding the complex result in time domain
-        unsafe {
-            arm_cfft_f32(&arm_cfft_sR_f32_len512, y_complex.as_mut_ptr(), 1, 1);
-        }
+        // Finding the FFT of the input signal and filtering in the frequency
+        // domain
+        multiply(Cmsis::rfft(&mut y), df_freq);
+
+        // Finding the result in time domain
+        Cmsis::irfft(&mut y);
     });
-    rprintln!("dft ticks: {:?}", time.as_ticks());
+    rprintln!(
+        "dft ticks: {:?}, {} bytes per buffer",
+        time.as_ticks(),
+        core::mem::size_of_val(&y)
+    );
 
     // signal to probe-run to exit
     loop {
@@ -13,9 +13,8 @@
 use panic_break as _;
 use stm32f4xx_hal as hal;
 
-use core::f32::consts::PI;
+use dsp::fft::{multiply, Microfft, Rfft};
 use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, stm32};
-use microfft::{complex::cfft_512, Complex32};
 use micromath::F32Ext;
 use rtt_target::{rprintln, rtt_init_print};
 use typenum::Unsigned;
@@ -44,51 +43,33 @@ fn main() -> ! {
     // Create a delay abstraction based on DWT cycle counter
     let dwt = cp.DWT.constrain(cp.DCB, clocks);
 
-    // Complex sum of sinusoidal signals
+    // Sum of sinusoidal signals, real so the real FFT will do
     let s1 = (0..N::to_usize()).map(|val| (W1 * val as f32).sin());
     let s2 = (0..N::to_usize()).map(|val| (W2 * val as f32).sin());
-    let s = s1.zip(s2).map(|(ess1, ess2)| ess1 + ess2);
-
-    let mut s_complex = s
-        .map(|f| Complex32 { re: f, im: 0.0 })
-        .collect::<heapless::Vec<Complex32, N>>();
-
-    // Complex impulse response of filter
-    let mut df_complex = H
-        .iter()
-        .cloned()
-        .map(|f| Complex32 { re: f, im: 0.0 })
-        .chain(core::iter::repeat(Complex32 { re: 0.0, im: 0.0 }))
-        //fill rest with zeros up to N
-        .take(N::to_usize())
-        .collect::<heapless::Vec<Complex32, N>>();
+    let mut y = s1
+        .zip(s2)
+        .map(|(ess1, ess2)| ess1 + ess2)
+        .collect::<heapless::Vec<f32, N>>();
+
+    // Impulse response of filter, the rest is filled with zeros up to N
+    let mut df = H.iter().cloned().collect::<heapless::Vec<f32, N>>();
 
     // Finding the FFT of the filter
-    let _ = cfft_512(&mut df_complex[..]);
+    let df_freq = Microfft::rfft(&mut df);
 
     let time: ClockDuration = dwt.measure(|| {
-        // Finding the FFT of the input signal
-        let _ = cfft_512(&mut s_complex[..]);
-
-        // Filtering in the frequency domain
-        let y_complex = s_complex
-            .iter()
-            .zip(df_complex.iter())
-            //multiply complex
-            .map(|(s, df)| Complex32 {
-                re: s.re * df.re - s.im * df.im,
-                im: s.re * df.im + s.im * df.re,
-            });
-
-        // Finding the complex result in time domain
-        // supposed to be inverse transform but microfft doesnt have it
-        // Could patch it in. inverse DFT is the same as the DFT, but with the
-        // opposite sign in the exponent and a 1/N factor, any FFT algorithm can
-        // easily be adapted for it.
-        // just dtfse approx instead for now
-        let _y_freq = dtfse::<N, _>(y_complex.clone(), 15).collect::<heapless::Vec<f32, N>>();
+        // Finding the FFT of the input signal and filtering in the frequency
+        // domain
+        multiply(Microfft::rfft(&mut y), df_freq);
+
+        // Finding the result in time domain
+        Microfft::irfft(&mut y);
     });
-    rprintln!("dft ticks: {:?}", time.as_ticks());
+    rprintln!(
+        "dft ticks: {:?}, {} bytes per buffer",
+        time.as_ticks(),
+        core::mem::size_of_val(&y)
+    );
 
     // signal to probe-run to exit
     loop {
@@ -106,22 +87,3 @@ static H: &[f32] = &[
     0.002912, 0.002698, 0.002499, 0.002313, 0.002141, 0.001981, 0.001833, 0.001695, 0.001567,
     0.001448,
 ];
-
-fn dtfse<N: Unsigned, I: Iterator<Item = Complex32> + Clone>(
-    coeff: I,
-    k_var: usize,
-) -> impl Iterator<Item = f32> {
-    let size = N::to_usize() as f32;
-    (0..N::to_usize()).map(move |n| {
-        coeff
-            .clone()
-            .take(k_var + 1)
-            .enumerate()
-            .map(|(k, complex)| {
-                let a = (complex.re * complex.re + complex.im * complex.im).sqrt();
-                let p = complex.im.atan2(complex.re);
-                a * ((2.0 * PI * k as f32 * n as f32 / size) + p).cos() / size
-            })
-            .sum::<f32>()
-    })
-}
@@ -0,0 +1,102 @@
+//! This project is used for comparing the Goertzel algorithm against the FFT
+//! when only a few frequencies matter. The input is the same sum of two
+//! sinusoidal signals as 4_5. A Goertzel bank evaluates the two tone bins, a
+//! bin in between and one between bins, then a real FFT of the whole block is
+//! calculated for the same bins. Both are timed with the DWT cycle counter and
+//! the magnitudes and phases printed.
+//!
+//! The Goertzel bank only ever needs the current sample, so the same thing can
+//! run sample by sample with `process` from the sampling interrupt instead.
+//!
+//! Requires `cargo install probe-run`
+//! `cargo run --release --example 4_16_goertzel`
+
+#![no_std]
+#![no_main]
+
+use panic_break as _;
+use stm32f4xx_hal as hal;
+
+use dsp::fft::{Microfft, Rfft};
+use dsp::goertzel::{magnitude, phase, Bank};
+use dsp::spectrum::bins;
+use dsp::Complex32;
+use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, stm32};
+use micromath::F32Ext;
+use rtt_target::{rprintln, rtt_init_print};
+use typenum::Unsigned;
+
+type N = heapless::consts::U512;
+type M = heapless::consts::U4;
+
+const W1: f32 = core::f32::consts::PI / 128.0;
+const W2: f32 = core::f32::consts::PI / 4.0;
+
+// bins of a 512 point DFT, W1 is bin 2 and W2 bin 64
+const K: [f32; 4] = [2.0, 64.0, 17.0, 100.5];
+
+#[cortex_m_rt::entry]
+fn main() -> ! {
+    rtt_init_print!(BlockIfFull, 128);
+
+    let dp = stm32::Peripherals::take().unwrap();
+    let cp = cortex_m::peripheral::Peripherals::take().unwrap();
+
+    // Set up the system clock.
+    let rcc = dp.RCC.constrain();
+
+    let clocks = rcc
+        .cfgr
+        .use_hse(8.mhz()) //discovery board has 8 MHz crystal for HSE
+        .sysclk(168.mhz())
+        .freeze();
+
+    // Create a delay abstraction based on DWT cycle counter
+    let dwt = cp.DWT.constrain(cp.DCB, clocks);
+
+    // Sum of sinusoidal signals
+    let s1 = (0..N::to_usize()).map(|val| (W1 * val as f32).sin());
+    let s2 = (0..N::to_usize()).map(|val| (W2 * val as f32).sin());
+    let s = s1
+        .zip(s2)
+        .map(|(ess1, ess2)| ess1 + ess2)
+        .collect::<heapless::Vec<f32, N>>();
+
+    let mut bank = Bank::<M>::new(&K, N::to_usize());
+    let mut goertzel = heapless::Vec::<Complex32, M>::new();
+    let goertzel_time: ClockDuration = dwt.measure(|| {
+        goertzel = bank.block(&s);
+    });
+
+    let mut x = s.clone();
+    let mut fft = heapless::Vec::<Complex32, M>::new();
+    let fft_time: ClockDuration = dwt.measure(|| {
+        let packed: &[Complex32] = Microfft::rfft(&mut x);
+        // the FFT only has whole bins, 100.5 falls back to 100
+        fft = K
+            .iter()
+            .map(|k| bins(packed).nth(*k as usize).unwrap())
+            .collect();
+    });
+
+    rprintln!(
+        "goertzel: {} ticks, rfft: {} ticks",
+        goertzel_time.as_ticks(),
+        fft_time.as_ticks()
+    );
+    for ((k, g), f) in K.iter().zip(goertzel.iter()).zip(fft.iter()) {
+        rprintln!(
+            "bin {}: goertzel {} {}, rfft {} {}",
+            k,
+            magnitude(*g),
+            phase(*g),
+            magnitude(*f),
+            phase(*f)
+        );
+    }
+
+    // signal to probe-run to exit
+    loop {
+        cortex_m::asm::bkpt()
+    }
+}
@@ -0,0 +1,121 @@
+//! This project is used for explaining DFTs of lengths that aren't a power of
+//! two. Here we sample the same 1000 accelerometer samples as
+//! accelerometer_usage_ii, with the sampling period set as 10 milliseconds.
+//! The FFTs only come in powers of two, so the buffer would have to be cut to
+//! 512 or padded to 1024 samples. Instead the chirp-z transform computes the
+//! 1000 point DFT exactly, using 2048 point FFTs internally, and then zooms in
+//! on the band from 0 to 5 Hz in 0.025 Hz steps. The magnitudes and the
+//! frequency of the largest component are printed.
+//!
+//! Requires `cargo install probe-run`
+//! `cargo run --release --example 4_17_czt_accelerometer`
+
+#![no_std]
+#![no_main]
+
+use panic_break as _;
+use stm32f4xx_hal as hal;
+
+use dsp::czt::Czt;
+use dsp::fft::Microfft;
+use dsp::spectrum::Spectrum;
+use dsp::Complex32;
+use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, spi, stm32};
+use lis3dsh::{accelerometer::RawAccelerometer, Lis3dsh};
+use rtt_target::{rprintln, rtt_init_print};
+
+const N: usize = 1000;
+// sampling frequency in Hz
+const FS: f32 = 100.0;
+
+// at least 2N - 1 for the DFT
+type L = heapless::consts::U2048;
+
+#[cortex_m_rt::entry]
+fn main() -> ! {
+    rtt_init_print!(BlockIfFull, 128);
+
+    let dp = stm32::Peripherals::take().unwrap();
+    let cp = cortex_m::peripheral::Peripherals::take().unwrap();
+
+    // Set up the system clock.
+    let rcc = dp.RCC.constrain();
+
+    let clocks = rcc
+        .cfgr
+        .use_hse(8.mhz()) //discovery board has 8 MHz crystal for HSE
+        .sysclk(168.mhz())
+        .freeze();
+
+    let mut delay = hal::delay::Delay::new(cp.SYST, clocks);
+
+    // Create a delay abstraction based on DWT cycle counter
+    let dwt = cp.DWT.constrain(cp.DCB, clocks);
+
+    let gpioa = dp.GPIOA.split();
+    let gpioe = dp.GPIOE.split();
+
+    let sck = gpioa.pa5.into_alternate_af5().internal_pull_up(false);
+    let miso = gpioa.pa6.into_alternate_af5().internal_pull_up(false);
+    let mosi = gpioa.pa7.into_alternate_af5().internal_pull_up(false);
+
+    let spi_mode = spi::Mode {
+        polarity: spi::Polarity::IdleLow,
+        phase: spi::Phase::CaptureOnFirstTransition,
+    };
+
+    let spi = spi::Spi::spi1(
+        dp.SPI1,
+        (sck, miso, mosi),
+        spi_mode,
+        10.mhz().into(),
+        clocks,
+    );
+
+    let chip_select = gpioe.pe3.into_push_pull_output();
+    let mut lis3dsh = Lis3dsh::new_spi(spi, chip_select);
+    lis3dsh.init(&mut delay).unwrap();
+
+    let mut accel = [0f32; N];
+    accel.iter_mut().for_each(|accel_ref| {
+        while !lis3dsh.is_data_ready().unwrap() {}
+        *accel_ref = lis3dsh.accel_raw().unwrap()[0] as f32;
+    });
+
+    // Each Czt holds three 2048 point buffers, about 48 KB, so there's only
+    // ever one and it lives in a static along with the output rather than on
+    // main's stack. 1000 point DFT first, the bins are 0.1 Hz apart.
+    let czt = cortex_m::singleton!(: Czt<Microfft, L> = Czt::dft(N).unwrap()).unwrap();
+    let out = cortex_m::singleton!(: heapless::Vec<Complex32, L> = heapless::Vec::new()).unwrap();
+
+    let time: ClockDuration = dwt.measure(|| {
+        czt.transform_real(&accel, out);
+    });
+    rprintln!("dft ticks: {:?}", time.as_ticks());
+
+    let mut dft_mag = heapless::Vec::<f32, heapless::consts::U512>::new();
+    dft_mag.resize(N / 2 + 1, 0.0).unwrap();
+    Microfft::magnitude(&out[..N / 2 + 1], &mut dft_mag);
+    rprintln!("dft mag: {:?}", dft_mag);
+
+    // 201 points from 0 to 5 Hz, four times finer than the bins
+    *czt = Czt::zoom(N, 201, 0.0, 0.025, FS).unwrap();
+    let time: ClockDuration = dwt.measure(|| {
+        czt.transform_real(&accel, out);
+    });
+    rprintln!("zoom ticks: {:?}", time.as_ticks());
+
+    let mut zoom_mag = heapless::Vec::<f32, heapless::consts::U256>::new();
+    zoom_mag.resize(out.len(), 0.0).unwrap();
+    Microfft::magnitude(out, &mut zoom_mag);
+
+    // skip DC, which is mostly gravity
+    let peak = (1..zoom_mag.len()).fold(1, |m, k| if zoom_mag[k] > zoom_mag[m] { k } else { m });
+    rprintln!("zoom mag: {:?}", zoom_mag);
+    rprintln!("largest component at {} Hz", czt.frequency(peak, FS));
+
+    // signal to probe-run to exit
+    loop {
+        cortex_m::asm::bkpt()
+    }
+}
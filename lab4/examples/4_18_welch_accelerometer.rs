@@ -0,0 +1,110 @@
+//! This project is used for explaining power spectral density estimation on
+//! real-world signals. Here we sample 2048 accelerometer samples with the
+//! sampling period set as 10 milliseconds, like fft_accelerometer. A single
+//! FFT of the capture gives a very noisy spectrum, every bin is off by about
+//! as much as its own value. The periodogram of the first 256 samples shows
+//! that, then Welch's method averages 15 Hann windowed segments of 256 samples
+//! overlapping by half, which brings the spread down by about 3.5 times. Both
+//! are in raw accelerometer units squared per Hz.
+//!
+//! Requires `cargo install probe-run`
+//! `cargo run --release --example 4_18_welch_accelerometer`
+
+#![no_std]
+#![no_main]
+
+use panic_break as _;
+use stm32f4xx_hal as hal;
+
+use dsp::fft::Microfft;
+use dsp::psd::{Detrend, Welch};
+use dsp::window::Window;
+use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, spi, stm32};
+use lis3dsh::{accelerometer::RawAccelerometer, Lis3dsh};
+use rtt_target::{rprintln, rtt_init_print};
+use typenum::Unsigned;
+
+const N: usize = 2048;
+// sampling frequency in Hz
+const FS: f32 = 100.0;
+
+type SEGMENT = heapless::consts::U256;
+type BINS = heapless::consts::U129;
+
+#[cortex_m_rt::entry]
+fn main() -> ! {
+    rtt_init_print!(BlockIfFull, 128);
+
+    let dp = stm32::Peripherals::take().unwrap();
+    let cp = cortex_m::peripheral::Peripherals::take().unwrap();
+
+    // Set up the system clock.
+    let rcc = dp.RCC.constrain();
+
+    let clocks = rcc
+        .cfgr
+        .use_hse(8.mhz()) //discovery board has 8 MHz crystal for HSE
+        .sysclk(168.mhz())
+        .freeze();
+
+    let mut delay = hal::delay::Delay::new(cp.SYST, clocks);
+
+    // Create a delay abstraction based on DWT cycle counter
+    let dwt = cp.DWT.constrain(cp.DCB, clocks);
+
+    let gpioa = dp.GPIOA.split();
+    let gpioe = dp.GPIOE.split();
+
+    let sck = gpioa.pa5.into_alternate_af5().internal_pull_up(false);
+    let miso = gpioa.pa6.into_alternate_af5().internal_pull_up(false);
+    let mosi = gpioa.pa7.into_alternate_af5().internal_pull_up(false);
+
+    let spi_mode = spi::Mode {
+        polarity: spi::Polarity::IdleLow,
+        phase: spi::Phase::CaptureOnFirstTransition,
+    };
+
+    let spi = spi::Spi::spi1(
+        dp.SPI1,
+        (sck, miso, mosi),
+        spi_mode,
+        10.mhz().into(),
+        clocks,
+    );
+
+    let chip_select = gpioe.pe3.into_push_pull_output();
+    let mut lis3dsh = Lis3dsh::new_spi(spi, chip_select);
+    lis3dsh.init(&mut delay).unwrap();
+
+    let mut accel = [0f32; N];
+    accel.iter_mut().for_each(|accel_ref| {
+        while !lis3dsh.is_data_ready().unwrap() {}
+        *accel_ref = lis3dsh.accel_raw().unwrap()[0] as f32;
+    });
+
+    let mut psd = heapless::Vec::<f32, BINS>::new();
+    psd.resize(BINS::to_usize(), 0.0).unwrap();
+
+    // gravity is a big offset on DC, take the mean out of both
+    let periodogram = Welch::<Microfft, SEGMENT>::new(Window::Hann, 0, Detrend::Mean, FS);
+    periodogram.estimate(&accel[..SEGMENT::to_usize()], &mut psd);
+    rprintln!("periodogram: {:?}", psd);
+
+    let welch = Welch::<Microfft, SEGMENT>::new(Window::Hann, 128, Detrend::Mean, FS);
+    let mut segments = 0;
+    let time: ClockDuration = dwt.measure(|| {
+        segments = welch.estimate(&accel, &mut psd);
+    });
+    rprintln!("welch ticks: {:?}", time.as_ticks());
+    rprintln!(
+        "welch of {} segments, {} Hz bins: {:?}",
+        segments,
+        welch.resolution(),
+        psd
+    );
+
+    // signal to probe-run to exit
+    loop {
+        cortex_m::asm::bkpt()
+    }
+}
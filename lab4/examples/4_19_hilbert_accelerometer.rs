@@ -0,0 +1,122 @@
+//! This project is used for explaining envelopes and instantaneous frequency
+//! on real-world signals. Here we sample 1024 accelerometer samples with the
+//! sampling period set as 10 milliseconds, like fft_accelerometer. The
+//! analytic signal of the whole capture is worked out with the FFT, and the
+//! envelope and instantaneous frequency read off it. Then the same samples go
+//! through a 63 tap FIR Hilbert transformer one at a time like they would from
+//! the sampling interrupt, which comes out 31 samples behind, and the cost per
+//! sample is measured.
+//!
+//! Requires `cargo install probe-run`
+//! `cargo run --release --example 4_19_hilbert_accelerometer`
+
+#![no_std]
+#![no_main]
+
+use panic_break as _;
+use stm32f4xx_hal as hal;
+
+use dsp::fft::Cmsis;
+use dsp::hilbert::{analytic, envelope, frequency, Fir};
+use dsp::psd::{detrend, Detrend};
+use dsp::window::Window;
+use dsp::Complex32;
+use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, spi, stm32};
+use lis3dsh::{accelerometer::RawAccelerometer, Lis3dsh};
+use rtt_target::{rprintln, rtt_init_print};
+
+const N: usize = 1024;
+// sampling frequency in Hz
+const FS: f32 = 100.0;
+
+type LEN = heapless::consts::U1024;
+type TAPS = heapless::consts::U63;
+
+#[cortex_m_rt::entry]
+fn main() -> ! {
+    rtt_init_print!(BlockIfFull, 128);
+
+    let dp = stm32::Peripherals::take().unwrap();
+    let cp = cortex_m::peripheral::Peripherals::take().unwrap();
+
+    // Set up the system clock.
+    let rcc = dp.RCC.constrain();
+
+    let clocks = rcc
+        .cfgr
+        .use_hse(8.mhz()) //discovery board has 8 MHz crystal for HSE
+        .sysclk(168.mhz())
+        .freeze();
+
+    let mut delay = hal::delay::Delay::new(cp.SYST, clocks);
+
+    // Create a delay abstraction based on DWT cycle counter
+    let dwt = cp.DWT.constrain(cp.DCB, clocks);
+
+    let gpioa = dp.GPIOA.split();
+    let gpioe = dp.GPIOE.split();
+
+    let sck = gpioa.pa5.into_alternate_af5().internal_pull_up(false);
+    let miso = gpioa.pa6.into_alternate_af5().internal_pull_up(false);
+    let mosi = gpioa.pa7.into_alternate_af5().internal_pull_up(false);
+
+    let spi_mode = spi::Mode {
+        polarity: spi::Polarity::IdleLow,
+        phase: spi::Phase::CaptureOnFirstTransition,
+    };
+
+    let spi = spi::Spi::spi1(
+        dp.SPI1,
+        (sck, miso, mosi),
+        spi_mode,
+        10.mhz().into(),
+        clocks,
+    );
+
+    let chip_select = gpioe.pe3.into_push_pull_output();
+    let mut lis3dsh = Lis3dsh::new_spi(spi, chip_select);
+    lis3dsh.init(&mut delay).unwrap();
+
+    let mut accel = [0f32; N];
+    accel.iter_mut().for_each(|accel_ref| {
+        while !lis3dsh.is_data_ready().unwrap() {}
+        *accel_ref = lis3dsh.accel_raw().unwrap()[0] as f32;
+    });
+
+    // gravity is a big offset on DC, which would swamp the envelope
+    detrend(&mut accel, Detrend::Mean);
+
+    let z = analytic::<Cmsis, LEN>(&accel);
+    let envelopes = z
+        .iter()
+        .map(|z| envelope(*z))
+        .collect::<heapless::Vec<f32, LEN>>();
+    rprintln!("envelope: {:?}", envelopes);
+    let frequencies = z
+        .windows(2)
+        .map(|z| frequency(z[0], z[1], FS))
+        .collect::<heapless::Vec<f32, LEN>>();
+    rprintln!("instantaneous frequency (Hz): {:?}", frequencies);
+
+    let mut fir = Fir::<TAPS>::new(Window::Hamming);
+    let mut streamed = heapless::Vec::<Complex32, LEN>::new();
+    let time: ClockDuration = dwt.measure(|| {
+        for x in accel.iter() {
+            streamed.push(fir.process(*x)).ok();
+        }
+    });
+    rprintln!("fir ticks per sample: {:?}", time.as_ticks() / N as u32);
+    rprintln!(
+        "fir envelope, {} samples behind: {:?}",
+        fir.delay(),
+        streamed
+            .iter()
+            .map(|z| envelope(*z))
+            .collect::<heapless::Vec<f32, LEN>>()
+    );
+
+    // signal to probe-run to exit
+    loop {
+        cortex_m::asm::bkpt()
+    }
+}
@@ -0,0 +1,100 @@
+//! This project is used for explaining cross-correlation and time delay
+//! estimation. The input is 256 samples of a sum of four sinusoidal signals at
+//! frequencies that don't share a period, and a copy of it 10.5 samples later.
+//! The two tones of 4_5 wouldn't do, W2 repeats every 8 samples so a delay of
+//! 2.5 looks just as good as 10.5 and the overlap favours it. Their linear
+//! cross-correlation is calculated three ways, with direct sums, through 512
+//! point FFTs and with CMSIS arm_correlate_f32, each timed with the DWT cycle
+//! counter. The delay is then read off the correlation peak, a parabola
+//! through the peak and its neighbours finds the half sample.
+//!
+//! Requires `cargo install probe-run`
+//! `cargo run --release --example 4_20_correlation`
+
+#![no_std]
+#![no_main]
+
+use panic_break as _;
+use stm32f4xx_hal as hal;
+
+use dsp::correlation::{correlate, correlate_cmsis, correlate_fft, delay, Scale};
+use dsp::fft::Cmsis;
+use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, stm32};
+use micromath::F32Ext;
+use rtt_target::{rprintln, rtt_init_print};
+use typenum::Unsigned;
+
+type N = heapless::consts::U256;
+// 2N - 1 lags, and the FFT length that holds them
+type LAGS = heapless::consts::U511;
+type FFT = heapless::consts::U512;
+
+// frequencies in cycles per sample, and phases
+const TONES: [(f32, f32); 4] = [(0.013, 0.3), (0.047, 1.9), (0.081, 4.0), (0.112, 2.5)];
+
+const DELAY: f32 = 10.5;
+
+#[cortex_m_rt::entry]
+fn main() -> ! {
+    rtt_init_print!(BlockIfFull, 128);
+
+    let dp = stm32::Peripherals::take().unwrap();
+    let cp = cortex_m::peripheral::Peripherals::take().unwrap();
+
+    // Set up the system clock.
+    let rcc = dp.RCC.constrain();
+
+    let clocks = rcc
+        .cfgr
+        .use_hse(8.mhz()) //discovery board has 8 MHz crystal for HSE
+        .sysclk(168.mhz())
+        .freeze();
+
+    // Create a delay abstraction based on DWT cycle counter
+    let dwt = cp.DWT.constrain(cp.DCB, clocks);
+
+    // Sum of sinusoidal signals, and the same DELAY samples later
+    let s = |t: f32| {
+        TONES
+            .iter()
+            .map(|(f, p)| (2.0 * core::f32::consts::PI * f * t + p).cos())
+            .sum::<f32>()
+    };
+    let x = (0..N::to_usize())
+        .map(|n| s(n as f32))
+        .collect::<heapless::Vec<f32, N>>();
+    let y = (0..N::to_usize())
+        .map(|n| s(n as f32 - DELAY))
+        .collect::<heapless::Vec<f32, N>>();
+
+    let mut r = heapless::Vec::<f32, LAGS>::new();
+    r.resize(LAGS::to_usize(), 0.0).unwrap();
+
+    let direct_time: ClockDuration = dwt.measure(|| {
+        correlate(&y, &x, Scale::Biased, &mut r);
+    });
+    let fft_time: ClockDuration = dwt.measure(|| {
+        correlate_fft::<Cmsis, FFT>(&y, &x, Scale::Biased, &mut r);
+    });
+    let cmsis_time: ClockDuration = dwt.measure(|| {
+        correlate_cmsis(&y, &x, Scale::Biased, &mut r);
+    });
+    rprintln!(
+        "direct: {} ticks, fft: {} ticks, arm_correlate_f32: {} ticks",
+        direct_time.as_ticks(),
+        fft_time.as_ticks(),
+        cmsis_time.as_ticks()
+    );
+    rprintln!("r: {:?}", r);
+
+    rprintln!(
+        "delay: {} samples, actually {}",
+        delay::<Cmsis, FFT>(&x, &y),
+        DELAY
+    );
+
+    // signal to probe-run to exit
+    loop {
+        cortex_m::asm::bkpt()
+    }
+}
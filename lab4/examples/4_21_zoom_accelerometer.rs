@@ -0,0 +1,113 @@
+//! This project is used for explaining the zoom FFT on real-world signals.
+//! Here we sample the accelerometer with the sampling period set as 10
+//! milliseconds, like fft_accelerometer. A 256 point FFT of that covers 0 to
+//! 50 Hz in 0.39 Hz bins, too coarse to separate modes that are close
+//! together. The zoom FFT mixes the band around CENTER down to DC, low-passes
+//! and keeps every 4th sample, and a 256 point FFT of those covers 12.5 Hz
+//! around CENTER in 0.098 Hz bins instead. The samples go in one at a time as
+//! they arrive, and the zoom with all of its buffers lives in a static rather
+//! than on the stack.
+//!
+//! Requires `cargo install probe-run`
+//! `cargo run --release --example 4_21_zoom_accelerometer`
+
+#![no_std]
+#![no_main]
+
+use panic_break as _;
+use stm32f4xx_hal as hal;
+
+use dsp::fft::Cmsis;
+use dsp::window::Window;
+use dsp::zoom::Zoom;
+use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, spi, stm32};
+use lis3dsh::{accelerometer::RawAccelerometer, Lis3dsh};
+use micromath::F32Ext;
+use rtt_target::{rprintln, rtt_init_print};
+
+// sampling frequency in Hz
+const FS: f32 = 100.0;
+
+// the band of interest in Hz
+const CENTER: f32 = 25.0;
+const DECIMATION: usize = 4;
+
+type N = heapless::consts::U256;
+type TAPS = heapless::consts::U65;
+
+#[cortex_m_rt::entry]
+fn main() -> ! {
+    rtt_init_print!(BlockIfFull, 128);
+
+    let dp = stm32::Peripherals::take().unwrap();
+    let cp = cortex_m::peripheral::Peripherals::take().unwrap();
+
+    // Set up the system clock.
+    let rcc = dp.RCC.constrain();
+
+    let clocks = rcc
+        .cfgr
+        .use_hse(8.mhz()) //discovery board has 8 MHz crystal for HSE
+        .sysclk(168.mhz())
+        .freeze();
+
+    let mut delay = hal::delay::Delay::new(cp.SYST, clocks);
+
+    // Create a delay abstraction based on DWT cycle counter
+    let dwt = cp.DWT.constrain(cp.DCB, clocks);
+
+    let gpioa = dp.GPIOA.split();
+    let gpioe = dp.GPIOE.split();
+
+    let sck = gpioa.pa5.into_alternate_af5().internal_pull_up(false);
+    let miso = gpioa.pa6.into_alternate_af5().internal_pull_up(false);
+    let mosi = gpioa.pa7.into_alternate_af5().internal_pull_up(false);
+
+    let spi_mode = spi::Mode {
+        polarity: spi::Polarity::IdleLow,
+        phase: spi::Phase::CaptureOnFirstTransition,
+    };
+
+    let spi = spi::Spi::spi1(
+        dp.SPI1,
+        (sck, miso, mosi),
+        spi_mode,
+        10.mhz().into(),
+        clocks,
+    );
+
+    let chip_select = gpioe.pe3.into_push_pull_output();
+    let mut lis3dsh = Lis3dsh::new_spi(spi, chip_select);
+    lis3dsh.init(&mut delay).unwrap();
+
+    let zoom = cortex_m::singleton!(: Zoom<Cmsis, N, TAPS> =
+        Zoom::new(CENTER, FS, DECIMATION, Window::Hann))
+    .unwrap();
+    rprintln!(
+        "{} samples for {} Hz bins",
+        zoom.samples_needed(),
+        zoom.resolution()
+    );
+
+    loop {
+        while !lis3dsh.is_data_ready().unwrap() {}
+        let accel = lis3dsh.accel_raw().unwrap()[0] as f32;
+        if zoom.push(accel) {
+            break;
+        }
+    }
+
+    let mut spectrum = heapless::Vec::new();
+    let time: ClockDuration = dwt.measure(|| {
+        spectrum = zoom.spectrum();
+    });
+    rprintln!("ticks: {:?}", time.as_ticks());
+    for (f, z) in zoom.frequencies().zip(spectrum.iter()) {
+        rprintln!("{} Hz: {}", f, z.norm_sqr().sqrt());
+    }
+
+    // signal to probe-run to exit
+    loop {
+        cortex_m::asm::bkpt()
+    }
+}
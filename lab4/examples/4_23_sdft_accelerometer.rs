@@ -0,0 +1,103 @@
+//! This project is used for explaining the sliding DFT on real-world signals.
+//! Here we sample the accelerometer with the sampling period set as 10
+//! milliseconds, like fft_accelerometer. stft_accelerometer has to wait for a
+//! whole block before there's any spectrum at all. The sliding DFT keeps a
+//! few bins of a 256 point DFT up to date on every sample instead, each one
+//! costing the same handful of multiplies whatever the DFT length, so it can
+//! be done as each sample arrives. The modulated version is used so the bins
+//! don't drift however long it runs. Every so often the bins are printed with
+//! how many ticks the last update took.
+//!
+//! Requires `cargo install probe-run`
+//! `cargo run --release --example 4_23_sdft_accelerometer`
+
+#![no_std]
+#![no_main]
+
+use panic_break as _;
+use stm32f4xx_hal as hal;
+
+use dsp::sdft::{Sdft, Window};
+use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, spi, stm32};
+use lis3dsh::{accelerometer::RawAccelerometer, Lis3dsh};
+use micromath::F32Ext;
+use rtt_target::{rprintln, rtt_init_print};
+
+// sampling frequency in Hz
+const FS: f32 = 100.0;
+
+// the frequencies to watch in Hz
+const WATCH: [f32; 4] = [2.0, 5.0, 10.0, 20.0];
+
+// print every this many samples
+const REPORT: usize = 50;
+
+type N = heapless::consts::U256;
+type M = heapless::consts::U4;
+
+#[cortex_m_rt::entry]
+fn main() -> ! {
+    rtt_init_print!(BlockIfFull, 128);
+
+    let dp = stm32::Peripherals::take().unwrap();
+    let cp = cortex_m::peripheral::Peripherals::take().unwrap();
+
+    // Set up the system clock.
+    let rcc = dp.RCC.constrain();
+
+    let clocks = rcc
+        .cfgr
+        .use_hse(8.mhz()) //discovery board has 8 MHz crystal for HSE
+        .sysclk(168.mhz())
+        .freeze();
+
+    let mut delay = hal::delay::Delay::new(cp.SYST, clocks);
+
+    // Create a delay abstraction based on DWT cycle counter
+    let dwt = cp.DWT.constrain(cp.DCB, clocks);
+
+    let gpioa = dp.GPIOA.split();
+    let gpioe = dp.GPIOE.split();
+
+    let sck = gpioa.pa5.into_alternate_af5().internal_pull_up(false);
+    let miso = gpioa.pa6.into_alternate_af5().internal_pull_up(false);
+    let mosi = gpioa.pa7.into_alternate_af5().internal_pull_up(false);
+
+    let spi_mode = spi::Mode {
+        polarity: spi::Polarity::IdleLow,
+        phase: spi::Phase::CaptureOnFirstTransition,
+    };
+
+    let spi = spi::Spi::spi1(
+        dp.SPI1,
+        (sck, miso, mosi),
+        spi_mode,
+        10.mhz().into(),
+        clocks,
+    );
+
+    let chip_select = gpioe.pe3.into_push_pull_output();
+    let mut lis3dsh = Lis3dsh::new_spi(spi, chip_select);
+    lis3dsh.init(&mut delay).unwrap();
+
+    let sdft = cortex_m::singleton!(: Sdft<N, M> =
+        Sdft::frequencies(&WATCH, FS, Window::Hann))
+    .unwrap();
+
+    let mut countdown = REPORT;
+    loop {
+        while !lis3dsh.is_data_ready().unwrap() {}
+        let accel = lis3dsh.accel_raw().unwrap()[0] as f32;
+
+        let time: ClockDuration = dwt.measure(|| sdft.process(accel));
+
+        countdown -= 1;
+        if countdown == 0 {
+            countdown = REPORT;
+            rprintln!("ticks: {:?} full: {}", time.as_ticks(), sdft.is_full());
+            for (f, z) in WATCH.iter().zip(sdft.bins().iter()) {
+                rprintln!("{} Hz: {}", f, z.norm_sqr().sqrt());
+            }
+        }
+    }
+}
@@ -16,18 +16,17 @@
 use panic_break as _;
 use stm32f4xx_hal as hal;
 
-use cmsis_dsp_sys::{arm_cfft_f32, arm_cfft_sR_f32_len256, arm_cmplx_mag_f32};
-use cty::uint32_t;
+// links the CMSIS-DSP library dsp::fft::Cmsis calls into
+use cmsis_dsp_sys as _;
+use dsp::fft::{Cfft, Cmsis};
+use dsp::spectrum::Spectrum;
+use dsp::Complex32;
 use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, stm32};
-use itertools::Itertools;
 use micromath::F32Ext;
 use rtt_target::{rprintln, rtt_init_print};
-use typenum::{Sum, Unsigned};
+use typenum::Unsigned;
 
 type N = heapless::consts::U256;
-type NCOMPLEX = Sum<N, N>;
-///todo derive this from N
-const N_CONST: usize = 256;
 
 const W1: f32 = core::f32::consts::PI / 128.0;
 const W2: f32 = core::f32::consts::PI / 4.0;
@@ -56,21 +55,23 @@ fn main() -> ! {
     let s1 = (0..N::to_usize()).map(|val| (W1 * val as f32).sin());
     let s2 = (0..N::to_usize()).map(|val| (W2 * val as f32).sin());
 
-    //we wont use complex this time since, but just interleave the zeros for the imaginary part
     let mut s = s1
         .zip(s2)
-        .map(|(ess1, ess2)| ess1 + ess2)
-        .interleave_shortest(core::iter::repeat(0.0))
-        .collect::<heapless::Vec<f32, NCOMPLEX>>();
+        .map(|(ess1, ess2)| Complex32 {
+            re: ess1 + ess2,
+            im: 0.0,
+        })
+        .collect::<heapless::Vec<Complex32, N>>();
 
-    let mut mag = [0f32; N_CONST];
+    let mut mag = heapless::Vec::<f32, N>::new();
+    mag.resize(N::to_usize(), 0.0).unwrap();
 
-    let time: ClockDuration = dwt.measure(|| unsafe {
+    let time: ClockDuration = dwt.measure(|| {
         //CFFT calculation
-        arm_cfft_f32(&arm_cfft_sR_f32_len256, s.as_mut_ptr(), 0, 1);
+        Cmsis::cfft(&mut s);
 
-        // Magnitude calculation
-        arm_cmplx_mag_f32(s.as_ptr(), mag.as_mut_ptr(), N::to_usize() as uint32_t);
+        // Magnitude calculation, arm_cmplx_mag_f32
+        Cmsis::magnitude(&s, &mut mag);
     });
     rprintln!("ticks: {:?}", time.as_ticks());
 
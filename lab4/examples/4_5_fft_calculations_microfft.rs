@@ -16,8 +16,10 @@
 use panic_break as _;
 use stm32f4xx_hal as hal;
 
+use dsp::fft::{Cfft, Microfft};
+use dsp::spectrum::Spectrum;
+use dsp::Complex32;
 use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, stm32};
-use microfft::{complex::cfft_256, Complex32};
 use micromath::F32Ext;
 use rtt_target::{rprintln, rtt_init_print};
 use typenum::Unsigned;
@@ -57,15 +59,15 @@ fn main() -> ! {
         .map(|f| Complex32 { re: f, im: 0.0 })
         .collect::<heapless::Vec<Complex32, N>>();
 
+    let mut mag = heapless::Vec::<f32, N>::new();
+    mag.resize(N::to_usize(), 0.0).unwrap();
+
     let time: ClockDuration = dwt.measure(|| {
         //CFFT calculation
-        let _ = cfft_256(&mut dtfsecoef[..]);
+        Microfft::cfft(&mut dtfsecoef);
 
         // Magnitude calculation
-        let _mag = dtfsecoef
-            .iter()
-            .map(|complex| (complex.re * complex.re + complex.im * complex.im).sqrt())
-            .collect::<heapless::Vec<f32, N>>();
+        Microfft::magnitude(&dtfsecoef, &mut mag);
     });
     rprintln!("ticks: {:?}", time.as_ticks());
 
@@ -6,6 +6,9 @@
 //! the output signal is calculated with the arm_cmplx_mag_f32 function. The
 //! result is saved in the Mag array.
 //!
+//! The accelerometer signal is real so the same magnitudes also come out of a
+//! real FFT, in half the memory and about half the cycles. Both are timed.
+//!
 //! Requires `cargo install probe-run`
 //! `cargo run --release --example 4_6_fft_accelerometer`
 
@@ -15,19 +18,18 @@
 use panic_break as _;
 use stm32f4xx_hal as hal;
 
-use cmsis_dsp_sys::{arm_cfft_f32, arm_cfft_sR_f32_len512, arm_cmplx_mag_f32};
-use cty::uint32_t;
-use hal::{prelude::*, spi, stm32};
-use itertools::Itertools;
+// links the CMSIS-DSP library dsp::fft::Cmsis calls into
+use cmsis_dsp_sys as _;
+use dsp::fft::{Cfft, Cmsis, Rfft};
+use dsp::spectrum::Spectrum;
+use dsp::Complex32;
+use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, spi, stm32};
 use lis3dsh::{accelerometer::RawAccelerometer, Lis3dsh};
 use micromath::F32Ext;
 use rtt_target::{rprintln, rtt_init_print};
-use typenum::{Sum, Unsigned};
+use typenum::Unsigned;
 
 type N = heapless::consts::U512;
-type NCOMPLEX = Sum<N, N>;
-//todo derive this from N
-const N_CONST: usize = 512;
 
 #[cortex_m_rt::entry]
 fn main() -> ! {
@@ -47,6 +49,9 @@ fn main() -> ! {
 
     let mut delay = hal::delay::Delay::new(cp.SYST, clocks);
 
+    // Create a delay abstraction based on DWT cycle counter
+    let dwt = cp.DWT.constrain(cp.DCB, clocks);
+
     let gpioa = dp.GPIOA.split();
     let gpioe = dp.GPIOE.split();
 
@@ -72,31 +77,48 @@ fn main() -> ! {
     lis3dsh.init(&mut delay).unwrap();
 
     // dont love the idea of delaying in an iterator ...
-    let dtfsecoef = (0..N::to_usize()).map(|_| {
-        while !lis3dsh.is_data_ready().unwrap() {}
-        let dat = lis3dsh.accel_raw().unwrap();
-        dat[0] as f32
-    });
-
-    let mut dtfsecoef = dtfsecoef
-        .interleave_shortest(core::iter::repeat(0.0))
-        .collect::<heapless::Vec<f32, NCOMPLEX>>();
+    let accel = (0..N::to_usize())
+        .map(|_| {
+            while !lis3dsh.is_data_ready().unwrap() {}
+            let dat = lis3dsh.accel_raw().unwrap();
+            dat[0] as f32
+        })
+        .collect::<heapless::Vec<f32, N>>();
+
+    let mut dtfsecoef = accel
+        .iter()
+        .map(|f| Complex32 { re: *f, im: 0.0 })
+        .collect::<heapless::Vec<Complex32, N>>();
+
+    let mut mag = heapless::Vec::<f32, N>::new();
+    mag.resize(N::to_usize(), 0.0).unwrap();
+
+    let cfft_time: ClockDuration = dwt.measure(|| {
+        //CFFT calculation
+        Cmsis::cfft(&mut dtfsecoef);
 
-    let mut mag = [0f32; N_CONST];
+        // Magnitude calculation, arm_cmplx_mag_f32
+        Cmsis::magnitude(&dtfsecoef, &mut mag);
+    });
 
-    unsafe {
-        //CFFT calculation
-        arm_cfft_f32(&arm_cfft_sR_f32_len512, dtfsecoef.as_mut_ptr(), 0, 1);
-
-        // Magnitude calculation
-        arm_cmplx_mag_f32(
-            dtfsecoef.as_ptr(),
-            mag.as_mut_ptr(),
-            N::to_usize() as uint32_t,
-        );
-    }
+    // Only the N/2 + 1 bins from DC to Nyquist, the rest mirror them
+    let mut x = accel.clone();
+    let mut half_mag = heapless::Vec::<f32, N>::new();
+    half_mag.resize(N::to_usize() / 2 + 1, 0.0).unwrap();
+    let rfft_time: ClockDuration = dwt.measure(|| {
+        let packed = Cmsis::rfft(&mut x);
+        Cmsis::magnitude_packed(packed, &mut half_mag);
+    });
 
-    rprintln!("mag: {:?}", mag);
+    rprintln!(
+        "cfft: {} bytes {} ticks, rfft: {} bytes {} ticks",
+        core::mem::size_of_val(&dtfsecoef),
+        cfft_time.as_ticks(),
+        core::mem::size_of_val(&x),
+        rfft_time.as_ticks()
+    );
+    rprintln!("mag: {:?}", &mag[..N::to_usize() / 2 + 1]);
+    rprintln!("rfft mag: {:?}", half_mag);
 
     // signal to probe-run to exit
     loop {
@@ -6,6 +6,9 @@
 //! the output signal is calculated with the arm_cmplx_mag_f32 function. The
 //! result is saved in the Mag array.
 //!
+//! The accelerometer signal is real so the same magnitudes also come out of a
+//! real FFT, in half the memory and about half the cycles. Both are timed.
+//!
 //! Requires `cargo install probe-run`
 //! `cargo run --release --example 4_6_fft_accelerometer_microfft`
 
@@ -15,10 +18,11 @@
 use panic_break as _;
 use stm32f4xx_hal as hal;
 
-use hal::{prelude::*, spi, stm32};
+use dsp::fft::{Cfft, Microfft, Rfft};
+use dsp::spectrum::Spectrum;
+use dsp::Complex32;
+use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, spi, stm32};
 use lis3dsh::{accelerometer::RawAccelerometer, Lis3dsh};
-use microfft::{complex::cfft_512, Complex32};
-use micromath::F32Ext;
 use rtt_target::{rprintln, rtt_init_print};
 use typenum::Unsigned;
 
@@ -42,6 +46,9 @@ fn main() -> ! {
 
     let mut delay = hal::delay::Delay::new(cp.SYST, clocks);
 
+    // Create a delay abstraction based on DWT cycle counter
+    let dwt = cp.DWT.constrain(cp.DCB, clocks);
+
     let gpioa = dp.GPIOA.split();
     let gpioe = dp.GPIOE.split();
 
@@ -67,27 +74,46 @@ fn main() -> ! {
     lis3dsh.init(&mut delay).unwrap();
 
     // dont love the idea of delaying in an iterator ...
-    let mut dtfsecoef = (0..N::to_usize())
+    let accel = (0..N::to_usize())
         .map(|_| {
             while !lis3dsh.is_data_ready().unwrap() {}
             let dat = lis3dsh.accel_raw().unwrap();
-
-            Complex32 {
-                re: dat[0] as f32,
-                im: 0.0,
-            }
+            dat[0] as f32
         })
-        .collect::<heapless::Vec<Complex32, N>>();
-
-    let _ = cfft_512(&mut dtfsecoef[..]);
+        .collect::<heapless::Vec<f32, N>>();
 
-    // Magnitude calculation
-    let mag = dtfsecoef
+    let mut dtfsecoef = accel
         .iter()
-        .map(|complex| (complex.re * complex.re + complex.im * complex.im).sqrt())
-        .collect::<heapless::Vec<f32, N>>();
+        .map(|f| Complex32 { re: *f, im: 0.0 })
+        .collect::<heapless::Vec<Complex32, N>>();
 
-    rprintln!("mag: {:?}", mag);
+    let mut mag = heapless::Vec::<f32, N>::new();
+    mag.resize(N::to_usize(), 0.0).unwrap();
+    let cfft_time: ClockDuration = dwt.measure(|| {
+        Microfft::cfft(&mut dtfsecoef);
+
+        // Magnitude calculation
+        Microfft::magnitude(&dtfsecoef, &mut mag);
+    });
+
+    // Only the N/2 + 1 bins from DC to Nyquist, the rest mirror them
+    let mut x = accel.clone();
+    let mut half_mag = heapless::Vec::<f32, N>::new();
+    half_mag.resize(N::to_usize() / 2 + 1, 0.0).unwrap();
+    let rfft_time: ClockDuration = dwt.measure(|| {
+        let packed = Microfft::rfft(&mut x);
+        Microfft::magnitude_packed(packed, &mut half_mag);
+    });
+
+    rprintln!(
+        "cfft: {} bytes {} ticks, rfft: {} bytes {} ticks",
+        core::mem::size_of_val(&dtfsecoef),
+        cfft_time.as_ticks(),
+        core::mem::size_of_val(&x),
+        rfft_time.as_ticks()
+    );
+    rprintln!("mag: {:?}", &mag[..N::to_usize() / 2 + 1]);
+    rprintln!("rfft mag: {:?}", half_mag);
 
     // signal to probe-run to exit
     loop {
@@ -14,11 +14,10 @@
 use panic_break as _;
 use stm32f4xx_hal as hal;
 
-use cmsis_dsp_sys::{arm_cfft_f32, arm_cfft_sR_f32_len16, arm_cos_f32};
-use core::f32::consts::PI;
-use cty::c_float;
+use dsp::fft::{Cfft, Cmsis};
+use dsp::fourier::Series;
+use dsp::Complex32;
 use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, stm32};
-use micromath::F32Ext;
 use rtt_target::{rprintln, rtt_init_print};
 use typenum::Unsigned;
 
@@ -52,48 +51,23 @@ fn main() -> ! {
         .collect::<heapless::Vec<Complex32, N>>();
 
     //Coefficient calculation with CFFT function
-    unsafe {
-        arm_cfft_f32(
-            &arm_cfft_sR_f32_len16,
-            dtfsecoef.as_mut_ptr() as *mut c_float,
-            0,
-            1,
-        );
-    }
+    Cmsis::cfft(&mut dtfsecoef);
+
+    // the harmonics, negative ones included, from the coefficients
+    let series = Series::<N>::from_fft(&dtfsecoef);
 
+    // one complex multiply per harmonic per sample, no trig
     let time: ClockDuration = dwt.measure(|| {
-        let _y_real =
-            dtfse::<N, _>(dtfsecoef.iter().cloned(), 15).collect::<heapless::Vec<f32, N>>();
+        let _y_real = series
+            .synthesize(N::to_usize(), |k| k <= 15)
+            .take(N::to_usize())
+            .collect::<heapless::Vec<f32, N>>();
     });
     rprintln!("ticks: {:?}", time.as_ticks());
+    rprintln!("thd: {:?}", series.thd());
 
     // signal to probe-run to exit
     loop {
         cortex_m::asm::bkpt()
     }
 }
-
-fn dtfse<N: Unsigned, I: Iterator<Item = Complex32> + Clone>(
-    coeff: I,
-    k_var: usize,
-) -> impl Iterator<Item = f32> {
-    let size = N::to_usize() as f32;
-    (0..N::to_usize()).map(move |n| {
-        coeff
-            .clone()
-            .take(k_var + 1)
-            .enumerate()
-            .map(|(k, complex)| {
-                let a = (complex.re * complex.re + complex.im * complex.im).sqrt();
-                let p = complex.im.atan2(complex.re);
-                unsafe { a * arm_cos_f32((2.0 * PI * k as f32 * n as f32 / size) + p) / size }
-            })
-            .sum::<f32>()
-    })
-}
-
-#[derive(Clone)]
-struct Complex32 {
-    re: f32,
-    im: f32,
-}
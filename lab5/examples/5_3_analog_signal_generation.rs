@@ -3,6 +3,9 @@
 //! signal generation. Then, the signal is generated by triggering DAC in timer
 //! interrupt subroutine using these tables. Here, the signal waveform can be
 //! changed by pressing onboard push button of the STM32F4 Discovery kit.  
+//! Each press moves to the next preset, the square wave also goes through
+//! cookbook biquads for some tone shaping. Switching presets ramps the filter
+//! coefficients so the output doesn't click.
 //!
 //! Requires `cargo install cargo-embed`
 //! `cargo embed --example 5_3_analog_signal_generation`
@@ -16,20 +19,49 @@ use stm32f4xx_hal as hal;
 use core::cell::RefCell;
 use core::f32::consts::PI;
 use core::ops::DerefMut;
-use core::sync::atomic::{AtomicBool, Ordering};
+use core::sync::atomic::{AtomicUsize, Ordering};
 use cortex_m::interrupt::{free, Mutex};
+use dsp::biquad::{Biquad, Coefficients, Kind, Params};
 use hal::dac::{DacOut, DacPin};
 use hal::gpio::{gpioa::PA0, Edge, ExtiPin, Input, PullDown};
 use hal::timer::Timer;
 use hal::{interrupt, prelude::*, stm32};
 use micromath::F32Ext;
 use nb::block;
+use rtt_target::{rprintln, rtt_init_print};
 use typenum::Unsigned;
 
 type N = heapless::consts::U160;
 
 static BUTTON: Mutex<RefCell<Option<PA0<Input<PullDown>>>>> = Mutex::new(RefCell::new(None));
-static FLAG: AtomicBool = AtomicBool::new(true);
+static PRESET: AtomicUsize = AtomicUsize::new(0);
+
+const FS: f32 = 16000.0;
+// 20ms at 16khz
+const RAMP: u32 = 320;
+const PRESETS: usize = 5;
+
+/// (use the sine table, filter applied to it)
+fn preset(index: usize) -> (bool, Option<Params>) {
+    let params = |kind, f0, q| {
+        Some(Params {
+            kind,
+            f0,
+            q,
+            fs: FS,
+        })
+    };
+    match index {
+        0 => (true, None),
+        1 => (false, None),
+        // round the edges off
+        2 => (false, params(Kind::LowPass, 800.0, 0.707)),
+        // pull up the 5th harmonic
+        3 => (false, params(Kind::Peaking(12.0), 500.0, 4.0)),
+        // thin it out
+        _ => (false, params(Kind::LowShelf(-12.0), 300.0, 0.707)),
+    }
+}
 
 #[cortex_m_rt::entry]
 fn main() -> ! {
@@ -85,21 +117,34 @@ fn main() -> ! {
     // im not sure if you can create and start twice?
     block!(timer.wait()).unwrap();
 
+    let mut filter = Biquad::new(Coefficients::IDENTITY);
+    let mut current = None;
+    // input scale so boosts don't clip, full scale unless the filter has
+    // gain above 0dB, eased towards the new preset's along with the ramp
+    let mut headroom = 1.0;
+    let mut target = 1.0;
+
     loop {
         // little wiggly because not an interrupt..
-        let sin = FLAG.load(Ordering::Relaxed);
-        if sin {
-            for n in 0..N::to_usize() {
-                dac.set_value(sin_lookup[n]);
-                timer.start(16.khz());
-                block!(timer.wait()).unwrap();
-            }
-        } else {
-            for n in 0..N::to_usize() {
-                dac.set_value(sq_lookup[n]);
-                timer.start(16.khz());
-                block!(timer.wait()).unwrap();
-            }
+        let index = PRESET.load(Ordering::Relaxed);
+        let (sin, params) = preset(index);
+        if current != Some(index) {
+            rprintln!("preset {} {:?}", index, params);
+            let coeffs = params.map_or(Coefficients::IDENTITY, |p| p.coefficients());
+            filter.retune(coeffs, RAMP);
+            target = 1.0 / coeffs.peak_gain(256).max(1.0);
+            current = Some(index);
+        }
+
+        let lookup = if sin { &sin_lookup } else { &sq_lookup };
+        for n in 0..N::to_usize() {
+            headroom += (target - headroom) / RAMP as f32;
+            let x = (lookup[n] as f32 - 2048.0) / 2048.0 * headroom;
+            let y = filter.process(x);
+            let value = (y * 2048.0 + 2048.0).max(0.0).min(4095.0);
+            dac.set_value(value as u16);
+            timer.start(16.khz());
+            block!(timer.wait()).unwrap();
         }
     }
 }
@@ -113,7 +158,7 @@ fn EXTI0() {
         if let Some(ref mut btn) = btn_ref.deref_mut() {
             btn.clear_interrupt_pending_bit();
         }
-        let flag = FLAG.load(Ordering::Relaxed);
-        FLAG.store(!flag, Ordering::Relaxed);
+        let preset = PRESET.load(Ordering::Relaxed);
+        PRESET.store((preset + 1) % PRESETS, Ordering::Relaxed);
     });
 }